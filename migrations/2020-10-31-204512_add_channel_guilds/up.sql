@@ -0,0 +1,13 @@
+ALTER TABLE channels ADD COLUMN guild_id bigint;
+
+-- Existing rows don't record which guild their channel is in, so there is no
+-- way to fill in the new column.  Drop them, leaving those channels to fall
+-- back to their guild's default mode until they are marked again.
+DELETE FROM channel_modes WHERE channel_id IN (
+  SELECT id FROM channels WHERE guild_id IS NULL
+);
+DELETE FROM channels WHERE guild_id IS NULL;
+
+ALTER TABLE channels ALTER COLUMN guild_id SET NOT NULL;
+
+CREATE INDEX ON channels(guild_id);
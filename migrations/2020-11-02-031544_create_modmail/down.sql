@@ -0,0 +1,2 @@
+DROP TABLE IF EXISTS modmail_tickets;
+DROP TABLE IF EXISTS modmail_inboxes;
@@ -0,0 +1,19 @@
+CREATE EXTENSION IF NOT EXISTS "uuid-ossp";
+
+CREATE TABLE modmail_inboxes (
+  guild_id   bigint PRIMARY KEY NOT NULL,
+  channel_id bigint NOT NULL
+);
+
+CREATE TABLE modmail_tickets (
+  id        uuid PRIMARY KEY NOT NULL DEFAULT uuid_generate_v4(),
+  ticket_id varchar(16) NOT NULL,
+  guild_id  bigint NOT NULL,
+  user_id   bigint NOT NULL,
+  is_open   boolean NOT NULL DEFAULT TRUE,
+  opened_at timestamptz NOT NULL DEFAULT now(),
+
+  UNIQUE(ticket_id)
+);
+
+CREATE INDEX ON modmail_tickets(guild_id);
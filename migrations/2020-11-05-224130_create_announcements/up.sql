@@ -0,0 +1,17 @@
+CREATE EXTENSION IF NOT EXISTS "uuid-ossp";
+
+CREATE TABLE announcements (
+  id              uuid PRIMARY KEY NOT NULL DEFAULT uuid_generate_v4(),
+  announcement_id varchar(16) NOT NULL,
+  guild_id        bigint NOT NULL,
+  channel_id      bigint NOT NULL,
+  message         text NOT NULL,
+  next_run        timestamptz NOT NULL,
+  repeat_secs     bigint,
+  is_paused       boolean NOT NULL DEFAULT FALSE,
+
+  UNIQUE(announcement_id)
+);
+
+CREATE INDEX ON announcements(guild_id);
+CREATE INDEX ON announcements(next_run);
@@ -0,0 +1,10 @@
+CREATE EXTENSION IF NOT EXISTS "uuid-ossp";
+
+CREATE TABLE custom_commands (
+  id       uuid PRIMARY KEY NOT NULL DEFAULT uuid_generate_v4(),
+  guild_id bigint NOT NULL,
+  name     varchar(32) NOT NULL,
+  response text NOT NULL,
+
+  UNIQUE(guild_id, name)
+);
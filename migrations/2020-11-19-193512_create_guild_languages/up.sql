@@ -0,0 +1,4 @@
+CREATE TABLE guild_languages (
+  guild_id bigint PRIMARY KEY NOT NULL,
+  language text NOT NULL
+);
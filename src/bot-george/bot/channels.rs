@@ -1,17 +1,28 @@
-use super::roles::NoPermissionError;
+use super::{
+    language::Language,
+    roles,
+    roles::{NoPermissionError, Role},
+    UNKNOWN_ALIAS,
+};
 use crate::{
-    db::{models::Channel, DbPool},
+    commands::{ArgContext, ArgResolveError},
+    db::{
+        models::{Channel, DisplayChannel, NewChannel, NewChannelMode, NewDefaultChannelMode},
+        DbPool,
+    },
     error::Result,
 };
 use anyhow::Context;
 use diesel::{prelude::*, result::Error as DieselError};
 use docbot::{prelude::*, HelpTopic};
+use log::warn;
 use serenity::model::id::{ChannelId, GuildId, UserId};
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
 use thiserror::Error;
+use uuid::Uuid;
 
-// TODO: allow referring to channels by bot-assigned alias?
 #[derive(Docbot, Debug)]
+#[docbot(context = "ArgContext")]
 /// TODO: document `ChannelCommand`
 pub enum ChannelCommand {
     /// help [command]
@@ -20,57 +31,136 @@ pub enum ChannelCommand {
     ///
     /// # Arguments
     /// command: The name of a subcommand to get info for
+    ///
+    /// # Locale fr
+    /// Obtenir de l'aide sur la gestion du comportement dans les salons, ou sur une
+    /// sous-commande
+    ///
+    /// # Arguments
+    /// command: Le nom d'une sous-commande
     Help(Option<ChannelCommandId>),
 
     /// (list|ls)
     /// List the available channel modes
+    ///
+    /// # Locale fr
+    /// Afficher les modes de salon disponibles
     List,
 
     /// show [channel]
     /// Show all channel modes, or list the mode of a given channel
     ///
     /// # Arguments
-    /// channel: The name of a channel to display the mode of.  Must be a valid
-    ///          #mention in order to work
-    Show(Option<ChannelId>),
+    /// channel: The name of a channel to display the mode of
+    ///
+    /// # Locale fr
+    /// Afficher les modes de tous les salons, ou le mode d'un salon
+    ///
+    /// # Arguments
+    /// channel: Le nom du salon dont afficher le mode
+    #[docbot(requires = "mod")]
+    Show(#[docbot(hint = "channel mention or name")] Option<ChannelId>),
 
-    /// default <mode>
+    /// (set default|default) <mode>
     /// Set the default behavior mode for unmarked channels
     ///
     /// # Arguments
-    /// mode: The default mode to use.  Run [`channels ls`]() for a list of
-    ///       valid modes
+    /// mode: The default mode to use
+    ///
+    /// # Locale fr
+    /// Définir le mode de comportement par défaut des salons non marqués
+    ///
+    /// # Arguments
+    /// mode: Le mode à utiliser par défaut
+    #[docbot(requires = "admin")]
     Default(ChannelMode),
 
     /// (mark|set) <channel> <mode>
     /// Change the behavior of the bot for a specific channel
     ///
     /// # Arguments
-    /// channel: The channel to mark.  Must be a valid #mention in order to work
-    /// mode: The mode to mark the channel with.  Run [`channels ls`]() for a
-    ///       list of valid modes
-    Mark(ChannelId, ChannelMode),
+    /// channel: The channel to mark
+    /// mode: The mode to mark the channel with
+    ///
+    /// # Locale fr
+    /// Changer le comportement du bot dans un salon
+    ///
+    /// # Arguments
+    /// channel: Le salon à marquer
+    /// mode: Le mode avec lequel marquer le salon
+    #[docbot(requires = "admin")]
+    Mark(
+        #[docbot(hint = "channel mention or name")] ChannelId,
+        ChannelMode,
+    ),
 
     /// (unmark|clear|reset) <channel>
     /// Clear any channel-specific behavior for a channel, resetting it to the
     /// default
     ///
     /// # Arguments
-    /// channel: The channel to reset.  Must be a valid #mention in order to
-    ///          work
-    Unmark(ChannelId),
+    /// channel: The channel to reset
+    ///
+    /// # Locale fr
+    /// Effacer le comportement propre à un salon, pour revenir au mode par défaut
+    ///
+    /// # Arguments
+    /// channel: Le salon à réinitialiser
+    #[docbot(requires = "admin")]
+    Unmark(#[docbot(hint = "channel mention or name")] ChannelId),
+
+    /// alias <channel> [name]
+    /// Give a channel a name it can be referred to by in commands, or remove
+    /// its current one
+    ///
+    /// # Arguments
+    /// channel: The channel to name
+    /// name: The alias to give the channel.  If omitted, the channel's alias
+    ///       is removed
+    ///
+    /// # Examples
+    /// alias <#123456789012345678> rules
+    ///
+    /// # Locale fr
+    /// Donner à un salon un nom utilisable dans les commandes, ou retirer
+    /// celui qu'il a
+    ///
+    /// # Arguments
+    /// channel: Le salon à nommer
+    /// name: L'alias à donner au salon.  Sans cet argument, l'alias du salon
+    ///       est retiré
+    #[docbot(requires = "admin")]
+    Alias(
+        #[docbot(hint = "channel mention or name")] ChannelId,
+        Option<String>,
+    ),
 }
 
 #[derive(Docbot, Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum ChannelMode {
+    /// normal
+    /// Respond to commands starting with the command prefix
+    ///
+    /// # Locale fr
+    /// Répondre aux commandes commençant par le préfixe de commande
+    Normal,
     /// (disabled|none)
     /// Do not allow the bot to operate in this channel
+    ///
+    /// # Locale fr
+    /// Empêcher le bot de fonctionner dans ce salon
     Disabled,
     /// (announcements|broadcast)
     /// Disable responding to commands, send announcements in this channel
+    ///
+    /// # Locale fr
+    /// Ne pas répondre aux commandes, et envoyer les annonces dans ce salon
     Announcements,
     /// (commands|command-only)
     /// Assume any messages sent in this channel are commands for the bot
+    ///
+    /// # Locale fr
+    /// Considérer chaque message envoyé dans ce salon comme une commande du bot
     Commands,
 }
 
@@ -81,15 +171,18 @@ pub enum ChannelCommandOk {
     List(&'static HelpTopic),
     ShowAll {
         default: ChannelMode,
-        modes: HashMap<Channel, ChannelMode>,
+        modes: HashMap<DisplayChannel, ChannelMode>,
     },
     ShowOne {
         is_default: bool,
-        channel: Channel,
+        channel: DisplayChannel,
         mode: ChannelMode,
     },
+    DefaultSet,
     Marked,
     Unmarked,
+    AliasSet,
+    AliasCleared,
 }
 
 #[derive(Error, Debug)]
@@ -98,6 +191,8 @@ pub enum ChannelCommandError {
     GuildRequired,
     #[error("{0}")]
     NoPermission(#[from] NoPermissionError),
+    #[error("alias {0:?} is already in use")]
+    AliasTaken(String),
     #[error("an unexpected error occurred")]
     Other(#[from] anyhow::Error),
 }
@@ -119,22 +214,485 @@ pub fn get_channel(channel: ChannelId, db: &DbPool) -> Result<Option<Channel>> {
     }
 }
 
+fn find_channel_alias(
+    guild: GuildId,
+    name: &str,
+    db: &DbPool,
+) -> Result<ChannelId, ArgResolveError>
+{
+    use crate::schema::channels::dsl::{alias, channel_id, channels, guild_id};
+
+    if name == UNKNOWN_ALIAS {
+        return Err(ArgResolveError::NotFound("channel", name.into()));
+    }
+
+    let db_conn = db.get().context("failed to connect to the database")?;
+
+    #[allow(clippy::cast_possible_wrap)]
+    let found = channels
+        .filter(guild_id.eq(guild.0 as i64).and(alias.eq(name)))
+        .select(channel_id)
+        .limit(2)
+        .load::<i64>(&db_conn)
+        .context("failed to retrieve channel alias from database")?;
+
+    #[allow(clippy::cast_sign_loss)]
+    match found.as_slice() {
+        [c] => Ok(ChannelId(*c as u64)),
+        [] => Err(ArgResolveError::NotFound("channel", name.into())),
+        _ => Err(ArgResolveError::Ambiguous("channel", name.into())),
+    }
+}
+
+/// Resolve a channel from a mention or ID, a channel name with or without the
+/// leading `#`, or a bot-assigned alias
+impl ParseWith<ArgContext> for ChannelId {
+    type Err = ArgResolveError;
+
+    fn parse_with(ctx: &ArgContext, s: &str) -> Result<Self, ArgResolveError> {
+        if let Ok(id) = s.parse() {
+            return Ok(id);
+        }
+
+        let guild = ctx
+            .guild
+            .as_ref()
+            .ok_or(ArgResolveError::GuildRequired("channel"))?;
+        let name = s.strip_prefix('#').unwrap_or(s);
+
+        let mut found = guild
+            .channels
+            .values()
+            .filter(|c| c.name.eq_ignore_ascii_case(name))
+            .map(|c| c.id);
+
+        match (found.next(), found.next()) {
+            (Some(id), None) => Ok(id),
+            (Some(_), Some(_)) => Err(ArgResolveError::Ambiguous("channel", s.into())),
+            (None, _) => find_channel_alias(guild.id, name, &ctx.db),
+        }
+    }
+}
+
+fn add_channel(
+    channel: ChannelId,
+    guild: GuildId,
+    channel_alias: impl Into<String>,
+    db: &DbPool,
+) -> Result<Channel>
+{
+    use crate::schema::channels::dsl::channels;
+
+    let db_conn = db.get().context("failed to connect to the database")?;
+    let uuid = Uuid::new_v4();
+    let channel_alias = channel_alias.into();
+
+    #[allow(clippy::cast_possible_wrap)]
+    diesel::insert_into(channels)
+        .values(vec![NewChannel {
+            id: uuid,
+            alias: channel_alias.clone(),
+            channel_id: channel.0 as i64,
+            guild_id: guild.0 as i64,
+        }])
+        .execute(&db_conn)
+        .context("failed to insert new channel")?;
+
+    Ok(Channel {
+        id: uuid,
+        alias: channel_alias,
+    })
+}
+
+/// Check whether a channel other than the given one already goes by an alias
+fn alias_taken(channel: ChannelId, guild: GuildId, name: &str, db: &DbPool) -> Result<bool> {
+    use crate::schema::channels::dsl::{alias, channel_id, channels, guild_id};
+
+    if name == UNKNOWN_ALIAS {
+        return Ok(true);
+    }
+
+    let db_conn = db.get().context("failed to connect to the database")?;
+
+    #[allow(clippy::cast_possible_wrap)]
+    diesel::select(diesel::dsl::exists(
+        channels.filter(
+            guild_id
+                .eq(guild.0 as i64)
+                .and(alias.eq(name))
+                .and(channel_id.ne(channel.0 as i64)),
+        ),
+    ))
+    .get_result(&db_conn)
+    .context("failed to query for existing channel aliases")
+}
+
+fn set_alias(channel: &Channel, new_alias: &str, db: &DbPool) -> Result<()> {
+    use crate::schema::channels::dsl::{alias, channels, id};
+
+    let db_conn = db.get().context("failed to connect to the database")?;
+
+    diesel::update(channels.filter(id.eq(channel.id)))
+        .set(alias.eq(new_alias))
+        .execute(&db_conn)
+        .context("failed to update channel alias")?;
+
+    Ok(())
+}
+
+/// Stop tracking a channel once it has neither a mode nor an alias set
+fn delete_if_unused(channel: &Channel, db: &DbPool) -> Result<()> {
+    use crate::schema::{channel_modes, channels};
+
+    let db_conn = db.get().context("failed to connect to the database")?;
+
+    if diesel::select(diesel::dsl::exists(
+        channel_modes::table.filter(channel_modes::channel_id.eq(channel.id)),
+    ))
+    .get_result(&db_conn)
+    .context("failed to query for remaining channel modes")?
+    {
+        return Ok(());
+    }
+
+    diesel::delete(
+        channels::table.filter(
+            channels::id
+                .eq(channel.id)
+                .and(channels::alias.eq(UNKNOWN_ALIAS)),
+        ),
+    )
+    .execute(&db_conn)
+    .context("failed to delete unused channel")?;
+
+    Ok(())
+}
+
+fn parse_modes(channel: &Channel, modes: Vec<String>, db: &DbPool) -> Result<Option<ChannelMode>> {
+    use crate::schema::channel_modes::dsl::{channel_id, channel_modes, mode};
+
+    let mut remove = BTreeSet::new();
+
+    let parsed: Vec<ChannelMode> = modes
+        .into_iter()
+        .filter_map(|m| {
+            m.parse()
+                .map_err(|e| {
+                    warn!("channel mode {:?} couldn't be parsed: {:?}", m, e);
+                    remove.insert(m);
+                })
+                .ok()
+        })
+        .collect();
+
+    if !remove.is_empty() {
+        let db_conn = db.get().context("failed to connect to the database")?;
+
+        warn!(
+            "Removing invalid modes off {:?}: {:?}",
+            channel.alias, remove
+        );
+
+        diesel::delete(channel_modes.filter(channel_id.eq(channel.id).and(mode.eq_any(remove))))
+            .execute(&db_conn)
+            .context("failed to remove broken channel modes")?;
+    }
+
+    Ok(parsed.into_iter().next())
+}
+
+fn get_mode(channel: &Channel, db: &DbPool) -> Result<Option<ChannelMode>> {
+    use crate::schema::channel_modes::dsl::{channel_id, channel_modes, mode};
+
+    let db_conn = db.get().context("failed to connect to the database")?;
+
+    let modes = channel_modes
+        .filter(channel_id.eq(channel.id))
+        .select(mode)
+        .load::<String>(&db_conn)
+        .context("failed to retrieve channel modes from database")?;
+
+    parse_modes(channel, modes, db)
+}
+
+/// Get the default mode for unmarked channels in a guild
+pub fn get_default_mode(guild: GuildId, db: &DbPool) -> Result<ChannelMode> {
+    use crate::schema::default_channel_modes::dsl::{default_channel_modes, guild_id, mode};
+
+    let db_conn = db.get().context("failed to connect to the database")?;
+
+    #[allow(clippy::cast_possible_wrap)]
+    let default = match default_channel_modes
+        .filter(guild_id.eq(guild.0 as i64))
+        .select(mode)
+        .first::<Option<String>>(&db_conn)
+    {
+        Ok(m) => m,
+        Err(DieselError::NotFound) => None,
+        Err(e) => return Err(e).context("failed to retrieve default channel mode"),
+    };
+
+    Ok(default
+        .and_then(|m| {
+            m.parse()
+                .map_err(|e| warn!("default channel mode {:?} couldn't be parsed: {:?}", m, e))
+                .ok()
+        })
+        .unwrap_or(ChannelMode::Normal))
+}
+
+/// Get the mode of a channel, falling back to the guild default if it is
+/// unmarked.  Returns the mode and whether it was the default.
+pub fn get_channel_mode(
+    channel: ChannelId,
+    guild: GuildId,
+    db: &DbPool,
+) -> Result<(ChannelMode, bool)>
+{
+    let mode = get_channel(channel, db)
+        .context("failed to get channel")?
+        .map_or_else(|| Ok(None), |c| get_mode(&c, db))
+        .context("failed to get channel mode")?;
+
+    match mode {
+        Some(m) => Ok((m, false)),
+        None => Ok((
+            get_default_mode(guild, db).context("failed to get default mode")?,
+            true,
+        )),
+    }
+}
+
+fn get_all_modes(guild: GuildId, db: &DbPool) -> Result<HashMap<DisplayChannel, ChannelMode>> {
+    use crate::schema::{channel_modes, channels};
+
+    let db_conn = db.get().context("failed to connect to the database")?;
+
+    #[allow(clippy::cast_possible_wrap)]
+    let rows = channels::table
+        .inner_join(channel_modes::table)
+        .filter(channels::guild_id.eq(guild.0 as i64))
+        .select((
+            channels::id,
+            channels::alias,
+            channels::channel_id,
+            channel_modes::mode,
+        ))
+        .load::<(Uuid, String, i64, String)>(&db_conn)
+        .context("failed to retrieve channel modes from database")?;
+
+    let mut modes: HashMap<_, (Channel, Vec<_>)> = HashMap::new();
+
+    for (id, alias, channel_id, mode) in rows {
+        modes
+            .entry(channel_id)
+            .or_insert_with(|| (Channel { id, alias }, Vec::new()))
+            .1
+            .push(mode);
+    }
+
+    let mut ret = HashMap::new();
+
+    for (channel_id, (channel, modes)) in modes {
+        if let Some(mode) = parse_modes(&channel, modes, db)? {
+            #[allow(clippy::cast_sign_loss)]
+            ret.insert(
+                DisplayChannel {
+                    channel_id: ChannelId(channel_id as u64),
+                    alias: channel.alias,
+                },
+                mode,
+            );
+        }
+    }
+
+    Ok(ret)
+}
+
+fn set_default_mode(guild: GuildId, default: ChannelMode, db: &DbPool) -> Result<()> {
+    use crate::schema::default_channel_modes::dsl::{default_channel_modes, guild_id, mode};
+
+    let db_conn = db.get().context("failed to connect to the database")?;
+    let default = format!("{}", default);
+
+    #[allow(clippy::cast_possible_wrap)]
+    diesel::insert_into(default_channel_modes)
+        .values(vec![NewDefaultChannelMode {
+            guild_id: guild.0 as i64,
+            mode: Some(default.clone()),
+        }])
+        .on_conflict(guild_id)
+        .do_update()
+        .set(mode.eq(default))
+        .execute(&db_conn)
+        .context("failed to set default channel mode")?;
+
+    Ok(())
+}
+
+fn set_mode(channel: &Channel, new_mode: ChannelMode, db: &DbPool) -> Result<()> {
+    use crate::schema::channel_modes::dsl::{channel_id, channel_modes};
+
+    let db_conn = db.get().context("failed to connect to the database")?;
+
+    db_conn
+        .transaction(|| {
+            diesel::delete(channel_modes.filter(channel_id.eq(channel.id))).execute(&db_conn)?;
+
+            diesel::insert_into(channel_modes)
+                .values(vec![NewChannelMode {
+                    channel_id: channel.id,
+                    mode: format!("{}", new_mode),
+                }])
+                .execute(&db_conn)
+        })
+        .context("failed to replace channel mode")?;
+
+    Ok(())
+}
+
+fn delete_mode(channel: &Channel, db: &DbPool) -> Result<()> {
+    use crate::schema::channel_modes::dsl::{channel_id, channel_modes};
+
+    let db_conn = db.get().context("failed to connect to the database")?;
+
+    diesel::delete(channel_modes.filter(channel_id.eq(channel.id)))
+        .execute(&db_conn)
+        .context("failed to delete channel modes")?;
+
+    delete_if_unused(channel, db)
+}
+
 pub fn execute(
     command: ChannelCommand,
     sender: UserId,
     guild: Option<GuildId>,
     db: &DbPool,
     superuser: UserId,
+    lang: Language,
 ) -> ChannelCommandResult<ChannelCommandOk>
 {
+    let is_super = sender == superuser;
+
     let get_guild = || guild.ok_or(ChannelCommandError::GuildRequired);
 
+    let get_sender_roles = |guild| -> ChannelCommandResult<_> {
+        Ok(roles::get_user_roles(sender, guild, db).context("failed to get sender permissions")?)
+    };
+
+    let check_edit = |guild| -> ChannelCommandResult<_> {
+        if !is_super && !get_sender_roles(guild)?.contains(&Role::Admin) {
+            return Err(NoPermissionError::EditChannels.into());
+        }
+
+        Ok(())
+    };
+
     Ok(match command {
-        ChannelCommand::Help(topic) => ChannelCommandOk::Help(ChannelCommand::help(topic)),
-        ChannelCommand::List => ChannelCommandOk::List(ChannelMode::help(None)),
-        ChannelCommand::Show(_target) => todo!(),
-        ChannelCommand::Default(_mode) => todo!(),
-        ChannelCommand::Mark(_target, _mode) => todo!(),
-        ChannelCommand::Unmark(_target) => todo!(),
+        ChannelCommand::Help(topic) => {
+            ChannelCommandOk::Help(ChannelCommand::help_in(topic, lang.code()))
+        },
+        ChannelCommand::List => ChannelCommandOk::List(ChannelMode::help_in(None, lang.code())),
+        ChannelCommand::Show(target) => {
+            let guild = get_guild()?;
+
+            if !is_super && get_sender_roles(guild)?.is_empty() {
+                return Err(NoPermissionError::ShowChannels.into());
+            }
+
+            match target {
+                Some(t) => {
+                    let channel = get_channel(t, db).context("failed to get target")?;
+                    let mode = channel
+                        .as_ref()
+                        .map_or_else(|| Ok(None), |c| get_mode(c, db))
+                        .context("failed to get target mode")?;
+
+                    let (mode, is_default) = match mode {
+                        Some(m) => (m, false),
+                        None => (
+                            get_default_mode(guild, db).context("failed to get default mode")?,
+                            true,
+                        ),
+                    };
+
+                    ChannelCommandOk::ShowOne {
+                        is_default,
+                        channel: DisplayChannel {
+                            alias: channel.map_or_else(|| UNKNOWN_ALIAS.into(), |c| c.alias),
+                            channel_id: t,
+                        },
+                        mode,
+                    }
+                },
+                None => ChannelCommandOk::ShowAll {
+                    default: get_default_mode(guild, db).context("failed to get default mode")?,
+                    modes: get_all_modes(guild, db).context("failed to get channel modes")?,
+                },
+            }
+        },
+        ChannelCommand::Default(mode) => {
+            let guild = get_guild()?;
+            check_edit(guild)?;
+
+            set_default_mode(guild, mode, db).context("failed to set default mode")?;
+
+            ChannelCommandOk::DefaultSet
+        },
+        ChannelCommand::Mark(target, mode) => {
+            let guild = get_guild()?;
+            check_edit(guild)?;
+
+            let channel = get_channel(target, db)
+                .context("failed to get target")?
+                .map_or_else(|| add_channel(target, guild, UNKNOWN_ALIAS, db), Ok)
+                .context("failed to add new channel entry for target")?;
+
+            set_mode(&channel, mode, db).context("failed to set target mode")?;
+
+            ChannelCommandOk::Marked
+        },
+        ChannelCommand::Unmark(target) => {
+            let guild = get_guild()?;
+            check_edit(guild)?;
+
+            if let Some(channel) = get_channel(target, db).context("failed to get target")? {
+                delete_mode(&channel, db).context("failed to reset target mode")?;
+            }
+
+            ChannelCommandOk::Unmarked
+        },
+        ChannelCommand::Alias(target, name) => {
+            let guild = get_guild()?;
+            check_edit(guild)?;
+
+            let channel = get_channel(target, db).context("failed to get target")?;
+
+            if let Some(name) = name {
+                let name = name.strip_prefix('#').unwrap_or(&name);
+
+                if alias_taken(target, guild, name, db).context("failed to check target alias")? {
+                    return Err(ChannelCommandError::AliasTaken(name.into()));
+                }
+
+                match channel {
+                    Some(c) => set_alias(&c, name, db).context("failed to set target alias")?,
+                    None => {
+                        add_channel(target, guild, name, db)
+                            .context("failed to add new channel entry for target")?;
+                    },
+                }
+
+                ChannelCommandOk::AliasSet
+            } else {
+                if let Some(channel) = channel {
+                    set_alias(&channel, UNKNOWN_ALIAS, db)
+                        .context("failed to clear target alias")?;
+                    delete_if_unused(&channel, db).context("failed to clean up target")?;
+                }
+
+                ChannelCommandOk::AliasCleared
+            }
+        },
     })
 }
@@ -0,0 +1,437 @@
+use super::{
+    language::Language,
+    roles,
+    roles::{NoPermissionError, Role},
+};
+use crate::{
+    commands::{ArgContext, BaseCommandId},
+    db::{
+        models::{CustomResponse, NewCustomResponse},
+        DbPool,
+    },
+    error::Result,
+};
+use anyhow::{anyhow, Context};
+use diesel::{
+    prelude::*,
+    result::{DatabaseErrorKind, Error as DieselError},
+};
+use docbot::{prelude::*, DynamicCommand, DynamicCommandSet, HelpTopic, IdParseError};
+use lazy_static::lazy_static;
+use regex::{Captures, Regex};
+use serenity::{
+    model::id::{ChannelId, GuildId, UserId},
+    utils::MessageBuilder,
+};
+use std::{
+    collections::HashMap,
+    fmt::{self, Display, Formatter},
+    str::FromStr,
+    sync::{Arc, RwLock},
+};
+use thiserror::Error;
+use uuid::Uuid;
+
+const MAX_NAME_LEN: usize = 32;
+
+/// The maximum length of a response shown in help and command listings
+const MAX_PREVIEW_LEN: usize = 80;
+
+#[derive(Docbot, Debug)]
+#[docbot(context = "ArgContext")]
+/// Manage text commands specific to this server.  Custom commands are run like
+/// any other command, and reply with a fixed message.
+///
+/// Responses can include `{user}` to mention the user running the command,
+/// `{channel}` to mention the channel it was run in, and `{args}` for any text
+/// given after the command name.
+///
+/// # Locale fr
+/// Gérer les commandes textuelles propres à ce serveur.  Les commandes
+/// personnalisées se lancent comme n'importe quelle autre commande, et répondent
+/// avec un message fixe.
+///
+/// Les réponses peuvent contenir `{user}` pour mentionner l'utilisateur qui lance
+/// la commande, `{channel}` pour mentionner le salon où elle a été lancée, et
+/// `{args}` pour le texte donné après le nom de la commande.
+pub enum CustomCommand {
+    /// help [command]
+    /// Get help with custom commands, or a particular custom subcommand
+    ///
+    /// # Arguments
+    /// command: The name of a subcommand to get info for
+    ///
+    /// # Locale fr
+    /// Obtenir de l'aide sur les commandes personnalisées, ou sur une sous-commande
+    ///
+    /// # Arguments
+    /// command: Le nom d'une sous-commande
+    Help(Option<CustomCommandId>),
+
+    /// (list|ls)
+    /// List the custom commands defined for this server
+    ///
+    /// # Locale fr
+    /// Afficher les commandes personnalisées définies sur ce serveur
+    List,
+
+    /// add <name> <response...>
+    /// Define a new custom command
+    ///
+    /// # Arguments
+    /// name: The name of the command.  Can contain letters, numbers, dashes,
+    ///       and underscores, and cannot be the name of a built-in command
+    /// response: The message to reply with when the command is run
+    ///
+    /// # Examples
+    /// add jam Jam session tonight at 8, {user}!
+    /// add discord-rules Please read the rules before posting in {channel}
+    ///
+    /// # Locale fr
+    /// Définir une nouvelle commande personnalisée
+    ///
+    /// # Arguments
+    /// name: Le nom de la commande.  Peut contenir des lettres, des chiffres, des
+    ///       tirets et des tirets bas, et ne peut pas être le nom d'une commande
+    ///       intégrée
+    /// response: Le message à envoyer lorsque la commande est lancée
+    #[docbot(requires = "admin")]
+    Add(CustomName, Vec<String>),
+
+    /// (remove|rm) <name>
+    /// Delete a custom command
+    ///
+    /// # Arguments
+    /// name: The name of the command to delete
+    ///
+    /// # Locale fr
+    /// Supprimer une commande personnalisée
+    ///
+    /// # Arguments
+    /// name: Le nom de la commande à supprimer
+    #[docbot(requires = "admin")]
+    Remove(CustomName),
+}
+
+/// The name of a custom command
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct CustomName(String);
+
+#[derive(Error, Debug)]
+#[error("invalid command name {0:?}, expected up to 32 letters, numbers, dashes, or underscores")]
+pub struct InvalidCustomName(String);
+
+impl FromStr for CustomName {
+    type Err = InvalidCustomName;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.is_empty()
+            || s.len() > MAX_NAME_LEN
+            || s.starts_with('-')
+            || !s
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        {
+            return Err(InvalidCustomName(s.into()));
+        }
+
+        Ok(Self(s.to_lowercase()))
+    }
+}
+
+impl Display for CustomName {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result { f.write_str(&self.0) }
+}
+
+impl CustomName {
+    /// Get the string representation of this name
+    #[must_use]
+    pub fn as_str(&self) -> &str { &self.0 }
+}
+
+/// The custom commands of a guild, ready to be matched against input
+#[derive(Debug)]
+pub struct GuildCommands {
+    set: DynamicCommandSet,
+    responses: HashMap<String, String>,
+}
+
+impl GuildCommands {
+    fn new(commands: Vec<CustomResponse>) -> Result<Self> {
+        let mut builder = DynamicCommandSet::builder();
+
+        for cmd in &commands {
+            // Only offer an argument if the response has somewhere to put it
+            builder = if cmd.response.contains("{args}") {
+                builder.command(
+                    &format!("{} [args...]", cmd.name),
+                    &preview(&cmd.response),
+                    &[("args", "Text to fill in for {args} in the response")],
+                )
+            } else {
+                builder.command(&cmd.name, &preview(&cmd.response), &[])
+            }
+            .with_context(|| format!("invalid custom command {:?}", cmd.name))?;
+        }
+
+        Ok(Self {
+            set: builder.build(),
+            responses: commands.into_iter().map(|c| (c.name, c.response)).collect(),
+        })
+    }
+
+    /// Returns true if the guild has no custom commands
+    #[must_use]
+    pub fn is_empty(&self) -> bool { self.responses.is_empty() }
+
+    /// Try to parse a command as one of this guild's custom commands
+    ///
+    /// # Errors
+    /// Returns an error if no custom command matched, or if arguments were
+    /// given to a command that takes none.
+    pub fn parse<I: IntoIterator<Item = S>, S: docbot::Spanned>(
+        &self,
+        iter: I,
+    ) -> Result<DynamicCommand, docbot::CommandParseError>
+    {
+        self.set.parse(iter)
+    }
+
+    /// Retrieve the help topic for a custom command, or the list of all custom
+    /// commands if `name` is `None`
+    ///
+    /// # Errors
+    /// Returns an error if the name does not match a custom command.
+    pub fn help(&self, name: Option<&str>) -> Result<&HelpTopic, IdParseError> {
+        self.set.help(name)
+    }
+
+    /// Get the response to a parsed custom command, with placeholders filled in
+    #[must_use]
+    pub fn respond(&self, cmd: &DynamicCommand, user: UserId, channel: ChannelId) -> String {
+        lazy_static! {
+            static ref PLACEHOLDER_RE: Regex = Regex::new(r"\{(user|channel|args)\}").unwrap();
+        }
+
+        let response = self.responses.get(cmd.id()).map_or("", String::as_str);
+
+        PLACEHOLDER_RE
+            .replace_all(response, |caps: &Captures| {
+                let mut b = MessageBuilder::new();
+
+                match &caps[1] {
+                    "user" => b.mention(&user),
+                    "channel" => b.channel(channel),
+                    _ => b.push_safe(cmd.get_all("args").join(" ")),
+                };
+
+                b.build()
+            })
+            .into_owned()
+    }
+}
+
+/// Cache of each guild's custom commands, so command sets are only rebuilt
+/// when a guild's commands change
+#[derive(Debug, Default)]
+pub struct CustomCommandCache(RwLock<HashMap<GuildId, Arc<GuildCommands>>>);
+
+impl CustomCommandCache {
+    /// Get the custom commands of a guild, loading them from the database if
+    /// they are not cached
+    pub fn get(&self, guild: GuildId, db: &DbPool) -> Result<Arc<GuildCommands>> {
+        if let Some(cmds) = self
+            .0
+            .read()
+            .map_err(|_| anyhow!("custom command cache was poisoned"))?
+            .get(&guild)
+        {
+            return Ok(Arc::clone(cmds));
+        }
+
+        let cmds = Arc::new(GuildCommands::new(
+            list_commands(guild, db).context("failed to list custom commands")?,
+        )?);
+
+        self.0
+            .write()
+            .map_err(|_| anyhow!("custom command cache was poisoned"))?
+            .insert(guild, Arc::clone(&cmds));
+
+        Ok(cmds)
+    }
+
+    /// Drop the cached commands of a guild, so they are reloaded on next use.
+    /// Also used to free the commands of guilds the bot has left.
+    pub fn invalidate(&self, guild: GuildId) {
+        if let Ok(mut cache) = self.0.write() {
+            cache.remove(&guild);
+        }
+    }
+}
+
+/// Shorten a response to its first line, truncated to fit in a listing
+#[must_use]
+pub fn preview(response: &str) -> String {
+    let line = response.lines().next().unwrap_or_default();
+
+    if line.chars().count() <= MAX_PREVIEW_LEN && line.len() == response.len() {
+        return line.into();
+    }
+
+    let end = line
+        .char_indices()
+        .nth(MAX_PREVIEW_LEN - 3)
+        .map_or(line.len(), |(i, _)| i);
+
+    format!("{}...", &line[..end])
+}
+
+pub type CustomCommandResult<T> = Result<T, CustomCommandError>;
+
+pub enum CustomCommandOk {
+    Help(&'static HelpTopic),
+    List(Vec<CustomResponse>),
+    Added(CustomName),
+    Removed,
+}
+
+#[derive(Error, Debug)]
+pub enum CustomCommandError {
+    #[error("no guild ID was provided")]
+    GuildRequired,
+    #[error("{0}")]
+    NoPermission(#[from] NoPermissionError),
+    #[error("{0} is the name of a built-in command")]
+    Reserved(CustomName),
+    #[error("a custom command named {0} already exists")]
+    Exists(CustomName),
+    #[error("no custom command named {0}")]
+    NoCommand(CustomName),
+    #[error("an unexpected error occurred")]
+    Other(#[from] anyhow::Error),
+}
+
+fn list_commands(guild: GuildId, db: &DbPool) -> Result<Vec<CustomResponse>> {
+    use crate::schema::custom_commands::dsl::{custom_commands, guild_id, name};
+
+    let db_conn = db.get().context("failed to connect to the database")?;
+
+    #[allow(clippy::cast_possible_wrap)]
+    let ret = custom_commands
+        .filter(guild_id.eq(guild.0 as i64))
+        .order(name.asc())
+        .load::<CustomResponse>(&db_conn)
+        .context("failed to retrieve custom commands from database")?;
+
+    Ok(ret)
+}
+
+/// Add a custom command, returning false if one with the same name exists
+fn add_command(guild: GuildId, name: &CustomName, response: String, db: &DbPool) -> Result<bool> {
+    use crate::schema::custom_commands::dsl::custom_commands;
+
+    let db_conn = db.get().context("failed to connect to the database")?;
+
+    #[allow(clippy::cast_possible_wrap)]
+    match diesel::insert_into(custom_commands)
+        .values(vec![NewCustomResponse {
+            id: Uuid::new_v4(),
+            guild_id: guild.0 as i64,
+            name: name.to_string(),
+            response,
+        }])
+        .execute(&db_conn)
+    {
+        Ok(_) => Ok(true),
+        Err(DieselError::DatabaseError(DatabaseErrorKind::UniqueViolation, _)) => Ok(false),
+        Err(e) => Err(e).context("failed to insert new custom command"),
+    }
+}
+
+/// Delete a custom command, returning false if it did not exist
+fn delete_command(guild: GuildId, target: &CustomName, db: &DbPool) -> Result<bool> {
+    use crate::schema::custom_commands::dsl::{custom_commands, guild_id, name};
+
+    let db_conn = db.get().context("failed to connect to the database")?;
+
+    #[allow(clippy::cast_possible_wrap)]
+    let count = diesel::delete(
+        custom_commands.filter(guild_id.eq(guild.0 as i64).and(name.eq(target.as_str()))),
+    )
+    .execute(&db_conn)
+    .context("failed to delete custom command")?;
+
+    Ok(count > 0)
+}
+
+pub fn execute(
+    command: CustomCommand,
+    sender: UserId,
+    guild: Option<GuildId>,
+    db: &DbPool,
+    cache: &CustomCommandCache,
+    superuser: UserId,
+    lang: Language,
+) -> CustomCommandResult<CustomCommandOk>
+{
+    let is_super = sender == superuser;
+
+    let get_guild = || guild.ok_or(CustomCommandError::GuildRequired);
+
+    let check_edit = |guild| -> CustomCommandResult<_> {
+        if !is_super
+            && !roles::get_user_roles(sender, guild, db)
+                .context("failed to get sender permissions")?
+                .contains(&Role::Admin)
+        {
+            return Err(NoPermissionError::EditCustom.into());
+        }
+
+        Ok(())
+    };
+
+    Ok(match command {
+        CustomCommand::Help(topic) => {
+            CustomCommandOk::Help(CustomCommand::help_in(topic, lang.code()))
+        },
+        CustomCommand::List => CustomCommandOk::List(
+            list_commands(get_guild()?, db).context("failed to list custom commands")?,
+        ),
+        CustomCommand::Add(name, response) => {
+            let guild = get_guild()?;
+            check_edit(guild)?;
+
+            // Custom commands are only tried once no built-in command matches,
+            // so a name a built-in could match would never be reachable
+            if !matches!(
+                name.as_str().parse::<BaseCommandId>(),
+                Err(IdParseError::NoMatch(..))
+            ) {
+                return Err(CustomCommandError::Reserved(name));
+            }
+
+            if !add_command(guild, &name, response.join(" "), db)
+                .context("failed to add custom command")?
+            {
+                return Err(CustomCommandError::Exists(name));
+            }
+
+            cache.invalidate(guild);
+
+            CustomCommandOk::Added(name)
+        },
+        CustomCommand::Remove(name) => {
+            let guild = get_guild()?;
+            check_edit(guild)?;
+
+            if !delete_command(guild, &name, db).context("failed to remove custom command")? {
+                return Err(CustomCommandError::NoCommand(name));
+            }
+
+            cache.invalidate(guild);
+
+            CustomCommandOk::Removed
+        },
+    })
+}
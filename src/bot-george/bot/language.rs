@@ -0,0 +1,247 @@
+use super::{
+    roles,
+    roles::{NoPermissionError, Role},
+};
+use crate::{
+    commands::ArgContext,
+    db::{models::NewGuildLanguage, DbPool},
+    error::Result,
+    locale::{self, Strings},
+};
+use anyhow::Context;
+use diesel::{prelude::*, result::Error as DieselError};
+use docbot::{prelude::*, HelpTopic};
+use log::warn;
+use serenity::model::id::{GuildId, UserId};
+use thiserror::Error;
+
+#[derive(Docbot, Debug)]
+#[docbot(context = "ArgContext")]
+/// Choose the language the bot uses for help and error messages in this
+/// server.
+///
+/// # Locale fr
+/// Choisir la langue utilisée par le bot pour l'aide et les messages d'erreur
+/// sur ce serveur.
+pub enum LanguageCommand {
+    /// help [command]
+    /// Get help with language settings, or a particular language subcommand
+    ///
+    /// # Arguments
+    /// command: The name of a subcommand to get info for
+    ///
+    /// # Locale fr
+    /// Obtenir de l'aide sur les paramètres de langue, ou sur une sous-commande
+    ///
+    /// # Arguments
+    /// command: Le nom d'une sous-commande
+    Help(Option<LanguageCommandId>),
+
+    /// (list|ls)
+    /// List the available languages
+    ///
+    /// # Locale fr
+    /// Afficher les langues disponibles
+    List,
+
+    /// show
+    /// Show the language used in this server
+    ///
+    /// # Locale fr
+    /// Afficher la langue utilisée sur ce serveur
+    Show,
+
+    /// set <language>
+    /// Change the language used in this server
+    ///
+    /// # Arguments
+    /// language: The language to use
+    ///
+    /// # Examples
+    /// set fr
+    ///
+    /// # Locale fr
+    /// Changer la langue utilisée sur ce serveur
+    ///
+    /// # Arguments
+    /// language: La langue à utiliser
+    #[docbot(requires = "admin")]
+    Set(Language),
+
+    /// (reset|clear)
+    /// Go back to using English in this server
+    ///
+    /// # Locale fr
+    /// Revenir à l'anglais sur ce serveur
+    #[docbot(requires = "admin")]
+    Reset,
+}
+
+#[derive(Docbot, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Language {
+    /// (en|english)
+    /// English
+    English,
+    /// (fr|french|francais)
+    /// Français
+    French,
+}
+
+impl Default for Language {
+    fn default() -> Self { Self::English }
+}
+
+impl Language {
+    /// The locale code used to look up translated help, such as `fr`
+    #[must_use]
+    pub fn code(self) -> &'static str { self.to_str() }
+
+    /// The name of this language, written in the language itself
+    #[must_use]
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::English => "English",
+            Self::French => "Français",
+        }
+    }
+
+    /// Get the catalog of bot messages for this language
+    #[must_use]
+    pub fn strings(self) -> &'static Strings {
+        match self {
+            Self::English => &locale::ENGLISH,
+            Self::French => &locale::FRENCH,
+        }
+    }
+}
+
+pub type LanguageCommandResult<T> = Result<T, LanguageCommandError>;
+
+pub enum LanguageCommandOk {
+    Help(&'static HelpTopic),
+    List(&'static HelpTopic),
+    Show(Language),
+    Set(Language),
+    Reset,
+}
+
+#[derive(Error, Debug)]
+pub enum LanguageCommandError {
+    #[error("no guild ID was provided")]
+    GuildRequired,
+    #[error("{0}")]
+    NoPermission(#[from] NoPermissionError),
+    #[error("an unexpected error occurred")]
+    Other(#[from] anyhow::Error),
+}
+
+/// Get the language used in a guild, falling back to English if none is set
+pub fn get_language(guild: GuildId, db: &DbPool) -> Result<Language> {
+    use crate::schema::guild_languages::dsl::{guild_id, guild_languages, language};
+
+    let db_conn = db.get().context("failed to connect to the database")?;
+
+    #[allow(clippy::cast_possible_wrap)]
+    let lang = match guild_languages
+        .filter(guild_id.eq(guild.0 as i64))
+        .select(language)
+        .first::<String>(&db_conn)
+    {
+        Ok(l) => Some(l),
+        Err(DieselError::NotFound) => None,
+        Err(e) => return Err(e).context("failed to retrieve guild language"),
+    };
+
+    Ok(lang
+        .and_then(|l| {
+            l.parse()
+                .map_err(|e| warn!("guild language {:?} couldn't be parsed: {:?}", l, e))
+                .ok()
+        })
+        .unwrap_or_default())
+}
+
+fn set_language(guild: GuildId, lang: Language, db: &DbPool) -> Result<()> {
+    use crate::schema::guild_languages::dsl::{guild_id, guild_languages, language};
+
+    let db_conn = db.get().context("failed to connect to the database")?;
+
+    #[allow(clippy::cast_possible_wrap)]
+    diesel::insert_into(guild_languages)
+        .values(vec![NewGuildLanguage {
+            guild_id: guild.0 as i64,
+            language: lang.code().into(),
+        }])
+        .on_conflict(guild_id)
+        .do_update()
+        .set(language.eq(lang.code()))
+        .execute(&db_conn)
+        .context("failed to set guild language")?;
+
+    Ok(())
+}
+
+fn delete_language(guild: GuildId, db: &DbPool) -> Result<()> {
+    use crate::schema::guild_languages::dsl::{guild_id, guild_languages};
+
+    let db_conn = db.get().context("failed to connect to the database")?;
+
+    #[allow(clippy::cast_possible_wrap)]
+    diesel::delete(guild_languages.filter(guild_id.eq(guild.0 as i64)))
+        .execute(&db_conn)
+        .context("failed to delete guild language")?;
+
+    Ok(())
+}
+
+pub fn execute(
+    command: LanguageCommand,
+    sender: UserId,
+    guild: Option<GuildId>,
+    db: &DbPool,
+    superuser: UserId,
+    lang: Language,
+) -> LanguageCommandResult<LanguageCommandOk>
+{
+    let is_super = sender == superuser;
+
+    let get_guild = || guild.ok_or(LanguageCommandError::GuildRequired);
+
+    let check_edit = |guild| -> LanguageCommandResult<_> {
+        if !is_super
+            && !roles::get_user_roles(sender, guild, db)
+                .context("failed to get sender permissions")?
+                .contains(&Role::Admin)
+        {
+            return Err(NoPermissionError::EditLanguage.into());
+        }
+
+        Ok(())
+    };
+
+    Ok(match command {
+        LanguageCommand::Help(topic) => {
+            LanguageCommandOk::Help(LanguageCommand::help_in(topic, lang.code()))
+        },
+        LanguageCommand::List => LanguageCommandOk::List(Language::help_in(None, lang.code())),
+        LanguageCommand::Show => LanguageCommandOk::Show(
+            get_language(get_guild()?, db).context("failed to get guild language")?,
+        ),
+        LanguageCommand::Set(new_lang) => {
+            let guild = get_guild()?;
+            check_edit(guild)?;
+
+            set_language(guild, new_lang, db).context("failed to set guild language")?;
+
+            LanguageCommandOk::Set(new_lang)
+        },
+        LanguageCommand::Reset => {
+            let guild = get_guild()?;
+            check_edit(guild)?;
+
+            delete_language(guild, db).context("failed to reset guild language")?;
+
+            LanguageCommandOk::Reset
+        },
+    })
+}
@@ -1,3 +1,10 @@
 pub mod channels;
+pub mod custom;
+pub mod language;
+pub mod modmail;
 pub mod roles;
 pub mod schedule;
+
+/// The alias given to users and channels the bot records without knowing their
+/// name.  Never resolved as a name, since any number of rows may share it.
+pub const UNKNOWN_ALIAS: &str = "???";
@@ -0,0 +1,461 @@
+use super::{
+    language::Language,
+    roles,
+    roles::{NoPermissionError, Role},
+};
+use crate::{
+    commands::ArgContext,
+    db::{
+        models::{NewInbox, NewTicket, Ticket},
+        DbPool,
+    },
+    error::Result,
+    util::ShortId,
+};
+use anyhow::Context;
+use diesel::{
+    prelude::*,
+    result::{DatabaseErrorKind, Error as DieselError},
+};
+use docbot::{prelude::*, HelpTopic};
+use serenity::model::id::{ChannelId, GuildId, UserId};
+use thiserror::Error;
+use uuid::Uuid;
+
+const TICKET_ID_LEN: usize = 6;
+const MAX_TICKET_ID_ATTEMPTS: usize = 8;
+
+#[derive(Docbot, Debug)]
+#[docbot(context = "ArgContext")]
+/// Manage anonymous modmail tickets
+///
+/// # Locale fr
+/// Gérer les tickets de modmail anonymes
+pub enum TicketCommand {
+    /// help [command]
+    /// Get help with modmail tickets, or a particular ticket subcommand
+    ///
+    /// # Arguments
+    /// command: The name of a subcommand to get info for
+    ///
+    /// # Locale fr
+    /// Obtenir de l'aide sur les tickets de modmail, ou sur une sous-commande
+    ///
+    /// # Arguments
+    /// command: Le nom d'une sous-commande
+    Help(Option<TicketCommandId>),
+
+    /// inbox
+    /// Show the channel modmail is delivered to
+    ///
+    /// # Locale fr
+    /// Afficher le salon dans lequel le modmail est envoyé
+    #[docbot(requires = "mod")]
+    Inbox,
+
+    /// (set inbox) <channel>
+    /// Set the channel modmail is delivered to
+    ///
+    /// # Arguments
+    /// channel: The channel to deliver modmail to
+    ///
+    /// # Locale fr
+    /// Définir le salon dans lequel le modmail est envoyé
+    ///
+    /// # Arguments
+    /// channel: Le salon dans lequel envoyer le modmail
+    #[docbot(requires = "admin")]
+    SetInbox(#[docbot(hint = "channel mention or name")] ChannelId),
+
+    /// (list|ls)
+    /// List all open tickets
+    ///
+    /// # Locale fr
+    /// Afficher tous les tickets ouverts
+    #[docbot(requires = "mod")]
+    List,
+
+    /// reply <ticket> <message...>
+    /// Reply to a ticket
+    ///
+    /// # Overview
+    /// When run by a moderator, the reply is sent to the author of the ticket
+    /// as a direct message.  When run by the author of a ticket in a direct
+    /// message, the reply is sent to the moderators.
+    ///
+    /// # Arguments
+    /// ticket: The ID of the ticket to reply to
+    /// message: The contents of the reply
+    ///
+    /// # See also
+    /// - `tickets open`
+    /// - `tickets close`
+    ///
+    /// # Locale fr
+    /// Répondre à un ticket
+    ///
+    /// # Description
+    /// Lancée par un modérateur, la réponse est envoyée à l'auteur du ticket en
+    /// message privé.  Lancée par l'auteur d'un ticket en message privé, la réponse
+    /// est envoyée aux modérateurs.
+    ///
+    /// # Arguments
+    /// ticket: L'identifiant du ticket auquel répondre
+    /// message: Le contenu de la réponse
+    Reply(TicketId, Vec<String>),
+
+    /// (open|reopen) <ticket>
+    /// Reopen a closed ticket
+    ///
+    /// # Arguments
+    /// ticket: The ID of the ticket to reopen
+    ///
+    /// # Locale fr
+    /// Rouvrir un ticket fermé
+    ///
+    /// # Arguments
+    /// ticket: L'identifiant du ticket à rouvrir
+    Open(TicketId),
+
+    /// close <ticket>
+    /// Close a ticket, preventing any further replies
+    ///
+    /// # Arguments
+    /// ticket: The ID of the ticket to close
+    ///
+    /// # Locale fr
+    /// Fermer un ticket, empêchant toute nouvelle réponse
+    ///
+    /// # Arguments
+    /// ticket: L'identifiant du ticket à fermer
+    Close(TicketId),
+}
+
+/// A short, anonymous identifier for a modmail ticket
+pub type TicketId = ShortId;
+
+/// The destination of a relayed modmail message
+pub enum Recipient {
+    /// The author of a ticket, to be messaged directly
+    Author(UserId),
+    /// The modmail inbox of a guild
+    Inbox(ChannelId),
+}
+
+pub type TicketCommandResult<T> = Result<T, TicketCommandError>;
+
+pub enum TicketCommandOk {
+    Help(&'static HelpTopic),
+    Inbox(Option<ChannelId>),
+    InboxSet,
+    List(Vec<TicketId>),
+    Relay {
+        ticket: TicketId,
+        guild: GuildId,
+        to: Recipient,
+        message: String,
+    },
+    Opened {
+        ticket: TicketId,
+        guild: GuildId,
+        notify: Recipient,
+    },
+    Closed {
+        ticket: TicketId,
+        guild: GuildId,
+        notify: Recipient,
+    },
+}
+
+#[derive(Error, Debug)]
+pub enum TicketCommandError {
+    #[error("no guild ID was provided")]
+    GuildRequired,
+    #[error("{0}")]
+    NoPermission(#[from] NoPermissionError),
+    #[error("no modmail inbox is configured")]
+    NoInbox,
+    #[error("no ticket with ID {0}")]
+    NoTicket(TicketId),
+    #[error("ticket {0} is closed")]
+    TicketClosed(TicketId),
+    #[error("an unexpected error occurred")]
+    Other(#[from] anyhow::Error),
+}
+
+pub fn get_inbox(guild: GuildId, db: &DbPool) -> Result<Option<ChannelId>> {
+    use crate::schema::modmail_inboxes::dsl::{channel_id, guild_id, modmail_inboxes};
+
+    let db_conn = db.get().context("failed to connect to the database")?;
+
+    #[allow(clippy::cast_possible_wrap, clippy::cast_sign_loss)]
+    match modmail_inboxes
+        .filter(guild_id.eq(guild.0 as i64))
+        .select(channel_id)
+        .first::<i64>(&db_conn)
+    {
+        Ok(c) => Ok(Some(ChannelId(c as u64))),
+        Err(DieselError::NotFound) => Ok(None),
+        Err(e) => Err(e).context("failed to retrieve modmail inbox from database"),
+    }
+}
+
+/// List all guilds with a modmail inbox configured
+pub fn get_inbox_guilds(db: &DbPool) -> Result<Vec<GuildId>> {
+    use crate::schema::modmail_inboxes::dsl::{guild_id, modmail_inboxes};
+
+    let db_conn = db.get().context("failed to connect to the database")?;
+
+    let guilds = modmail_inboxes
+        .select(guild_id)
+        .load::<i64>(&db_conn)
+        .context("failed to retrieve modmail inboxes from database")?;
+
+    #[allow(clippy::cast_sign_loss)]
+    let guilds = guilds.into_iter().map(|g| GuildId(g as u64)).collect();
+
+    Ok(guilds)
+}
+
+fn set_inbox(guild: GuildId, channel: ChannelId, db: &DbPool) -> Result<()> {
+    use crate::schema::modmail_inboxes::dsl::{channel_id, guild_id, modmail_inboxes};
+
+    let db_conn = db.get().context("failed to connect to the database")?;
+
+    #[allow(clippy::cast_possible_wrap)]
+    diesel::insert_into(modmail_inboxes)
+        .values(vec![NewInbox {
+            guild_id: guild.0 as i64,
+            channel_id: channel.0 as i64,
+        }])
+        .on_conflict(guild_id)
+        .do_update()
+        .set(channel_id.eq(channel.0 as i64))
+        .execute(&db_conn)
+        .context("failed to set modmail inbox")?;
+
+    Ok(())
+}
+
+fn get_ticket(ticket: &TicketId, db: &DbPool) -> Result<Option<Ticket>> {
+    use crate::schema::modmail_tickets::dsl::{
+        guild_id, id, is_open, modmail_tickets, ticket_id, user_id,
+    };
+
+    let db_conn = db.get().context("failed to connect to the database")?;
+
+    match modmail_tickets
+        .filter(ticket_id.eq(ticket.as_str()))
+        .select((id, ticket_id, guild_id, user_id, is_open))
+        .first::<Ticket>(&db_conn)
+    {
+        Ok(t) => Ok(Some(t)),
+        Err(DieselError::NotFound) => Ok(None),
+        Err(e) => Err(e).context("failed to retrieve ticket from database"),
+    }
+}
+
+fn list_tickets(guild: GuildId, db: &DbPool) -> Result<Vec<TicketId>> {
+    use crate::schema::modmail_tickets::dsl::{
+        guild_id, is_open, modmail_tickets, opened_at, ticket_id,
+    };
+
+    let db_conn = db.get().context("failed to connect to the database")?;
+
+    #[allow(clippy::cast_possible_wrap)]
+    let tickets = modmail_tickets
+        .filter(guild_id.eq(guild.0 as i64).and(is_open.eq(true)))
+        .order(opened_at.asc())
+        .select(ticket_id)
+        .load::<String>(&db_conn)
+        .context("failed to retrieve tickets from database")?;
+
+    Ok(tickets.into_iter().map(TicketId::from).collect())
+}
+
+fn set_ticket_open(ticket: &Ticket, open: bool, db: &DbPool) -> Result<()> {
+    use crate::schema::modmail_tickets::dsl::{id, is_open, modmail_tickets};
+
+    let db_conn = db.get().context("failed to connect to the database")?;
+
+    diesel::update(modmail_tickets.filter(id.eq(ticket.id)))
+        .set(is_open.eq(open))
+        .execute(&db_conn)
+        .context("failed to update ticket")?;
+
+    Ok(())
+}
+
+/// Open a new ticket for a message sent by the given user.  Returns the ID of
+/// the new ticket and the inbox to deliver the message to.
+pub fn open_ticket(
+    sender: UserId,
+    guild: GuildId,
+    db: &DbPool,
+) -> TicketCommandResult<(TicketId, ChannelId)>
+{
+    use crate::schema::modmail_tickets::dsl::modmail_tickets;
+
+    let inbox = get_inbox(guild, db)
+        .context("failed to get modmail inbox")?
+        .ok_or(TicketCommandError::NoInbox)?;
+
+    let db_conn = db.get().context("failed to connect to the database")?;
+
+    for _ in 0..MAX_TICKET_ID_ATTEMPTS {
+        let ticket = TicketId::random(TICKET_ID_LEN);
+
+        #[allow(clippy::cast_possible_wrap)]
+        match diesel::insert_into(modmail_tickets)
+            .values(vec![NewTicket {
+                id: Uuid::new_v4(),
+                ticket_id: ticket.to_string(),
+                guild_id: guild.0 as i64,
+                user_id: sender.0 as i64,
+            }])
+            .execute(&db_conn)
+        {
+            Ok(_) => return Ok((ticket, inbox)),
+            Err(DieselError::DatabaseError(DatabaseErrorKind::UniqueViolation, _)) => (),
+            Err(e) => Err(e).context("failed to insert new ticket")?,
+        }
+    }
+
+    Err(anyhow::anyhow!("failed to generate a unique ticket ID").into())
+}
+
+pub fn execute(
+    command: TicketCommand,
+    sender: UserId,
+    guild: Option<GuildId>,
+    db: &DbPool,
+    superuser: UserId,
+    lang: Language,
+) -> TicketCommandResult<TicketCommandOk>
+{
+    let is_super = sender == superuser;
+
+    let get_guild = || guild.ok_or(TicketCommandError::GuildRequired);
+
+    let has_role = |guild, role| -> TicketCommandResult<_> {
+        Ok(is_super
+            || roles::get_user_roles(sender, guild, db)
+                .context("failed to get sender permissions")?
+                .contains(&role))
+    };
+
+    // Admins may do anything a moderator can
+    let check_mod = |guild| -> TicketCommandResult<_> {
+        if !has_role(guild, Role::Mod)? && !has_role(guild, Role::Admin)? {
+            return Err(NoPermissionError::Tickets.into());
+        }
+
+        Ok(())
+    };
+
+    // Moderators may act on tickets from their own guild, and authors may act
+    // on their own tickets from a direct message
+    let get_ticket_for = |id: TicketId| -> TicketCommandResult<_> {
+        let ticket = get_ticket(&id, db)
+            .context("failed to get ticket")?
+            .ok_or_else(|| TicketCommandError::NoTicket(id.clone()))?;
+
+        #[allow(clippy::cast_sign_loss)]
+        let ticket_guild = GuildId(ticket.guild_id as u64);
+
+        let is_mod = match guild {
+            Some(g) if g == ticket_guild => {
+                check_mod(g)?;
+
+                true
+            },
+            // Don't reveal the existence of tickets from other guilds
+            Some(_) => return Err(TicketCommandError::NoTicket(id)),
+            #[allow(clippy::cast_sign_loss)]
+            None if UserId(ticket.user_id as u64) == sender => false,
+            None => return Err(TicketCommandError::NoTicket(id)),
+        };
+
+        Ok((ticket, ticket_guild, is_mod))
+    };
+
+    let notify_other = |ticket: &Ticket, ticket_guild, is_mod| -> TicketCommandResult<_> {
+        #[allow(clippy::cast_sign_loss)]
+        let author = UserId(ticket.user_id as u64);
+
+        Ok(if is_mod {
+            Recipient::Author(author)
+        } else {
+            Recipient::Inbox(
+                get_inbox(ticket_guild, db)
+                    .context("failed to get modmail inbox")?
+                    .ok_or(TicketCommandError::NoInbox)?,
+            )
+        })
+    };
+
+    Ok(match command {
+        TicketCommand::Help(topic) => {
+            TicketCommandOk::Help(TicketCommand::help_in(topic, lang.code()))
+        },
+        TicketCommand::Inbox => {
+            let guild = get_guild()?;
+            check_mod(guild)?;
+
+            TicketCommandOk::Inbox(get_inbox(guild, db).context("failed to get modmail inbox")?)
+        },
+        TicketCommand::SetInbox(channel) => {
+            let guild = get_guild()?;
+
+            if !has_role(guild, Role::Admin)? {
+                return Err(NoPermissionError::Inbox.into());
+            }
+
+            set_inbox(guild, channel, db).context("failed to set modmail inbox")?;
+
+            TicketCommandOk::InboxSet
+        },
+        TicketCommand::List => {
+            let guild = get_guild()?;
+            check_mod(guild)?;
+
+            TicketCommandOk::List(list_tickets(guild, db).context("failed to list tickets")?)
+        },
+        TicketCommand::Reply(id, message) => {
+            let (ticket, ticket_guild, is_mod) = get_ticket_for(id.clone())?;
+
+            if !ticket.is_open {
+                return Err(TicketCommandError::TicketClosed(id));
+            }
+
+            TicketCommandOk::Relay {
+                to: notify_other(&ticket, ticket_guild, is_mod)?,
+                ticket: id,
+                guild: ticket_guild,
+                message: message.join(" "),
+            }
+        },
+        TicketCommand::Open(id) => {
+            let (ticket, ticket_guild, is_mod) = get_ticket_for(id.clone())?;
+
+            set_ticket_open(&ticket, true, db).context("failed to reopen ticket")?;
+
+            TicketCommandOk::Opened {
+                notify: notify_other(&ticket, ticket_guild, is_mod)?,
+                ticket: id,
+                guild: ticket_guild,
+            }
+        },
+        TicketCommand::Close(id) => {
+            let (ticket, ticket_guild, is_mod) = get_ticket_for(id.clone())?;
+
+            set_ticket_open(&ticket, false, db).context("failed to close ticket")?;
+
+            TicketCommandOk::Closed {
+                notify: notify_other(&ticket, ticket_guild, is_mod)?,
+                ticket: id,
+                guild: ticket_guild,
+            }
+        },
+    })
+}
@@ -1,4 +1,6 @@
+use super::{language::Language, UNKNOWN_ALIAS};
 use crate::{
+    commands::{ArgContext, ArgResolveError},
     db::{
         models::{DisplayUser, NewUser, NewUserRole, User},
         DbPool,
@@ -17,8 +19,8 @@ use std::{
 use thiserror::Error;
 use uuid::Uuid;
 
-// TODO: allow referring to users by bot-assigned alias?
 #[derive(Docbot, Debug)]
+#[docbot(context = "ArgContext")]
 /// TODO
 pub enum RoleCommand {
     /// help [command]
@@ -26,47 +28,116 @@ pub enum RoleCommand {
     ///
     /// # Arguments
     /// command: The name of a subcommand to get info for
+    ///
+    /// # Locale fr
+    /// Obtenir de l'aide sur la gestion des rôles, ou sur une sous-commande
+    ///
+    /// # Arguments
+    /// command: Le nom d'une sous-commande
     Help(Option<RoleCommandId>),
 
     /// (list|ls)
     /// List the available roles
+    ///
+    /// # Locale fr
+    /// Afficher les rôles disponibles
     List,
 
     /// show [user]
     /// Show all assigned roles, or list the roles of a given user
     ///
     /// # Arguments
-    /// user: The user to show roles for.  Must be a valid user mention in order
-    ///       to work
-    Show(Option<UserId>),
+    /// user: The user to show roles for
+    ///
+    /// # Locale fr
+    /// Afficher tous les rôles attribués, ou les rôles d'un utilisateur
+    ///
+    /// # Arguments
+    /// user: L'utilisateur dont afficher les rôles
+    #[docbot(requires = "mod")]
+    Show(#[docbot(hint = "user mention or tag")] Option<UserId>),
 
     /// add <user> <roles...>
     /// Add one or more roles to a user
     ///
     /// # Arguments
-    /// user: The user to add roles to.  Must be a valid user mention in order
-    ///       to work
-    /// roles: The roles to add.  Run [`roles ls`]() for a list of valid roles
-    Add(UserId, BTreeSet<Role>),
+    /// user: The user to add roles to
+    /// roles: The roles to add
+    ///
+    /// # Examples
+    /// add <@123456789012345678> mod
+    ///
+    /// # Locale fr
+    /// Ajouter un ou plusieurs rôles à un utilisateur
+    ///
+    /// # Arguments
+    /// user: L'utilisateur auquel ajouter des rôles
+    /// roles: Les rôles à ajouter
+    #[docbot(requires = "admin")]
+    Add(
+        #[docbot(hint = "user mention or tag")] UserId,
+        BTreeSet<Role>,
+    ),
 
     /// (remove|rm) <user> <roles...>
     /// Remove one or more roles from a user
     ///
     /// # Arguments
-    /// user: The user to remove roles from.  Must be a valid user mention in
-    ///       order to work
-    /// roles: The roles to remove.  Run [`roles ls`]() for a list of valid
-    ///        roles
-    Remove(UserId, BTreeSet<Role>),
+    /// user: The user to remove roles from
+    /// roles: The roles to remove
+    ///
+    /// # Locale fr
+    /// Retirer un ou plusieurs rôles à un utilisateur
+    ///
+    /// # Arguments
+    /// user: L'utilisateur auquel retirer des rôles
+    /// roles: Les rôles à retirer
+    #[docbot(requires = "admin")]
+    Remove(
+        #[docbot(hint = "user mention or tag")] UserId,
+        BTreeSet<Role>,
+    ),
+
+    /// alias <user> [name]
+    /// Give a user a name they can be referred to by in commands, or remove
+    /// their current one
+    ///
+    /// # Arguments
+    /// user: The user to name
+    /// name: The alias to give the user.  If omitted, the user's alias is
+    ///       removed
+    ///
+    /// # Examples
+    /// alias <@123456789012345678> alice
+    ///
+    /// # Locale fr
+    /// Donner à un utilisateur un nom utilisable dans les commandes, ou
+    /// retirer celui qu'il a
+    ///
+    /// # Arguments
+    /// user: L'utilisateur à nommer
+    /// name: L'alias à donner à l'utilisateur.  Sans cet argument, l'alias de
+    ///       l'utilisateur est retiré
+    #[docbot(requires = "admin")]
+    Alias(
+        #[docbot(hint = "user mention or tag")] UserId,
+        Option<String>,
+    ),
 }
 
 #[derive(Docbot, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
 pub enum Role {
     /// admin
     /// Allow users to control the behavior of the bot, and appoint mods
+    ///
+    /// # Locale fr
+    /// Permettre de contrôler le comportement du bot et de nommer des modérateurs
     Admin,
     /// (mod|moderator)
     /// Allow users to receive modmail
+    ///
+    /// # Locale fr
+    /// Permettre de recevoir le modmail
     Mod,
 }
 
@@ -79,6 +150,8 @@ pub enum RoleCommandOk {
     ShowOne(DisplayUser, BTreeSet<Role>),
     Added(usize),
     Removed(usize),
+    AliasSet,
+    AliasCleared,
 }
 
 #[derive(Error, Debug)]
@@ -87,6 +160,8 @@ pub enum RoleCommandError {
     GuildRequired,
     #[error("{0}")]
     NoPermission(#[from] NoPermissionError),
+    #[error("alias {0:?} is already in use")]
+    AliasTaken(String),
     #[error("an unexpected error occurred")]
     Other(#[from] anyhow::Error),
 }
@@ -99,6 +174,24 @@ pub enum NoPermissionError {
     Add(Role),
     #[error("missing permissions to remove role {0:?}")]
     Remove(Role),
+    #[error("missing permissions to change user aliases")]
+    Alias,
+    #[error("missing permissions to show channel modes")]
+    ShowChannels,
+    #[error("missing permissions to change channel modes")]
+    EditChannels,
+    #[error("missing permissions to manage modmail tickets")]
+    Tickets,
+    #[error("missing permissions to configure the modmail inbox")]
+    Inbox,
+    #[error("missing permissions to show scheduled announcements")]
+    ShowSchedule,
+    #[error("missing permissions to schedule announcements")]
+    EditSchedule,
+    #[error("missing permissions to manage custom commands")]
+    EditCustom,
+    #[error("missing permissions to change the server language")]
+    EditLanguage,
 }
 
 pub fn get_user(user: UserId, guild: GuildId, db: &DbPool) -> Result<Option<User>> {
@@ -118,6 +211,136 @@ pub fn get_user(user: UserId, guild: GuildId, db: &DbPool) -> Result<Option<User
     }
 }
 
+fn find_user_alias(guild: GuildId, name: &str, db: &DbPool) -> Result<UserId, ArgResolveError> {
+    use crate::schema::users::dsl::{alias, guild_id, user_id, users};
+
+    if name == UNKNOWN_ALIAS {
+        return Err(ArgResolveError::NotFound("user", name.into()));
+    }
+
+    let db_conn = db.get().context("failed to connect to the database")?;
+
+    #[allow(clippy::cast_possible_wrap)]
+    let found = users
+        .filter(guild_id.eq(guild.0 as i64).and(alias.eq(name)))
+        .select(user_id)
+        .limit(2)
+        .load::<i64>(&db_conn)
+        .context("failed to retrieve user alias from database")?;
+
+    #[allow(clippy::cast_sign_loss)]
+    match found.as_slice() {
+        [u] => Ok(UserId(*u as u64)),
+        [] => Err(ArgResolveError::NotFound("user", name.into())),
+        _ => Err(ArgResolveError::Ambiguous("user", name.into())),
+    }
+}
+
+/// Resolve a user from a mention or ID, a tag of the form `name#1234` with or
+/// without a leading `@`, or a bot-assigned alias
+impl ParseWith<ArgContext> for UserId {
+    type Err = ArgResolveError;
+
+    fn parse_with(ctx: &ArgContext, s: &str) -> Result<Self, ArgResolveError> {
+        if let Ok(id) = s.parse() {
+            return Ok(id);
+        }
+
+        let guild = ctx
+            .guild
+            .as_ref()
+            .ok_or(ArgResolveError::GuildRequired("user"))?;
+        let name = s.strip_prefix('@').unwrap_or(s);
+
+        // Tags are unique, so there's no need to check for ambiguity
+        if let Some(member) = guild
+            .members
+            .values()
+            .find(|m| m.user.tag().eq_ignore_ascii_case(name))
+        {
+            return Ok(member.user.id);
+        }
+
+        find_user_alias(guild.id, name, &ctx.db)
+    }
+}
+
+/// Get the roles of a user in a guild, or an empty set if they have none
+pub fn get_user_roles(user: UserId, guild: GuildId, db: &DbPool) -> Result<BTreeSet<Role>> {
+    get_user(user, guild, db)
+        .context("failed to get user")?
+        .map_or_else(|| Ok(BTreeSet::new()), |u| get_roles(&u, db))
+}
+
+fn get_all_roles(guild: GuildId, db: &DbPool) -> Result<HashMap<DisplayUser, BTreeSet<Role>>> {
+    use crate::schema::{user_roles, users};
+
+    let db_conn = db.get().context("failed to connect to the database")?;
+
+    #[allow(clippy::cast_possible_wrap)]
+    let rows = users::table
+        .inner_join(user_roles::table)
+        .filter(users::guild_id.eq(guild.0 as i64))
+        .select((users::id, users::alias, users::user_id, user_roles::role))
+        .load::<(Uuid, String, i64, String)>(&db_conn)
+        .context("failed to retrieve user roles from database")?;
+
+    let mut grouped: HashMap<_, (User, Vec<_>)> = HashMap::new();
+
+    for (id, alias, user_id, role) in rows {
+        grouped
+            .entry(user_id)
+            .or_insert_with(|| (User { id, alias }, Vec::new()))
+            .1
+            .push(role);
+    }
+
+    let mut ret = HashMap::new();
+
+    for (user_id, (user, roles)) in grouped {
+        let mut remove = BTreeSet::new();
+
+        let roles: BTreeSet<Role> = roles
+            .into_iter()
+            .filter_map(|r| {
+                r.parse()
+                    .map_err(|e| {
+                        warn!("role {:?} couldn't be parsed: {:?}", r, e);
+                        remove.insert(r);
+                    })
+                    .ok()
+            })
+            .collect();
+
+        if !remove.is_empty() {
+            warn!("Removing invalid roles off {:?}: {:?}", user.alias, remove);
+
+            diesel::delete(
+                user_roles::table.filter(
+                    user_roles::user_id
+                        .eq(user.id)
+                        .and(user_roles::role.eq_any(remove)),
+                ),
+            )
+            .execute(&db_conn)
+            .context("failed to remove broken roles")?;
+        }
+
+        if !roles.is_empty() {
+            #[allow(clippy::cast_sign_loss)]
+            ret.insert(
+                DisplayUser {
+                    user_id: UserId(user_id as u64),
+                    alias: user.alias,
+                },
+                roles,
+            );
+        }
+    }
+
+    Ok(ret)
+}
+
 fn add_user(
     user: UserId,
     guild: GuildId,
@@ -148,7 +371,66 @@ fn add_user(
     })
 }
 
-fn get_roles(user: &User, db: &DbPool) -> Result<BTreeSet<Role>> {
+/// Check whether a user other than the given one already goes by an alias
+fn alias_taken(user: UserId, guild: GuildId, name: &str, db: &DbPool) -> Result<bool> {
+    use crate::schema::users::dsl::{alias, guild_id, user_id, users};
+
+    if name == UNKNOWN_ALIAS {
+        return Ok(true);
+    }
+
+    let db_conn = db.get().context("failed to connect to the database")?;
+
+    #[allow(clippy::cast_possible_wrap)]
+    diesel::select(diesel::dsl::exists(
+        users.filter(
+            guild_id
+                .eq(guild.0 as i64)
+                .and(alias.eq(name))
+                .and(user_id.ne(user.0 as i64)),
+        ),
+    ))
+    .get_result(&db_conn)
+    .context("failed to query for existing user aliases")
+}
+
+fn set_alias(user: &User, new_alias: &str, db: &DbPool) -> Result<()> {
+    use crate::schema::users::dsl::{alias, id, users};
+
+    let db_conn = db.get().context("failed to connect to the database")?;
+
+    diesel::update(users.filter(id.eq(user.id)))
+        .set(alias.eq(new_alias))
+        .execute(&db_conn)
+        .context("failed to update user alias")?;
+
+    Ok(())
+}
+
+/// Stop tracking a user once they have neither a role nor an alias
+fn delete_if_unused(user: &User, db: &DbPool) -> Result<()> {
+    use crate::schema::{user_roles, users};
+
+    let db_conn = db.get().context("failed to connect to the database")?;
+
+    // TODO: make this a postgres hook?
+    if diesel::select(diesel::dsl::exists(
+        user_roles::table.filter(user_roles::user_id.eq(user.id)),
+    ))
+    .get_result(&db_conn)
+    .context("failed to query for remaining roles")?
+    {
+        return Ok(());
+    }
+
+    diesel::delete(users::table.filter(users::id.eq(user.id).and(users::alias.eq(UNKNOWN_ALIAS))))
+        .execute(&db_conn)
+        .context("failed to delete orphaned user")?;
+
+    Ok(())
+}
+
+pub fn get_roles(user: &User, db: &DbPool) -> Result<BTreeSet<Role>> {
     use crate::schema::user_roles::dsl::{role, user_id, user_roles};
 
     let db_conn = db.get().context("failed to connect to the database")?;
@@ -218,19 +500,7 @@ fn delete_roles(user: &User, roles: BTreeSet<Role>, db: &DbPool) -> Result<()> {
     .execute(&db_conn)
     .context("failed to delete roles")?;
 
-    // TODO: make this a postgres hook?
-    if !diesel::select(diesel::dsl::exists(user_roles.filter(user_id.eq(user.id))))
-        .get_result(&db_conn)
-        .context("failed to query for remaining roles")?
-    {
-        use crate::schema::users::dsl::{id, users};
-
-        diesel::delete(users.filter(id.eq(user.id)))
-            .execute(&db_conn)
-            .context("failed to delete orphaned user")?;
-    }
-
-    Ok(())
+    delete_if_unused(user, db)
 }
 
 pub fn execute(
@@ -239,6 +509,7 @@ pub fn execute(
     guild: Option<GuildId>,
     db: &DbPool,
     superuser: UserId,
+    lang: Language,
 ) -> RoleCommandResult<RoleCommandOk>
 {
     let is_super = sender == superuser;
@@ -266,8 +537,8 @@ pub fn execute(
     };
 
     Ok(match command {
-        RoleCommand::Help(topic) => RoleCommandOk::Help(RoleCommand::help(topic)),
-        RoleCommand::List => RoleCommandOk::List(Role::help(None)),
+        RoleCommand::Help(topic) => RoleCommandOk::Help(RoleCommand::help_in(topic, lang.code())),
+        RoleCommand::List => RoleCommandOk::List(Role::help_in(None, lang.code())),
         RoleCommand::Show(target) => {
             let guild = get_guild()?;
             let (_, sender_roles) = get_sender(guild)?;
@@ -282,13 +553,15 @@ pub fn execute(
 
                     RoleCommandOk::ShowOne(
                         DisplayUser {
-                            alias: target.map_or_else(|| "???".into(), |t| t.alias),
+                            alias: target.map_or_else(|| UNKNOWN_ALIAS.into(), |t| t.alias),
                             user_id: t,
                         },
                         target_roles,
                     )
                 },
-                None => RoleCommandOk::ShowAll(todo!()),
+                None => RoleCommandOk::ShowAll(
+                    get_all_roles(guild, db).context("failed to get assigned roles")?,
+                ),
             }
         },
         RoleCommand::Add(target_id, roles) => {
@@ -315,7 +588,7 @@ pub fn execute(
             let num = to_add.len();
 
             let target = target
-                .map_or_else(|| add_user(target_id, guild, "???", db), Ok)
+                .map_or_else(|| add_user(target_id, guild, UNKNOWN_ALIAS, db), Ok)
                 .context("failed to add new user entry for target")?;
 
             insert_roles(&target, to_add, db).context("failed to add roles to target")?;
@@ -354,5 +627,43 @@ pub fn execute(
                 RoleCommandOk::Removed(0)
             }
         },
+        RoleCommand::Alias(target_id, name) => {
+            let guild = get_guild()?;
+            let (_, sender_roles) = get_sender(guild)?;
+
+            if !is_super && !sender_roles.contains(&Role::Admin) {
+                return Err(NoPermissionError::Alias.into());
+            }
+
+            let target = get_user(target_id, guild, db).context("failed to get target")?;
+
+            if let Some(name) = name {
+                let name = name.strip_prefix('@').unwrap_or(&name);
+
+                if alias_taken(target_id, guild, name, db)
+                    .context("failed to check target alias")?
+                {
+                    return Err(RoleCommandError::AliasTaken(name.into()));
+                }
+
+                match target {
+                    Some(t) => set_alias(&t, name, db).context("failed to set target alias")?,
+                    None => {
+                        add_user(target_id, guild, name, db)
+                            .context("failed to add new user entry for target")?;
+                    },
+                }
+
+                RoleCommandOk::AliasSet
+            } else {
+                if let Some(target) = target {
+                    set_alias(&target, UNKNOWN_ALIAS, db)
+                        .context("failed to clear target alias")?;
+                    delete_if_unused(&target, db).context("failed to clean up target")?;
+                }
+
+                RoleCommandOk::AliasCleared
+            }
+        },
     })
 }
@@ -1,12 +1,651 @@
-use docbot::Docbot;
+use super::{
+    channels,
+    channels::ChannelMode,
+    language::Language,
+    roles,
+    roles::{NoPermissionError, Role},
+};
+use crate::{
+    commands::ArgContext,
+    db::{
+        models::{Announcement, NewAnnouncement},
+        DbPool,
+    },
+    error::Result,
+    util::ShortId,
+};
+use anyhow::Context;
+use chrono::{DateTime, Duration, NaiveDateTime, SecondsFormat, Utc};
+use diesel::{
+    prelude::*,
+    result::{DatabaseErrorKind, Error as DieselError},
+};
+use docbot::{prelude::*, HelpTopic};
+use lazy_static::lazy_static;
+use regex::Regex;
+use serenity::model::id::{ChannelId, GuildId, UserId};
+use std::{
+    fmt::{self, Display, Formatter},
+    str::FromStr,
+};
+use thiserror::Error;
+use uuid::Uuid;
+
+const ANNOUNCEMENT_ID_LEN: usize = 6;
+const MAX_ANNOUNCEMENT_ID_ATTEMPTS: usize = 8;
+const MAX_DURATION_SECS: i64 = 10 * 365 * 24 * 60 * 60;
+
+/// How late an announcement can be delivered before it counts as missed
+pub const MISSED_GRACE_SECS: i64 = 15 * 60;
 
 #[derive(Docbot, Debug)]
-/// TODO
+#[docbot(context = "ArgContext")]
+/// Schedule announcements to be sent to announcement channels.  All times are
+/// in UTC.
+///
+/// If the bot is offline when an announcement is due, it is still sent as long
+/// as the bot comes back within 15 minutes.  Otherwise, missed one-time
+/// announcements are paused, and missed repeating announcements skip ahead to
+/// their next run.
+///
+/// # Locale fr
+/// Programmer des annonces à envoyer dans les salons d'annonces.  Toutes les
+/// heures sont en UTC.
+///
+/// Si le bot est hors ligne lorsqu'une annonce doit être envoyée, elle est tout de
+/// même envoyée s'il revient dans les 15 minutes.  Sinon, les annonces uniques
+/// manquées sont mises en pause, et les annonces répétées manquées passent
+/// directement à leur prochain envoi.
 pub enum ScheduleCommand {
     /// help [command]
     /// Get help with scheduling, or a particular schedule subcommand
     ///
     /// # Arguments
     /// command: The name of a subcommand to get info for
+    ///
+    /// # Locale fr
+    /// Obtenir de l'aide sur la programmation, ou sur une sous-commande
+    ///
+    /// # Arguments
+    /// command: Le nom d'une sous-commande
     Help(Option<ScheduleCommandId>),
+
+    /// (list|ls)
+    /// List all scheduled announcements
+    ///
+    /// # Locale fr
+    /// Afficher toutes les annonces programmées
+    #[docbot(requires = "mod")]
+    List,
+
+    /// show <id>
+    /// Show the details of a scheduled announcement
+    ///
+    /// # Arguments
+    /// id: The ID of the announcement to show
+    ///
+    /// # Locale fr
+    /// Afficher les détails d'une annonce programmée
+    ///
+    /// # Arguments
+    /// id: L'identifiant de l'annonce à afficher
+    #[docbot(requires = "mod")]
+    Show(AnnouncementId),
+
+    /// add [-r|--repeat <interval>] <channel> <time> <message...>
+    /// Schedule an announcement, optionally repeating it
+    ///
+    /// # Arguments
+    /// --repeat: How often to repeat the announcement.  Either hourly, daily,
+    ///           weekly, or a duration such as 1d12h.  If omitted, the
+    ///           announcement is only sent once
+    /// channel: The channel to send the announcement in.  Must be an
+    ///          announcements channel
+    /// time: When to send the announcement, e.g. 2020-11-05T18:30 or +1h30m
+    ///       for a delay from now
+    /// message: The contents of the announcement
+    ///
+    /// # Examples
+    /// add <#123456789012345678> +1h30m The meeting starts soon!
+    /// add --repeat weekly <#123456789012345678> 2020-11-05T18:30 Weekly reminder
+    ///
+    /// # Locale fr
+    /// Programmer une annonce, éventuellement répétée
+    ///
+    /// # Arguments
+    /// --repeat: La fréquence de répétition de l'annonce.  Soit hourly, daily,
+    ///           weekly, soit une durée comme 1d12h.  Sans cette option, l'annonce
+    ///           n'est envoyée qu'une fois
+    /// channel: Le salon dans lequel envoyer l'annonce.  Doit être un salon
+    ///          d'annonces
+    /// time: Quand envoyer l'annonce, par exemple 2020-11-05T18:30, ou +1h30m pour
+    ///       un délai à partir de maintenant
+    /// message: Le contenu de l'annonce
+    #[docbot(requires = "admin")]
+    Add(
+        #[docbot(validate = "Interval::validate")] Option<Interval>,
+        #[docbot(hint = "channel mention or name")] ChannelId,
+        ScheduleTime,
+        Vec<String>,
+    ),
+
+    /// (remove|rm) <id>
+    /// Delete a scheduled announcement
+    ///
+    /// # Arguments
+    /// id: The ID of the announcement to delete
+    ///
+    /// # Locale fr
+    /// Supprimer une annonce programmée
+    ///
+    /// # Arguments
+    /// id: L'identifiant de l'annonce à supprimer
+    #[docbot(requires = "admin")]
+    Remove(AnnouncementId),
+
+    /// pause <id>
+    /// Stop sending a scheduled announcement until it is resumed
+    ///
+    /// # Arguments
+    /// id: The ID of the announcement to pause
+    ///
+    /// # Locale fr
+    /// Ne plus envoyer une annonce programmée jusqu'à ce qu'elle soit reprise
+    ///
+    /// # Arguments
+    /// id: L'identifiant de l'annonce à mettre en pause
+    #[docbot(requires = "admin")]
+    Pause(AnnouncementId),
+
+    /// (resume|unpause) <id>
+    /// Resume a paused announcement.  One-time announcements whose time has
+    /// passed are sent immediately.
+    ///
+    /// # Arguments
+    /// id: The ID of the announcement to resume
+    ///
+    /// # Locale fr
+    /// Reprendre une annonce en pause.  Les annonces uniques dont l'heure est passée
+    /// sont envoyées immédiatement.
+    ///
+    /// # Arguments
+    /// id: L'identifiant de l'annonce à reprendre
+    #[docbot(requires = "admin")]
+    Resume(AnnouncementId),
+}
+
+/// A short identifier for a scheduled announcement
+pub type AnnouncementId = ShortId;
+
+/// A point in time to schedule an announcement for
+#[derive(Clone, Copy, Debug)]
+pub struct ScheduleTime(pub DateTime<Utc>);
+
+#[derive(Error, Debug)]
+#[error("invalid time {0:?}, expected e.g. 2020-11-05T18:30 or +1h30m")]
+pub struct InvalidTime(String);
+
+impl FromStr for ScheduleTime {
+    type Err = InvalidTime;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.eq_ignore_ascii_case("now") {
+            return Ok(Self(Utc::now()));
+        }
+
+        if let Some(delay) = s.strip_prefix('+') {
+            return parse_duration(delay)
+                .map(|d| Self(Utc::now() + d))
+                .ok_or_else(|| InvalidTime(s.into()));
+        }
+
+        if let Ok(time) = DateTime::parse_from_rfc3339(s) {
+            return Ok(Self(time.with_timezone(&Utc)));
+        }
+
+        for fmt in &[
+            "%Y-%m-%dT%H:%M",
+            "%Y-%m-%dT%H:%M:%S",
+            "%Y-%m-%d %H:%M",
+            "%Y-%m-%d %H:%M:%S",
+        ] {
+            if let Ok(time) = NaiveDateTime::parse_from_str(s, fmt) {
+                return Ok(Self(DateTime::from_utc(time, Utc)));
+            }
+        }
+
+        Err(InvalidTime(s.into()))
+    }
+}
+
+impl Display for ScheduleTime {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.write_str(&self.0.to_rfc3339_opts(SecondsFormat::AutoSi, true))
+    }
+}
+
+/// The time between runs of a repeating announcement
+#[derive(Clone, Copy, Debug)]
+pub struct Interval(pub Duration);
+
+#[derive(Error, Debug)]
+pub enum InvalidInterval {
+    #[error("invalid interval {0:?}, expected e.g. daily or 1d12h")]
+    Format(String),
+    #[error("interval {0:?} is shorter than one minute")]
+    TooShort(String),
+}
+
+impl FromStr for Interval {
+    type Err = InvalidInterval;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let duration = match s.to_lowercase().as_str() {
+            "hourly" => Duration::hours(1),
+            "daily" => Duration::days(1),
+            "weekly" => Duration::weeks(1),
+            s => parse_duration(s).ok_or_else(|| InvalidInterval::Format(s.into()))?,
+        };
+
+        Ok(Self(duration))
+    }
+}
+
+impl Display for Interval {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.write_str(&format_duration(self.0.num_seconds()).replace(' ', ""))
+    }
+}
+
+impl Interval {
+    /// Check that an announcement repeating at this interval would not spam
+    /// its channel
+    ///
+    /// # Errors
+    /// Returns an error if the interval is shorter than one minute.
+    pub fn validate(&self) -> Result<(), InvalidInterval> {
+        if self.0 < Duration::minutes(1) {
+            return Err(InvalidInterval::TooShort(format_duration(
+                self.0.num_seconds(),
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+fn parse_duration(s: &str) -> Option<Duration> {
+    lazy_static! {
+        static ref DURATION_RE: Regex =
+            Regex::new(r"^(?:(\d+)w)?(?:(\d+)d)?(?:(\d+)h)?(?:(\d+)m)?(?:(\d+)s)?$").unwrap();
+    }
+
+    let s = s.to_lowercase();
+    let caps = DURATION_RE.captures(&s)?;
+
+    if s.is_empty() {
+        return None;
+    }
+
+    let mut secs = 0_i64;
+
+    for (i, unit) in [7 * 24 * 60 * 60, 24 * 60 * 60, 60 * 60, 60, 1]
+        .iter()
+        .enumerate()
+    {
+        if let Some(cap) = caps.get(i + 1) {
+            secs = secs.checked_add(cap.as_str().parse::<i64>().ok()?.checked_mul(*unit)?)?;
+        }
+    }
+
+    if secs > MAX_DURATION_SECS {
+        return None;
+    }
+
+    Some(Duration::seconds(secs))
+}
+
+/// Format a number of seconds as a human-readable duration, e.g. `1d 12h`
+#[must_use]
+pub fn format_duration(secs: i64) -> String {
+    let mut ret = Vec::new();
+    let mut secs = secs;
+
+    for (unit, name) in &[
+        (7 * 24 * 60 * 60, "w"),
+        (24 * 60 * 60, "d"),
+        (60 * 60, "h"),
+        (60, "m"),
+        (1, "s"),
+    ] {
+        if secs >= *unit {
+            ret.push(format!("{}{}", secs / unit, name));
+            secs %= unit;
+        }
+    }
+
+    if ret.is_empty() {
+        "0s".into()
+    } else {
+        ret.join(" ")
+    }
+}
+
+/// Format a time for display to users
+#[must_use]
+pub fn format_time(time: &DateTime<Utc>) -> String { time.format("%Y-%m-%d %H:%M UTC").to_string() }
+
+pub type ScheduleCommandResult<T> = Result<T, ScheduleCommandError>;
+
+pub enum ScheduleCommandOk {
+    Help(&'static HelpTopic),
+    List(Vec<Announcement>),
+    Show(Announcement),
+    Added(AnnouncementId, DateTime<Utc>),
+    Removed,
+    Paused,
+    Resumed,
+}
+
+#[derive(Error, Debug)]
+pub enum ScheduleCommandError {
+    #[error("no guild ID was provided")]
+    GuildRequired,
+    #[error("{0}")]
+    NoPermission(#[from] NoPermissionError),
+    #[error("no announcement with ID {0}")]
+    NoAnnouncement(AnnouncementId),
+    #[error("channel {0} is not an announcements channel")]
+    NotAnnouncements(ChannelId),
+    #[error("time {0} is in the past")]
+    InPast(DateTime<Utc>),
+    #[error("an unexpected error occurred")]
+    Other(#[from] anyhow::Error),
+}
+
+fn add_announcement(
+    guild: GuildId,
+    channel: ChannelId,
+    message: String,
+    next_run: DateTime<Utc>,
+    repeat_secs: Option<i64>,
+    db: &DbPool,
+) -> Result<AnnouncementId>
+{
+    use crate::schema::announcements::dsl::announcements;
+
+    let db_conn = db.get().context("failed to connect to the database")?;
+
+    for _ in 0..MAX_ANNOUNCEMENT_ID_ATTEMPTS {
+        let announcement = AnnouncementId::random(ANNOUNCEMENT_ID_LEN);
+
+        #[allow(clippy::cast_possible_wrap)]
+        match diesel::insert_into(announcements)
+            .values(vec![NewAnnouncement {
+                id: Uuid::new_v4(),
+                announcement_id: announcement.to_string(),
+                guild_id: guild.0 as i64,
+                channel_id: channel.0 as i64,
+                message: message.clone(),
+                next_run,
+                repeat_secs,
+            }])
+            .execute(&db_conn)
+        {
+            Ok(_) => return Ok(announcement),
+            Err(DieselError::DatabaseError(DatabaseErrorKind::UniqueViolation, _)) => (),
+            Err(e) => Err(e).context("failed to insert new announcement")?,
+        }
+    }
+
+    Err(anyhow::anyhow!(
+        "failed to generate a unique announcement ID"
+    ))
+}
+
+fn get_announcement(
+    announcement: &AnnouncementId,
+    guild: GuildId,
+    db: &DbPool,
+) -> Result<Option<Announcement>>
+{
+    use crate::schema::announcements::dsl::{announcement_id, announcements, guild_id};
+
+    let db_conn = db.get().context("failed to connect to the database")?;
+
+    #[allow(clippy::cast_possible_wrap)]
+    match announcements
+        .filter(
+            announcement_id
+                .eq(announcement.as_str())
+                .and(guild_id.eq(guild.0 as i64)),
+        )
+        .first::<Announcement>(&db_conn)
+    {
+        Ok(a) => Ok(Some(a)),
+        Err(DieselError::NotFound) => Ok(None),
+        Err(e) => Err(e).context("failed to retrieve announcement from database"),
+    }
+}
+
+fn list_announcements(guild: GuildId, db: &DbPool) -> Result<Vec<Announcement>> {
+    use crate::schema::announcements::dsl::{announcements, guild_id, next_run};
+
+    let db_conn = db.get().context("failed to connect to the database")?;
+
+    #[allow(clippy::cast_possible_wrap)]
+    let ret = announcements
+        .filter(guild_id.eq(guild.0 as i64))
+        .order(next_run.asc())
+        .load::<Announcement>(&db_conn)
+        .context("failed to retrieve announcements from database")?;
+
+    Ok(ret)
+}
+
+fn delete_announcement(announcement: &Announcement, db: &DbPool) -> Result<()> {
+    use crate::schema::announcements::dsl::{announcements, id};
+
+    let db_conn = db.get().context("failed to connect to the database")?;
+
+    diesel::delete(announcements.filter(id.eq(announcement.id)))
+        .execute(&db_conn)
+        .context("failed to delete announcement")?;
+
+    Ok(())
+}
+
+fn update_announcement(
+    announcement: &Announcement,
+    paused: bool,
+    run: DateTime<Utc>,
+    db: &DbPool,
+) -> Result<()>
+{
+    use crate::schema::announcements::dsl::{announcements, id, is_paused, next_run};
+
+    let db_conn = db.get().context("failed to connect to the database")?;
+
+    diesel::update(announcements.filter(id.eq(announcement.id)))
+        .set((is_paused.eq(paused), next_run.eq(run)))
+        .execute(&db_conn)
+        .context("failed to update announcement")?;
+
+    Ok(())
+}
+
+/// Get all unpaused announcements due at or before the given time
+pub fn get_due(now: DateTime<Utc>, db: &DbPool) -> Result<Vec<Announcement>> {
+    use crate::schema::announcements::dsl::{announcements, is_paused, next_run};
+
+    let db_conn = db.get().context("failed to connect to the database")?;
+
+    let ret = announcements
+        .filter(is_paused.eq(false).and(next_run.le(now)))
+        .order(next_run.asc())
+        .load::<Announcement>(&db_conn)
+        .context("failed to retrieve due announcements from database")?;
+
+    Ok(ret)
+}
+
+/// Returns true if an announcement is too far past due to be delivered
+#[must_use]
+pub fn is_missed(announcement: &Announcement, now: DateTime<Utc>) -> bool {
+    now - announcement.next_run > Duration::seconds(MISSED_GRACE_SECS)
+}
+
+/// Update a due announcement after attempting to deliver it.  Repeating
+/// announcements skip ahead to their first run after `now`, so missed runs are
+/// never sent in a burst.  One-time announcements are deleted once delivered,
+/// or paused if they could not be delivered.
+pub fn reschedule(
+    announcement: &Announcement,
+    delivered: bool,
+    now: DateTime<Utc>,
+    db: &DbPool,
+) -> Result<()>
+{
+    match announcement.repeat_secs {
+        Some(secs) => {
+            let secs = secs.max(1);
+            let missed = (now - announcement.next_run).num_seconds() / secs;
+            let run = announcement.next_run + Duration::seconds(secs * (missed + 1));
+
+            update_announcement(announcement, false, run, db)
+        },
+        None if delivered => delete_announcement(announcement, db),
+        None => update_announcement(announcement, true, announcement.next_run, db),
+    }
+}
+
+fn schedule(
+    guild: GuildId,
+    channel: ChannelId,
+    time: ScheduleTime,
+    message: Vec<String>,
+    repeat_secs: Option<i64>,
+    db: &DbPool,
+) -> ScheduleCommandResult<ScheduleCommandOk>
+{
+    let (mode, _) =
+        channels::get_channel_mode(channel, guild, db).context("failed to get channel mode")?;
+
+    if mode != ChannelMode::Announcements {
+        return Err(ScheduleCommandError::NotAnnouncements(channel));
+    }
+
+    if time.0 < Utc::now() - Duration::minutes(1) {
+        return Err(ScheduleCommandError::InPast(time.0));
+    }
+
+    let id = add_announcement(guild, channel, message.join(" "), time.0, repeat_secs, db)
+        .context("failed to add announcement")?;
+
+    Ok(ScheduleCommandOk::Added(id, time.0))
+}
+
+pub fn execute(
+    command: ScheduleCommand,
+    sender: UserId,
+    guild: Option<GuildId>,
+    db: &DbPool,
+    superuser: UserId,
+    lang: Language,
+) -> ScheduleCommandResult<ScheduleCommandOk>
+{
+    let is_super = sender == superuser;
+
+    let get_guild = || guild.ok_or(ScheduleCommandError::GuildRequired);
+
+    let get_sender_roles = |guild| -> ScheduleCommandResult<_> {
+        Ok(roles::get_user_roles(sender, guild, db).context("failed to get sender permissions")?)
+    };
+
+    let check_show = |guild| -> ScheduleCommandResult<_> {
+        if !is_super && get_sender_roles(guild)?.is_empty() {
+            return Err(NoPermissionError::ShowSchedule.into());
+        }
+
+        Ok(())
+    };
+
+    let check_edit = |guild| -> ScheduleCommandResult<_> {
+        if !is_super && !get_sender_roles(guild)?.contains(&Role::Admin) {
+            return Err(NoPermissionError::EditSchedule.into());
+        }
+
+        Ok(())
+    };
+
+    let get_target = |id: AnnouncementId, guild| -> ScheduleCommandResult<_> {
+        get_announcement(&id, guild, db)
+            .context("failed to get announcement")?
+            .ok_or(ScheduleCommandError::NoAnnouncement(id))
+    };
+
+    Ok(match command {
+        ScheduleCommand::Help(topic) => {
+            ScheduleCommandOk::Help(ScheduleCommand::help_in(topic, lang.code()))
+        },
+        ScheduleCommand::List => {
+            let guild = get_guild()?;
+            check_show(guild)?;
+
+            ScheduleCommandOk::List(
+                list_announcements(guild, db).context("failed to list announcements")?,
+            )
+        },
+        ScheduleCommand::Show(id) => {
+            let guild = get_guild()?;
+            check_show(guild)?;
+
+            ScheduleCommandOk::Show(get_target(id, guild)?)
+        },
+        ScheduleCommand::Add(repeat, channel, time, message) => {
+            let guild = get_guild()?;
+            check_edit(guild)?;
+
+            let repeat_secs = repeat.map(|i| i.0.num_seconds());
+
+            schedule(guild, channel, time, message, repeat_secs, db)?
+        },
+        ScheduleCommand::Remove(id) => {
+            let guild = get_guild()?;
+            check_edit(guild)?;
+
+            delete_announcement(&get_target(id, guild)?, db)
+                .context("failed to remove announcement")?;
+
+            ScheduleCommandOk::Removed
+        },
+        ScheduleCommand::Pause(id) => {
+            let guild = get_guild()?;
+            check_edit(guild)?;
+
+            let target = get_target(id, guild)?;
+            update_announcement(&target, true, target.next_run, db)
+                .context("failed to pause announcement")?;
+
+            ScheduleCommandOk::Paused
+        },
+        ScheduleCommand::Resume(id) => {
+            let guild = get_guild()?;
+            check_edit(guild)?;
+
+            let target = get_target(id, guild)?;
+
+            // Overdue one-time announcements would otherwise be paused again
+            // immediately for being missed
+            let run = match target.repeat_secs {
+                None => target.next_run.max(Utc::now()),
+                Some(_) => target.next_run,
+            };
+
+            update_announcement(&target, false, run, db)
+                .context("failed to resume announcement")?;
+
+            ScheduleCommandOk::Resumed
+        },
+    })
 }
@@ -1,25 +1,72 @@
 //! Contains the bot command definitions
 
 use crate::{
-    bot::{channels::ChannelCommand, roles::RoleCommand, schedule::ScheduleCommand},
+    bot::{
+        channels::{ChannelCommand, ChannelCommandId},
+        custom::{CustomCommand, CustomCommandId},
+        language::{LanguageCommand, LanguageCommandId},
+        modmail::{TicketCommand, TicketCommandId},
+        roles::{RoleCommand, RoleCommandId},
+        schedule::{ScheduleCommand, ScheduleCommandId},
+    },
+    db::DbPool,
     error::Result,
 };
 use docbot::{prelude::*, CommandParseError};
 use lazy_static::lazy_static;
 use regex::Regex;
+use serenity::model::guild::Guild;
+use thiserror::Error;
+
+/// State used to resolve names given as command arguments, such as channel
+/// names or user aliases
+pub struct ArgContext {
+    /// A snapshot of the guild the command was sent in, if any
+    pub guild: Option<Guild>,
+    /// The database connection pool, for looking up bot-assigned aliases
+    pub db: DbPool,
+}
+
+/// Error type for an argument which could not be resolved by name
+#[derive(Error, Debug)]
+pub enum ArgResolveError {
+    #[error("{0} names can only be used in a server")]
+    GuildRequired(&'static str),
+    #[error("no {0} found matching {1:?}")]
+    NotFound(&'static str, String),
+    #[error("more than one {0} matches {1:?}")]
+    Ambiguous(&'static str, String),
+    #[error("an unexpected error occurred")]
+    Other(#[from] anyhow::Error),
+}
 
 #[derive(Docbot, Debug)]
+#[docbot(root, context = "ArgContext")]
 /// TODO: document `BaseCommand`
 pub enum BaseCommand {
-    /// help [command]
+    /// help [command...]
     /// Display information about the bot, or get help on a particular command
     ///
     /// # Arguments
-    /// command: The name of a command to get info for
-    Help(Option<BaseCommandId>),
+    /// command: The name of a command to get info for, followed by the names
+    ///          of any subcommands
+    ///
+    /// # Examples
+    /// help roles add
+    ///
+    /// # Locale fr
+    /// Afficher des informations sur le bot, ou obtenir de l'aide sur une
+    /// commande
+    ///
+    /// # Arguments
+    /// command: Le nom d'une commande, suivi du nom de ses sous-commandes
+    Help(#[docbot(hint = "command name")] Vec<String>),
 
     /// version
     /// Display the bot version and build info
+    ///
+    /// # Locale fr
+    /// Afficher la version du bot et ses informations de compilation
     Version,
 
     /// roles <subcommand...>
@@ -27,6 +74,13 @@ pub enum BaseCommand {
     ///
     /// # Arguments
     /// subcommand: The subcommand to run.  Run [`roles help`]() for more info
+    ///
+    /// # Locale fr
+    /// Gérer les rôles propres au bot
+    ///
+    /// # Arguments
+    /// subcommand: La sous-commande à lancer.  Lancez [`roles help`]() pour
+    ///             plus d'informations
     Role(#[docbot(subcommand)] RoleCommand),
 
     /// channels <subcommand...>
@@ -34,6 +88,13 @@ pub enum BaseCommand {
     ///
     /// # Arguments
     /// subcommand: The subcommand to run.  Run [`channels help`]() for more info
+    ///
+    /// # Locale fr
+    /// Gérer le comportement du bot dans chaque salon
+    ///
+    /// # Arguments
+    /// subcommand: La sous-commande à lancer.  Lancez [`channels help`]()
+    ///             pour plus d'informations
     Channel(#[docbot(subcommand)] ChannelCommand),
 
     /// schedule <subcommand...>
@@ -42,8 +103,44 @@ pub enum BaseCommand {
     /// # Arguments
     /// subcommand: The subcommand to run.  Run [`schedule help`]() for more
     ///             info
+    ///
+    /// # Locale fr
+    /// Gérer les annonces programmées
+    ///
+    /// # Arguments
+    /// subcommand: La sous-commande à lancer.  Lancez [`schedule help`]()
+    ///             pour plus d'informations
     Schedule(#[docbot(subcommand)] ScheduleCommand),
 
+    /// custom <subcommand...>
+    /// Manage text commands specific to this server
+    ///
+    /// # Arguments
+    /// subcommand: The subcommand to run.  Run [`custom help`]() for more info
+    ///
+    /// # Locale fr
+    /// Gérer les commandes textuelles propres à ce serveur
+    ///
+    /// # Arguments
+    /// subcommand: La sous-commande à lancer.  Lancez [`custom help`]() pour
+    ///             plus d'informations
+    Custom(#[docbot(subcommand)] CustomCommand),
+
+    /// (language|lang) <subcommand...>
+    /// Choose the language the bot uses in this server
+    ///
+    /// # Arguments
+    /// subcommand: The subcommand to run.  Run [`language help`]() for more
+    ///             info
+    ///
+    /// # Locale fr
+    /// Choisir la langue utilisée par le bot sur ce serveur
+    ///
+    /// # Arguments
+    /// subcommand: La sous-commande à lancer.  Lancez [`language help`]()
+    ///             pour plus d'informations
+    Language(#[docbot(subcommand)] LanguageCommand),
+
     /// (modmail|mm) <message...>
     /// Send a message to the moderators without any personal data attached
     ///
@@ -56,33 +153,142 @@ pub enum BaseCommand {
     /// # Arguments
     /// message: The contents of the message to send.  Only this and an
     ///          anonymous ticket ID will be displayed in the sent message.
+    ///
+    /// # See also
+    /// - `tickets reply`
+    /// - `tickets close`
+    ///
+    /// # Locale fr
+    /// Envoyer un message aux modérateurs sans y joindre de données personnelles
+    ///
+    /// # Description
+    /// Envoyer un message aux modérateurs du serveur, qui peuvent le lire et y
+    /// répondre sans que le bot ne leur transmette d'information permettant de
+    /// vous identifier.  **Attention, Discord n'est pas un moyen de
+    /// communication sécurisé.  Nous ne sommes pas responsables de la manière
+    /// dont Discord traite vos données.**
+    ///
+    /// # Arguments
+    /// message: Le contenu du message à envoyer.  Seul ce texte et un
+    ///          identifiant de ticket anonyme apparaîtront dans le message
+    ///          envoyé.
     Modmail(Vec<String>),
+
+    /// (tickets|ticket) <subcommand...>
+    /// Follow up on or manage modmail tickets
+    ///
+    /// # Overview
+    /// Replies from the moderators are sent to you as a direct message.  Run
+    /// [`tickets help reply`]() to see how to answer them.
+    ///
+    /// # Arguments
+    /// subcommand: The subcommand to run.  Run [`tickets help`]() for more info
+    ///
+    /// # Locale fr
+    /// Suivre ou gérer les tickets de modmail
+    ///
+    /// # Description
+    /// Les réponses des modérateurs vous sont envoyées en message privé.
+    /// Lancez [`tickets help reply`]() pour savoir comment y répondre.
+    ///
+    /// # Arguments
+    /// subcommand: La sous-commande à lancer.  Lancez [`tickets help`]() pour
+    ///             plus d'informations
+    Ticket(#[docbot(subcommand)] TicketCommand),
 }
 
 lazy_static! {
-    static ref COMMAND_ARG_RE: Regex =
-        Regex::new(r#"\s*(?:([^'"]\S*)|'([^']*)'|"((?:[^"\\]|\\.)*)")"#).unwrap();
-    static ref COMMAND_DQUOTE_ESCAPE_RE: Regex = Regex::new(r"\\(.)").unwrap();
     static ref USER_MENTION_RE: Regex = Regex::new(r"^\s*<@!(\d+)>\s*$").unwrap();
 }
 
-/// Parse a base command from a string
+/// Parse a base command from a string, resolving arguments with the given
+/// context
 /// # Errors
-/// Returns an error if the command parser failed to find a matching command for
-/// the given strings, if a syntax error occurred while parsing the command, or
-/// if parsing any arguments returned an error.
-pub fn parse_base<S: AsRef<str>>(s: S) -> Result<BaseCommand, CommandParseError> {
-    let tokens = COMMAND_ARG_RE.captures_iter(s.as_ref()).map(|cap| {
-        cap.get(3).map_or_else(
-            || {
-                cap.get(2)
-                    .unwrap_or_else(|| cap.get(1).unwrap())
-                    .as_str()
-                    .into()
-            },
-            |dquote| COMMAND_DQUOTE_ESCAPE_RE.replace_all(dquote.as_str(), "$1"),
-        )
-    });
-
-    BaseCommand::parse(tokens)
+/// Returns an error if the string contained an unterminated quote, if the
+/// command parser failed to find a matching command for the given strings, if
+/// a syntax error occurred while parsing the command, or if parsing any
+/// arguments returned an error.
+pub fn parse_base<S: AsRef<str>>(s: S, ctx: &ArgContext) -> Result<BaseCommand, CommandParseError> {
+    BaseCommand::parse_with(ctx, docbot::tokenize(s.as_ref())?)
+}
+
+/// Get the canonical names of a parsed command and its subcommand if either
+/// was abbreviated in the input, so the user can see how it was interpreted.
+/// Arguments are left out, since they can be private, such as the body of a
+/// modmail message.
+#[must_use]
+pub fn expand_abbreviations(input: &str, cmd: &BaseCommand) -> Option<String> {
+    let words: Vec<_> = docbot::tokenize(input)
+        .ok()?
+        .into_iter()
+        .map(|t| t.text)
+        .collect();
+    let (len, base) = docbot::match_id::<BaseCommandId, _>(&words)?;
+    let rest = &words[len..];
+
+    // Every subcommand immediately follows the name of its parent command
+    let sub = match cmd {
+        BaseCommand::Role(c) => {
+            docbot::match_id::<RoleCommandId, _>(rest).map(|m| (m, c.id().to_str()))
+        },
+        BaseCommand::Channel(c) => {
+            docbot::match_id::<ChannelCommandId, _>(rest).map(|m| (m, c.id().to_str()))
+        },
+        BaseCommand::Schedule(c) => {
+            docbot::match_id::<ScheduleCommandId, _>(rest).map(|m| (m, c.id().to_str()))
+        },
+        BaseCommand::Custom(c) => {
+            docbot::match_id::<CustomCommandId, _>(rest).map(|m| (m, c.id().to_str()))
+        },
+        BaseCommand::Language(c) => {
+            docbot::match_id::<LanguageCommandId, _>(rest).map(|m| (m, c.id().to_str()))
+        },
+        BaseCommand::Ticket(c) => {
+            docbot::match_id::<TicketCommandId, _>(rest).map(|m| (m, c.id().to_str()))
+        },
+        BaseCommand::Help(_) | BaseCommand::Version | BaseCommand::Modmail(_) => None,
+    };
+
+    let mut names = vec![cmd.id().to_str()];
+    let mut abbrev = base;
+
+    if let Some(((_, sub_abbrev), name)) = sub {
+        names.push(name);
+        abbrev |= sub_abbrev;
+    }
+
+    if abbrev {
+        Some(names.join(" "))
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn expand(input: &str) -> Option<String> {
+        let cmd = BaseCommand::parse(docbot::tokenize(input).unwrap()).unwrap();
+
+        expand_abbreviations(input, &cmd)
+    }
+
+    #[test]
+    fn expands_names() {
+        assert_eq!(expand("rol ls").as_deref(), Some("roles list"));
+        assert_eq!(expand("roles l").as_deref(), Some("roles list"));
+        assert_eq!(expand("roles list"), None);
+        assert_eq!(expand("ver").as_deref(), Some("version"));
+    }
+
+    #[test]
+    fn never_echoes_arguments() {
+        assert_eq!(expand("mod hi").as_deref(), Some("modmail"));
+        assert_eq!(expand("mo secret text").as_deref(), Some("modmail"));
+        assert_eq!(
+            expand("ti rep ABCD secret").as_deref(),
+            Some("tickets reply")
+        );
+    }
 }
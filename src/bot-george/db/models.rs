@@ -1,6 +1,10 @@
-use crate::schema::{user_roles, users};
+use crate::schema::{
+    announcements, channel_modes, channels, custom_commands, default_channel_modes,
+    guild_languages, modmail_inboxes, modmail_tickets, user_roles, users,
+};
+use chrono::{DateTime, Utc};
 use diesel::Queryable;
-use serenity::model::id::UserId;
+use serenity::model::id::{ChannelId, UserId};
 use std::{
     cmp::PartialEq,
     hash::{Hash, Hasher},
@@ -15,6 +19,7 @@ pub struct User {
     pub alias: String,
 }
 
+#[derive(PartialEq, Eq, Hash)]
 pub struct DisplayUser {
     pub user_id: UserId,
     pub alias: String,
@@ -52,6 +57,35 @@ pub struct Channel {
     pub alias: String,
 }
 
+#[derive(PartialEq, Eq, Hash)]
+pub struct DisplayChannel {
+    pub channel_id: ChannelId,
+    pub alias: String,
+}
+
+#[derive(Insertable, Debug)]
+#[table_name = "channels"]
+pub struct NewChannel {
+    pub id: Uuid,
+    pub alias: String,
+    pub channel_id: i64,
+    pub guild_id: i64,
+}
+
+#[derive(Insertable, Debug)]
+#[table_name = "channel_modes"]
+pub struct NewChannelMode {
+    pub channel_id: Uuid,
+    pub mode: String,
+}
+
+#[derive(Insertable, Debug)]
+#[table_name = "default_channel_modes"]
+pub struct NewDefaultChannelMode {
+    pub guild_id: i64,
+    pub mode: Option<String>,
+}
+
 impl PartialEq for Channel {
     fn eq(&self, rhs: &Channel) -> bool { self.id == rhs.id }
 }
@@ -59,3 +93,84 @@ impl PartialEq for Channel {
 impl Hash for Channel {
     fn hash<H: Hasher>(&self, state: &mut H) { self.id.hash(state); }
 }
+
+///// Modmail
+
+#[derive(Queryable)]
+pub struct Ticket {
+    pub id: Uuid,
+    pub ticket_id: String,
+    pub guild_id: i64,
+    pub user_id: i64,
+    pub is_open: bool,
+}
+
+#[derive(Insertable, Debug)]
+#[table_name = "modmail_tickets"]
+pub struct NewTicket {
+    pub id: Uuid,
+    pub ticket_id: String,
+    pub guild_id: i64,
+    pub user_id: i64,
+}
+
+#[derive(Insertable, Debug)]
+#[table_name = "modmail_inboxes"]
+pub struct NewInbox {
+    pub guild_id: i64,
+    pub channel_id: i64,
+}
+
+///// Announcements
+
+#[derive(Queryable)]
+pub struct Announcement {
+    pub id: Uuid,
+    pub announcement_id: String,
+    pub guild_id: i64,
+    pub channel_id: i64,
+    pub message: String,
+    pub next_run: DateTime<Utc>,
+    pub repeat_secs: Option<i64>,
+    pub is_paused: bool,
+}
+
+#[derive(Insertable, Debug)]
+#[table_name = "announcements"]
+pub struct NewAnnouncement {
+    pub id: Uuid,
+    pub announcement_id: String,
+    pub guild_id: i64,
+    pub channel_id: i64,
+    pub message: String,
+    pub next_run: DateTime<Utc>,
+    pub repeat_secs: Option<i64>,
+}
+
+///// Custom commands
+
+#[derive(Queryable)]
+pub struct CustomResponse {
+    pub id: Uuid,
+    pub guild_id: i64,
+    pub name: String,
+    pub response: String,
+}
+
+#[derive(Insertable, Debug)]
+#[table_name = "custom_commands"]
+pub struct NewCustomResponse {
+    pub id: Uuid,
+    pub guild_id: i64,
+    pub name: String,
+    pub response: String,
+}
+
+///// Languages
+
+#[derive(Insertable, Debug)]
+#[table_name = "guild_languages"]
+pub struct NewGuildLanguage {
+    pub guild_id: i64,
+    pub language: String,
+}
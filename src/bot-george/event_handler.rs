@@ -1,17 +1,39 @@
 use crate::{
-    bot::{channels, channels::ChannelCommand, roles, roles::RoleCommand},
+    bot::{
+        channels,
+        channels::{ChannelCommand, ChannelMode},
+        custom,
+        custom::{CustomCommand, CustomCommandCache},
+        language,
+        language::{Language, LanguageCommand},
+        modmail,
+        modmail::{Recipient, TicketCommand, TicketId},
+        roles,
+        roles::{Role, RoleCommand},
+        schedule,
+        schedule::ScheduleCommand,
+        UNKNOWN_ALIAS,
+    },
     commands,
-    commands::BaseCommand,
-    db::DbPool,
+    commands::{ArgContext, BaseCommand},
+    db::{
+        models::{Announcement, DisplayUser},
+        DbPool,
+    },
     error::Result,
+    help::DiscordRenderer,
+    locale::Strings,
+    slash::{self, Interaction, SlashClient},
     util::MessageBuilderExt,
 };
 use anyhow::Context as _;
+use chrono::{DateTime, Utc};
 use dispose::defer;
-use docbot::{prelude::*, ArgumentDesc, ArgumentName, ArgumentUsage, CommandUsage, HelpTopic};
+use docbot::{prelude::*, ArgumentName, HelpRenderer, HelpTopic};
 use lazy_static::lazy_static;
-use log::{error, info};
-use regex::{Captures, Regex};
+use log::{error, info, warn};
+use regex::Regex;
+use serde_json::Value;
 use serenity::{
     async_trait,
     client::{Context, EventHandler},
@@ -19,23 +41,25 @@ use serenity::{
     model::{
         channel::{Channel, Message},
         gateway::{Activity, Ready},
-        id::{ChannelId, UserId},
-        user::OnlineStatus,
+        guild::{Guild, GuildStatus, GuildUnavailable},
+        id::{ChannelId, GuildId, UserId},
+        user::{OnlineStatus, User},
     },
     utils::MessageBuilder,
 };
 use std::{
     borrow::Cow,
-    collections::BinaryHeap,
+    collections::BTreeSet,
     fmt::{Display, Write},
+    ops::Range,
     sync::{
-        atomic::{AtomicU64, Ordering},
+        atomic::{AtomicBool, AtomicU64, Ordering},
         Arc,
     },
     thread,
+    time::Duration,
 };
-use strsim::normalized_damerau_levenshtein;
-use tokio::runtime;
+use tokio::{runtime, time};
 
 // TODO: this is here because async closures are unstable
 macro_rules! stupid_try {
@@ -59,20 +83,62 @@ macro_rules! stupid_try {
     };
 }
 
+/// The maximum number of users to list in one page of assigned roles
+const ROLES_PAGE_SIZE: usize = 15;
+
+/// The maximum number of suggestions to give for an unrecognized ID
+const MAX_SUGGESTIONS: usize = 3;
+
+/// How similar an unrecognized ID must be to a name to suggest it, from 0 to 1
+const SUGGESTION_THRESHOLD: f64 = 0.3;
+
+/// How often to check for scheduled announcements that are due
+const SCHEDULE_POLL_INTERVAL: Duration = Duration::from_secs(30);
+
 lazy_static! {
     static ref WORD_END_RE: Regex = Regex::new(r"\w$").unwrap();
 }
 
+/// Where a command came from, either a chat message or a slash command
+struct CommandSource<'a> {
+    channel_id: ChannelId,
+    guild_id: Option<GuildId>,
+    author: &'a User,
+    message: Option<&'a Message>,
+    lang: Language,
+}
+
+impl<'a> From<&'a Message> for CommandSource<'a> {
+    fn from(msg: &'a Message) -> Self {
+        Self {
+            channel_id: msg.channel_id,
+            guild_id: msg.guild_id,
+            author: &msg.author,
+            message: Some(msg),
+            lang: Language::default(),
+        }
+    }
+}
+
 pub struct Handler {
     prefix: String,
     prefix_re: Regex,
     superuser: UserId,
     pool: DbPool,
     me: AtomicU64,
+    scheduler_started: AtomicBool,
+    slash: Arc<SlashClient>,
+    custom: CustomCommandCache,
 }
 
 impl Handler {
-    pub fn new(prefix: impl AsRef<str>, superuser: UserId, pool: DbPool) -> Result<Self> {
+    pub fn new(
+        prefix: impl AsRef<str>,
+        token: impl Into<String>,
+        superuser: UserId,
+        pool: DbPool,
+    ) -> Result<Self>
+    {
         let prefix_re = Regex::new(&format!(
             r"^\s*{}{}",
             regex::escape(prefix.as_ref()),
@@ -89,13 +155,21 @@ impl Handler {
             superuser,
             pool,
             me: 0.into(),
+            scheduler_started: false.into(),
+            slash: Arc::new(SlashClient::new(token)),
+            custom: CustomCommandCache::default(),
         });
     }
 
-    // TODO: don't prefix the command if the channel is command-only
-    pub fn prefix_command<C: Display>(&self, command: C) -> String {
+    pub fn prefix_command<C: Display>(&self, command: C, mode: ChannelMode) -> String {
         let mut ret = String::new();
 
+        if let ChannelMode::Commands = mode {
+            write!(ret, "{}", command).unwrap();
+
+            return ret;
+        }
+
         write!(ret, "{}{}", self.prefix, command).unwrap();
 
         if !self.prefix_re.is_match(&ret) {
@@ -123,11 +197,16 @@ impl Handler {
         .ok();
     }
 
-    async fn send_guild_required(channel_id: ChannelId, http: impl AsRef<Http>) -> Result<()> {
+    async fn send_guild_required(
+        channel_id: ChannelId,
+        http: impl AsRef<Http>,
+        strings: &Strings,
+    ) -> Result<()>
+    {
         channel_id
             .say(
                 http,
-                "**ERROR:** This command cannot be used in a DM channel.",
+                format!("{} {}", strings.error, strings.guild_required),
             )
             .await
             .context("failed to send guild ID error message")?;
@@ -135,23 +214,70 @@ impl Handler {
         Ok(())
     }
 
+    async fn send_commands_disabled(
+        channel_id: ChannelId,
+        http: impl AsRef<Http>,
+        strings: &Strings,
+    ) -> Result<()>
+    {
+        channel_id
+            .say(
+                http,
+                format!("{} {}", strings.error, strings.commands_disabled),
+            )
+            .await
+            .context("failed to send commands disabled error message")?;
+
+        Ok(())
+    }
+
+    async fn send_commands_disabled_interaction(
+        &self,
+        interaction: &Interaction,
+        strings: &Strings,
+    ) -> Result<()>
+    {
+        self.slash
+            .respond(
+                interaction,
+                format!("{} {}", strings.error, strings.commands_disabled_here),
+            )
+            .await
+            .context("failed to send commands disabled error message")
+    }
+
     async fn send_no_permission(
         channel_id: ChannelId,
         http: impl AsRef<Http>,
         err: roles::NoPermissionError,
+        strings: &Strings,
     ) -> Result<()>
     {
-        use roles::NoPermissionError::{Add, Remove, Show};
+        use roles::NoPermissionError::{
+            Add, Alias, EditChannels, EditCustom, EditLanguage, EditSchedule, Inbox, Remove, Show,
+            ShowChannels, ShowSchedule, Tickets,
+        };
 
         channel_id
             .say(
                 http,
                 format!(
-                    "**ERROR:** You do not have permission to {}",
+                    "{} {}{}",
+                    strings.error,
+                    strings.no_permission,
                     match err {
-                        Show => "show assigned roles".into(),
-                        Add(r) => format!("add the role **{}**", r),
-                        Remove(r) => format!("remove the role **{}**", r),
+                        Show => strings.show_roles.into(),
+                        Add(r) => format!("{}**{}**", strings.add_role, r),
+                        Remove(r) => format!("{}**{}**", strings.remove_role, r),
+                        Alias => strings.edit_aliases.into(),
+                        ShowChannels => strings.show_channels.into(),
+                        EditChannels => strings.edit_channels.into(),
+                        Tickets => strings.manage_tickets.into(),
+                        Inbox => strings.configure_inbox.into(),
+                        ShowSchedule => strings.show_schedule.into(),
+                        EditSchedule => strings.edit_schedule.into(),
+                        EditCustom => strings.edit_custom.into(),
+                        EditLanguage => strings.edit_language.into(),
                     }
                 ),
             )
@@ -161,158 +287,183 @@ impl Handler {
         Ok(())
     }
 
-    fn format_help_content<'a>(&self, s: &'a str) -> Cow<'a, str> {
-        lazy_static! {
-            static ref COMMAND_RE: Regex = Regex::new(r"\[`([^`]+)`\]\(\)").unwrap();
-        }
-
-        COMMAND_RE.replace_all(s, |c: &Captures| {
-            let cmd = &c[1];
-            assert!(commands::parse_base(cmd).is_ok());
+    async fn send_alias_taken(
+        channel_id: ChannelId,
+        http: impl AsRef<Http>,
+        name: String,
+        strings: &Strings,
+    ) -> Result<()>
+    {
+        channel_id
+            .say(
+                http,
+                MessageBuilder::new()
+                    .push(strings.error)
+                    .push(" ")
+                    .push(strings.alias_taken.0)
+                    .push_mono_safer(name)
+                    .push(strings.alias_taken.1),
+            )
+            .await
+            .context("failed to send error message")?;
 
-            format!("`{}`", self.prefix_command(cmd))
-        })
+        Ok(())
     }
 
-    fn format_arg_usage(usage: &ArgumentUsage) -> String {
-        let mut ret = String::new();
-
-        ret.push(if usage.is_required { '<' } else { '[' });
-        ret.push_str(usage.name);
-        if usage.is_rest {
-            ret.push_str("...");
-        }
-        ret.push(if usage.is_required { '>' } else { ']' });
+    /// Get a check for whether the author of a command has the permission
+    /// named by a `#[docbot(requires = "...")]` attribute.  Permissions are
+    /// role names, and admins and the superuser have every permission.
+    fn permission_check(&self, src: &CommandSource<'_>) -> Result<impl Fn(&str) -> bool> {
+        let roles = match src.guild_id {
+            _ if src.author.id == self.superuser => None,
+            Some(guild) => Some(
+                roles::get_user_roles(src.author.id, guild, &self.pool)
+                    .context("failed to get user roles")?,
+            ),
+            None => Some(BTreeSet::new()),
+        };
 
-        ret
+        Ok(move |perm: &str| {
+            roles.as_ref().map_or(true, |r| {
+                r.contains(&Role::Admin) || perm.parse().map_or(false, |p: Role| r.contains(&p))
+            })
+        })
     }
 
-    fn format_command_usage(&self, usage: &CommandUsage, rich: bool) -> String {
-        let mut ret = Vec::new();
-
-        {
-            let mut ids = String::new();
-
-            lazy_static! {
-                static ref NON_WORD_RE: Regex = Regex::new(r"\s").unwrap();
-            }
+    // TODO: send the reply to a DM if the channel is not a command-only channel
+    async fn send_help(
+        &self,
+        src: &CommandSource<'_>,
+        http: impl AsRef<Http>,
+        help: &HelpTopic,
+        list_title: impl Display,
+        mode: ChannelMode,
+    ) -> Result<()>
+    {
+        // Only list the commands the author is allowed to run
+        let allowed = self.permission_check(src)?;
+        let rendered = DiscordRenderer {
+            handler: self,
+            mode,
+            strings: src.lang.strings(),
+            list_title,
+        }
+        .render_visible(help, &allowed);
 
-            let paren = usage.ids.len() != 1 || NON_WORD_RE.is_match(usage.ids.first().unwrap());
+        src.channel_id
+            .send_message(http, |msg| {
+                if let Some(content) = rendered.content {
+                    msg.content(content);
+                }
 
-            if paren {
-                ids.push('(');
-            }
+                if let Some((title, desc)) = rendered.embed {
+                    msg.embed(|e| {
+                        e.title(title).description(desc);
 
-            write!(ids, "{}", usage.ids.join("|")).unwrap();
+                        for (name, value) in rendered.fields {
+                            e.field(name, value, false);
+                        }
 
-            if paren {
-                ids.push(')');
-            }
+                        e
+                    });
+                }
 
-            ret.push(ids);
-        }
+                msg
+            })
+            .await
+            .context("failed to send help")?;
 
-        ret.extend(usage.args.iter().map(|a| Self::format_arg_usage(a)));
+        Ok(())
+    }
 
-        let desc = self.format_help_content(usage.desc);
+    fn format_help_path_error(err: docbot::HelpPathError, strings: &Strings) -> String {
+        use docbot::HelpPathError::{BadId, NoSubcommands, Subcommand};
 
-        if rich {
-            format!("**{}**\n{}", ret.join(" "), desc)
-        } else {
-            format!("{}\n{}", ret.join(" "), desc)
+        match err {
+            BadId(e) => Self::format_id_error(e, strings).0,
+            NoSubcommands(cmd, s) => MessageBuilder::new()
+                .push_mono_safer(cmd)
+                .push(strings.no_subcommands.0)
+                .push_mono_safer(s)
+                .push(strings.no_subcommands.1)
+                .build(),
+            Subcommand(id, err) => MessageBuilder::new()
+                .push(strings.subcommand_help.0)
+                .push_mono_safer(id)
+                .push(strings.subcommand_help.1)
+                .push(Self::format_help_path_error(*err, strings))
+                .build(),
         }
     }
 
-    // TODO: send the reply to a DM if the channel is not a command-only channel
-    async fn send_help(
+    async fn handle_help(
         &self,
-        channel_id: ChannelId,
-        http: impl AsRef<Http>,
-        help: &HelpTopic,
-        list_title: impl Display,
+        ctx: Context,
+        src: &CommandSource<'_>,
+        path: &[String],
+        mode: ChannelMode,
     ) -> Result<()>
     {
-        channel_id
-            .send_message(http, |msg| match help {
-                HelpTopic::Command(u, d) => msg
-                    .content(format!(
-                        "**Usage:** {}",
-                        self.format_command_usage(u, false)
-                    ))
-                    .embed(|e| {
-                        e.title("Description").description({
-                            enum Block {
-                                Par(&'static str),
-                                Head(&'static str),
-                                Arg(&'static ArgumentDesc),
-                            }
+        let custom = match src.guild_id {
+            Some(guild) => Some(
+                self.custom
+                    .get(guild, &self.pool)
+                    .context("failed to get custom commands")?,
+            ),
+            None => None,
+        };
 
-                            let mut m = MessageBuilder::new();
-
-                            for (i, block) in d
-                                .summary
-                                .iter()
-                                .map(|s| Block::Par(s))
-                                .chain(d.args.first().map(|_| Block::Head("**Arguments**")))
-                                .chain(d.args.iter().map(|a| Block::Arg(a)))
-                                .chain(d.examples.iter().map(|_| Block::Head("**Examples**")))
-                                .chain(d.examples.iter().map(|s| Block::Par(s)))
-                                .enumerate()
-                            {
-                                if i != 0 {
-                                    m.push('\n');
-                                }
+        let strings = src.lang.strings();
 
-                                match block {
-                                    Block::Par(s) => {
-                                        m.push_line(self.format_help_content(s));
-                                    },
-                                    Block::Head(s) => {
-                                        m.push(s);
-                                    },
-                                    Block::Arg(a) => {
-                                        m.push(" - ").push_bold_safe(a.name);
+        match BaseCommand::help_path_in(path, src.lang.code()) {
+            Ok(topic) => {
+                let title = if path.is_empty() {
+                    strings.commands
+                } else {
+                    strings.subcommands
+                };
 
-                                        if !a.is_required {
-                                            m.push(" (optional)");
-                                        }
+                self.send_help(src, &ctx, topic, title, mode).await?;
 
-                                        m.push(": ").push_line(self.format_help_content(a.desc));
-                                    },
-                                }
-                            }
+                // List this server's custom commands after the built-in ones
+                match custom {
+                    Some(custom) if path.is_empty() && !custom.is_empty() => {
+                        let topic = custom.help(None).context("failed to get custom help")?;
 
-                            m
-                        })
-                    }),
-                #[allow(clippy::option_if_let_else)] // Lifetime issues forbid this
-                HelpTopic::CommandSet(s, c) => if let Some(s) = s {
-                    msg.content(self.format_help_content(s))
-                } else {
-                    msg
+                        self.send_help(src, ctx, topic, strings.custom_commands, mode)
+                            .await
+                    },
+                    _ => Ok(()),
+                }
+            },
+            Err(e) => {
+                // Custom commands aren't part of the command tree, so check
+                // for one before reporting the error
+                if let (docbot::HelpPathError::BadId(docbot::IdParseError::NoMatch(..)), [name]) =
+                    (&e, path)
+                {
+                    if let Some(Ok(topic)) = custom.as_ref().map(|c| c.help(Some(name.as_str()))) {
+                        return self
+                            .send_help(src, ctx, topic, strings.custom_commands, mode)
+                            .await;
+                    }
                 }
-                .embed(|e| {
-                    e.title(list_title).description({
-                        let mut m = MessageBuilder::new();
-
-                        for (i, cmd) in c.iter().enumerate() {
-                            if i != 0 {
-                                m.push('\n');
-                            }
-
-                            m.push(" - ")
-                                .push_line(self.format_command_usage(cmd, true));
-                        }
 
-                        m
-                    })
-                }),
-                HelpTopic::Custom(s) => msg.content(self.format_help_content(s)),
-            })
-            .await
-            .context("failed to send help")?;
+                src.channel_id
+                    .say(
+                        ctx,
+                        format!(
+                            "{} {}",
+                            strings.error,
+                            Self::format_help_path_error(e, strings)
+                        ),
+                    )
+                    .await
+                    .context("failed to send help error")?;
 
-        Ok(())
+                Ok(())
+            },
+        }
     }
 
     async fn send_version(chan: ChannelId, http: impl AsRef<Http>) -> Result<()> {
@@ -351,52 +502,25 @@ impl Handler {
         Ok(())
     }
 
-    fn format_id_error(err: docbot::IdParseError) -> (String, bool) {
-        #[derive(Debug, Clone, Copy, PartialEq)]
-        struct DidYouMean<S: AsRef<str>>(f64, S);
-
-        use std::cmp::Ordering;
-
-        impl<S: Eq + AsRef<str>> Eq for DidYouMean<S> {}
-        impl<S: PartialOrd + AsRef<str>> PartialOrd for DidYouMean<S> {
-            fn partial_cmp(&self, rhs: &Self) -> Option<Ordering> {
-                self.0
-                    .partial_cmp(&rhs.0)
-                    .map(|o| o.then_with(|| rhs.1.partial_cmp(&self.1).unwrap_or(Ordering::Equal)))
-            }
-        }
-        impl<S: Ord + AsRef<str>> Ord for DidYouMean<S> {
-            fn cmp(&self, rhs: &Self) -> Ordering { self.partial_cmp(rhs).unwrap() }
-        }
-
+    fn format_id_error(err: docbot::IdParseError, strings: &Strings) -> (String, bool) {
         use docbot::IdParseError::{Ambiguous, NoMatch};
 
         let mut b = MessageBuilder::new();
         let mut has_help = false;
 
         match err {
-            NoMatch(s, v) => {
-                b.push("Not sure what you mean by ").push_mono_safer(&s);
+            NoMatch(ref s, _) => {
+                b.push(strings.not_sure).push_mono_safer(s);
 
-                for (i, val) in v
-                    .iter()
-                    .map(|v| {
-                        DidYouMean(
-                            normalized_damerau_levenshtein(&s, &v[0..v.len().min(s.len() + 1)]),
-                            v,
-                        )
-                    })
-                    .collect::<BinaryHeap<_>>()
-                    .into_iter_sorted()
-                    .take_while(|DidYouMean(s, _)| *s >= 0.3)
-                    .take(3)
-                    .map(|DidYouMean(_, v)| v)
+                for (i, val) in err
+                    .suggestions(MAX_SUGGESTIONS, SUGGESTION_THRESHOLD)
+                    .into_iter()
                     .enumerate()
                 {
                     has_help = true;
 
                     if i == 0 {
-                        b.push("\nDid you mean: ");
+                        b.push("\n").push(strings.did_you_mean);
                     } else {
                         b.push(", ");
                     }
@@ -405,9 +529,9 @@ impl Handler {
                 }
             },
             Ambiguous(v, i) => {
-                b.push("Not sure what you mean by ")
+                b.push(strings.not_sure)
                     .push_mono_safer(i)
-                    .push(", could be ");
+                    .push(strings.could_be);
 
                 for (i, v) in v.iter().enumerate() {
                     if i != 0 {
@@ -425,29 +549,47 @@ impl Handler {
     fn format_cmd_error_with_path(
         &self,
         err: docbot::CommandParseError,
-        path: &mut Option<Vec<&'static str>>,
+        path: &mut Option<Vec<Cow<'static, str>>>,
+        mode: ChannelMode,
+        strings: &Strings,
     ) -> (String, bool)
     {
         use docbot::CommandParseError::{
-            BadConvert, BadId, MissingRequired, NoInput, Subcommand, Trailing,
+            BadConvert, BadId, DuplicateOption, MissingOptionValue, MissingRequired, NoInput,
+            Subcommand, Tokenize, Trailing, UnknownOption,
         };
 
+        let has_suggestions = !err
+            .suggestions(MAX_SUGGESTIONS, SUGGESTION_THRESHOLD)
+            .is_empty();
         let mut b = MessageBuilder::new();
         let mut has_help = false;
         let mut help_cmd = None;
 
         match err {
-            NoInput => b.push("Expected a command, got nothing"),
-            BadId(e) => {
-                let (s, help) = Self::format_id_error(e);
+            Tokenize(docbot::TokenizeError::UnterminatedQuote(_)) => {
+                b.push(strings.unterminated_quote)
+            },
+            NoInput => b.push(strings.no_input),
+            // Suggestions for a command ID are listed with the full path to the
+            // command by format_cmd_error
+            BadId(docbot::IdParseError::NoMatch(s, _), _)
+            | BadId(docbot::IdParseError::Ambiguous(_, s), _)
+                if path.is_some() =>
+            {
+                has_help |= has_suggestions;
+                b.push(strings.not_sure).push_mono_safer(s)
+            },
+            BadId(e, _) => {
+                let (s, help) = Self::format_id_error(e, strings);
                 has_help |= help;
                 b.push(s)
             },
             MissingRequired(ArgumentName { cmd, arg }) => {
                 help_cmd = Some(cmd);
-                b.push("Missing required argument ").push_mono_safer(arg)
+                b.push(strings.missing_required).push_mono_safer(arg)
             },
-            BadConvert(ArgumentName { cmd, arg }, err) => {
+            BadConvert(ArgumentName { cmd, arg }, _, err) => {
                 enum Downcast {
                     Cmd(docbot::CommandParseError),
                     Id(docbot::IdParseError),
@@ -455,58 +597,74 @@ impl Handler {
                 }
 
                 help_cmd = Some(cmd);
-                b.push("Failed to process argument ")
+                b.push(strings.bad_convert.0)
                     .push_mono_safer(arg)
-                    .push(": ");
+                    .push(strings.bad_convert.1);
 
                 match err.downcast().map_or_else(
                     |e| e.downcast().map_or_else(Downcast::Other, Downcast::Id),
                     Downcast::Cmd,
                 ) {
                     Downcast::Cmd(e) => {
-                        let (s, help) = self.format_cmd_error_with_path(e, &mut None);
+                        let (s, help) =
+                            self.format_cmd_error_with_path(e, &mut None, mode, strings);
                         has_help |= help;
                         b.push(s)
                     },
                     Downcast::Id(e) => {
-                        let (s, help) = Self::format_id_error(e);
+                        let (s, help) = Self::format_id_error(e, strings);
                         has_help |= help;
                         b.push(s)
                     },
                     Downcast::Other(e) => b.push_safe(e),
                 }
             },
-            Trailing(cmd, s) => {
+            Trailing(cmd, s, _) => {
                 help_cmd = Some(cmd);
-                b.push("Too many arguments given (starting with ")
+                b.push(strings.trailing.0)
                     .push_mono_safer(s)
-                    .push(")")
+                    .push(strings.trailing.1)
+            },
+            UnknownOption(cmd, s, _) => {
+                help_cmd = Some(cmd);
+                b.push(strings.unknown_option).push_mono_safer(s)
+            },
+            DuplicateOption(ArgumentName { cmd, arg }, _) => {
+                help_cmd = Some(cmd);
+                b.push(strings.duplicate_option.0)
+                    .push_mono_safer(arg)
+                    .push(strings.duplicate_option.1)
+            },
+            MissingOptionValue(ArgumentName { cmd, arg }, _) => {
+                help_cmd = Some(cmd);
+                b.push(strings.missing_option_value).push_mono_safer(arg)
             },
             Subcommand(id, err) => {
                 if let Some(p) = path.as_mut() {
-                    p.push(id)
+                    p.push(id.into())
                 }
-                let (s, help) = self.format_cmd_error_with_path(*err, path);
+                let (s, help) = self.format_cmd_error_with_path(*err, path, mode, strings);
                 has_help |= help;
 
-                b.push("Subcommand ")
+                b.push(strings.subcommand_failed.0)
                     .push_mono_safer(id)
-                    .push(" failed: ")
+                    .push(strings.subcommand_failed.1)
                     .push(s)
             },
         };
 
         if !has_help {
             if let Some(path) = path {
-                path.push("help");
+                path.push("help".into());
 
                 if let Some(cmd) = help_cmd {
                     path.push(cmd);
                 }
 
-                b.push("\nRun ")
-                    .push_mono_safer(self.prefix_command(path.join(" ")))
-                    .push(" for more info");
+                b.push("\n")
+                    .push(strings.more_info.0)
+                    .push_mono_safer(self.prefix_command(path.join(" "), mode))
+                    .push(strings.more_info.1);
 
                 has_help = true;
             }
@@ -515,34 +673,228 @@ impl Handler {
         (b.build(), has_help)
     }
 
-    fn format_cmd_error(&self, err: docbot::CommandParseError) -> String {
-        self.format_cmd_error_with_path(err, &mut Some(Vec::new()))
-            .0
+    fn format_cmd_error(
+        &self,
+        err: docbot::CommandParseError,
+        input: Option<&str>,
+        mode: ChannelMode,
+        strings: &Strings,
+    ) -> String
+    {
+        let span = input.and_then(|input| {
+            err.span().or_else(|| match err {
+                docbot::CommandParseError::MissingRequired(..) => Some(input.len()..input.len()),
+                _ => None,
+            })
+        });
+
+        let suggestions = err.suggestions(MAX_SUGGESTIONS, SUGGESTION_THRESHOLD);
+
+        let mut b = MessageBuilder::new();
+        b.push(
+            self.format_cmd_error_with_path(err, &mut Some(Vec::new()), mode, strings)
+                .0,
+        );
+
+        for (i, cmd) in suggestions.into_iter().enumerate() {
+            if i == 0 {
+                b.push("\n").push(strings.did_you_mean);
+            } else {
+                b.push(", ");
+            }
+
+            b.push_mono_safer(self.prefix_command(cmd, mode));
+        }
+
+        if let (Some(input), Some(span)) = (input, span) {
+            b.push("\n")
+                .push_codeblock_safe(Self::format_error_caret(input, span), None);
+        }
+
+        b.build()
+    }
+
+    /// Render the line of input containing the given span, with a caret
+    /// underneath the span
+    fn format_error_caret(input: &str, span: Range<usize>) -> String {
+        let line_start = input[..span.start].rfind('\n').map_or(0, |i| i + 1);
+        let line_end = input[span.start..]
+            .find('\n')
+            .map_or(input.len(), |i| span.start + i);
+        let col = input[line_start..span.start].chars().count();
+        let len = input[span.start..span.end.min(line_end)].chars().count();
+
+        format!(
+            "{}\n{}{}",
+            &input[line_start..line_end],
+            " ".repeat(col),
+            "^".repeat(len.max(1))
+        )
+    }
+
+    /// Check that a channel given as a command argument belongs to the guild
+    /// the command was sent from, reporting an error if it does not
+    async fn check_guild_channel(
+        ctx: &Context,
+        chan: ChannelId,
+        target: ChannelId,
+        guild: GuildId,
+        strings: &Strings,
+    ) -> Result<bool>
+    {
+        match target
+            .to_channel(ctx)
+            .await
+            .context("failed to look up target channel")?
+        {
+            Channel::Guild(c) if c.guild_id == guild => Ok(true),
+            _ => {
+                chan.say(
+                    ctx,
+                    format!("{} {}", strings.error, strings.channel_not_in_guild),
+                )
+                .await
+                .context("failed to send channel error message")?;
+
+                Ok(false)
+            },
+        }
+    }
+
+    /// Mention a user on its own line, along with their alias if they have one
+    fn push_user<'a>(b: &'a mut MessageBuilder, user: &DisplayUser) -> &'a mut MessageBuilder {
+        b.user(user.user_id);
+
+        if user.alias != UNKNOWN_ALIAS {
+            b.push(" (").push_safe(&user.alias).push(")");
+        }
+
+        b.push_line("")
     }
 
     async fn handle_role_command(
         &self,
         ctx: Context,
-        msg: &Message,
+        src: &CommandSource<'_>,
         cmd: RoleCommand,
+        mode: ChannelMode,
     ) -> Result<()>
     {
         use roles::{
-            RoleCommandError::{GuildRequired, NoPermission, Other},
-            RoleCommandOk::{Added, Help, List, Removed, ShowAll, ShowOne},
+            RoleCommandError::{AliasTaken, GuildRequired, NoPermission, Other},
+            RoleCommandOk::{Added, AliasCleared, AliasSet, Help, List, Removed, ShowAll, ShowOne},
         };
 
-        let chan = msg.channel_id;
+        let chan = src.channel_id;
+        let strings = src.lang.strings();
+
+        match roles::execute(
+            cmd,
+            src.author.id,
+            src.guild_id,
+            &self.pool,
+            self.superuser,
+            src.lang,
+        ) {
+            Ok(Help(c)) => {
+                self.send_help(src, ctx, c, strings.subcommands, mode)
+                    .await?
+            },
+            Ok(List(r)) => self.send_help(src, ctx, r, strings.roles, mode).await?,
+            Ok(ShowAll(users)) => {
+                let mut lines: Vec<_> = users
+                    .iter()
+                    .flat_map(|(user, roles)| roles.iter().map(move |r| (*r, user)))
+                    .collect();
+                lines.sort_by(|(a_role, a_user), (b_role, b_user)| {
+                    a_role
+                        .cmp(b_role)
+                        .then_with(|| a_user.alias.cmp(&b_user.alias))
+                        .then_with(|| a_user.user_id.cmp(&b_user.user_id))
+                });
+
+                let pages: Vec<_> = lines.chunks(ROLES_PAGE_SIZE).collect();
+
+                if pages.is_empty() {
+                    chan.send_message(&ctx, |m| {
+                        m.embed(|e| {
+                            e.title(strings.assigned_roles)
+                                .description(strings.no_roles)
+                        })
+                    })
+                    .await
+                    .context("failed to send assigned roles")?;
+                }
+
+                for (i, page) in pages.iter().enumerate() {
+                    let mut fields: Vec<(Role, MessageBuilder)> = Vec::new();
 
-        match roles::execute(cmd, msg.author.id, msg.guild_id, &self.pool, self.superuser) {
-            Ok(Help(c)) => self.send_help(chan, ctx, c, "Subcommands").await?,
-            Ok(List(r)) => self.send_help(chan, ctx, r, "Roles").await?,
-            Ok(ShowAll(_map)) => todo!(),
-            Ok(ShowOne(_user, _roles)) => todo!(),
+                    for (role, user) in *page {
+                        if fields.last().map_or(true, |(r, _)| r != role) {
+                            fields.push((*role, MessageBuilder::new()));
+                        }
+
+                        Self::push_user(&mut fields.last_mut().unwrap().1, user);
+                    }
+
+                    chan.send_message(&ctx, |m| {
+                        m.embed(|e| {
+                            e.title(strings.assigned_roles);
+
+                            for (role, b) in fields {
+                                e.field(role, b, false);
+                            }
+
+                            if pages.len() > 1 {
+                                e.footer(|f| {
+                                    f.text(format!(
+                                        "{}{}{}{}",
+                                        strings.page.0,
+                                        i + 1,
+                                        strings.page.1,
+                                        pages.len()
+                                    ))
+                                });
+                            }
+
+                            e
+                        })
+                    })
+                    .await
+                    .context("failed to send assigned roles")?;
+                }
+            },
+            Ok(ShowOne(user, roles)) => {
+                chan.send_message(&ctx, |m| {
+                    m.embed(|e| {
+                        e.title(strings.assigned_roles).description({
+                            let mut b = MessageBuilder::new();
+
+                            Self::push_user(&mut b, &user);
+
+                            if roles.is_empty() {
+                                b.push_italic_line(strings.no_roles);
+                            }
+
+                            for role in &roles {
+                                b.push(" - ").push_bold_line_safe(role);
+                            }
+
+                            b
+                        })
+                    })
+                })
+                .await
+                .context("failed to send assigned roles")?;
+            },
             Ok(Added(n)) => {
                 chan.say(
                     &ctx,
-                    format!("Added {} role{}.", n, if n == 1 { "" } else { "s" }),
+                    if n == 1 {
+                        strings.role_added.into()
+                    } else {
+                        format!("{}{}{}", strings.roles_added.0, n, strings.roles_added.1)
+                    },
                 )
                 .await
                 .context("failed to send success message")?;
@@ -550,13 +902,31 @@ impl Handler {
             Ok(Removed(n)) => {
                 chan.say(
                     &ctx,
-                    format!("Removed {} role{}.", n, if n == 1 { "" } else { "s" }),
+                    if n == 1 {
+                        strings.role_removed.into()
+                    } else {
+                        format!(
+                            "{}{}{}",
+                            strings.roles_removed.0, n, strings.roles_removed.1
+                        )
+                    },
                 )
                 .await
                 .context("failed to send success message")?;
             },
-            Err(GuildRequired) => Self::send_guild_required(chan, &ctx).await?,
-            Err(NoPermission(n)) => Self::send_no_permission(chan, &ctx, n).await?,
+            Ok(AliasSet) => {
+                chan.say(&ctx, strings.alias_set)
+                    .await
+                    .context("failed to send success message")?;
+            },
+            Ok(AliasCleared) => {
+                chan.say(&ctx, strings.alias_cleared)
+                    .await
+                    .context("failed to send success message")?;
+            },
+            Err(GuildRequired) => Self::send_guild_required(chan, &ctx, strings).await?,
+            Err(NoPermission(n)) => Self::send_no_permission(chan, &ctx, n, strings).await?,
+            Err(AliasTaken(name)) => Self::send_alias_taken(chan, &ctx, name, strings).await?,
             Err(Other(e)) => Err(e).context("an unexpected error occurred")?,
         }
 
@@ -566,76 +936,1208 @@ impl Handler {
     async fn handle_channel_command(
         &self,
         ctx: Context,
-        msg: &Message,
+        src: &CommandSource<'_>,
         cmd: ChannelCommand,
+        mode: ChannelMode,
     ) -> Result<()>
     {
         use channels::{
-            ChannelCommandError::{GuildRequired, NoPermission, Other},
-            ChannelCommandOk::{Help, List, Marked, ShowAll, ShowOne, Unmarked},
+            ChannelCommandError::{AliasTaken, GuildRequired, NoPermission, Other},
+            ChannelCommandOk::{
+                AliasCleared, AliasSet, DefaultSet, Help, List, Marked, ShowAll, ShowOne, Unmarked,
+            },
         };
 
-        let chan = msg.channel_id;
+        let chan = src.channel_id;
+        let strings = src.lang.strings();
 
-        match channels::execute(cmd, msg.author.id, msg.guild_id, &self.pool, self.superuser) {
-            Ok(Help(c)) => self.send_help(chan, ctx, c, "Subcommands").await?,
-            Ok(List(m)) => self.send_help(chan, ctx, m, "Channel modes").await?,
-            Ok(ShowAll { .. }) | Ok(ShowOne { .. }) | Ok(Marked) | Ok(Unmarked) => todo!(),
-            Err(GuildRequired) => Self::send_guild_required(chan, &ctx).await?,
-            Err(NoPermission(n)) => Self::send_no_permission(chan, &ctx, n).await?,
-            Err(Other(e)) => Err(e).context("an unexpected error occurred")?,
+        let target = match cmd {
+            ChannelCommand::Show(Some(t))
+            | ChannelCommand::Mark(t, _)
+            | ChannelCommand::Unmark(t)
+            | ChannelCommand::Alias(t, _) => Some(t),
+            _ => None,
+        };
+
+        if let (Some(target), Some(guild)) = (target, src.guild_id) {
+            if !Self::check_guild_channel(&ctx, chan, target, guild, strings).await? {
+                return Ok(());
+            }
         }
 
-        Ok(())
-    }
+        match channels::execute(
+            cmd,
+            src.author.id,
+            src.guild_id,
+            &self.pool,
+            self.superuser,
+            src.lang,
+        ) {
+            Ok(Help(c)) => {
+                self.send_help(src, ctx, c, strings.subcommands, mode)
+                    .await?
+            },
+            Ok(List(m)) => {
+                self.send_help(src, ctx, m, strings.channel_modes, mode)
+                    .await?
+            },
+            Ok(ShowAll { default, modes }) => {
+                let mut modes: Vec<_> = modes.into_iter().collect();
+                modes.sort_by_key(|(c, _)| c.channel_id);
 
-    async fn handle_command<S: AsRef<str>>(&self, s: S, ctx: Context, msg: &Message) -> Result<()> {
-        use BaseCommand::{Channel, Help, Modmail, Role, Schedule, Version};
+                chan.send_message(&ctx, |m| {
+                    m.embed(|e| {
+                        e.title(strings.channel_modes).description({
+                            let mut b = MessageBuilder::new();
 
-        let chan = msg.channel_id;
-        let http = Arc::clone(&ctx.http);
+                            b.push(strings.default_mode).push_bold_line_safe(default);
 
-        let _d = defer(|| {
-            if thread::panicking() {
-                tokio::task::spawn_blocking(move || {
-                    runtime::Builder::new()
-                        .enable_all()
-                        .basic_scheduler()
-                        .build()
-                        .unwrap()
-                        .block_on(chan.say(
-                            http,
-                            "**ERROR:** thread panicked while servicing your request",
-                        ))
-                        .ok()
-                });
-            }
-        });
+                            if modes.is_empty() {
+                                b.push_italic_line(strings.no_channels);
+                            }
+
+                            for (channel, mode) in &modes {
+                                b.push(" - ")
+                                    .channel(channel.channel_id)
+                                    .push(": ")
+                                    .push_bold_line_safe(mode);
+                            }
+
+                            b
+                        })
+                    })
+                })
+                .await
+                .context("failed to send channel modes")?;
+            },
+            Ok(ShowOne {
+                is_default,
+                channel,
+                mode,
+            }) => {
+                chan.send_message(&ctx, |m| {
+                    m.embed(|e| {
+                        e.title(strings.channel_mode).description({
+                            let mut b = MessageBuilder::new();
+
+                            b.channel(channel.channel_id)
+                                .push(": ")
+                                .push_bold_safe(mode);
+
+                            if is_default {
+                                b.push(strings.is_default);
+                            }
+
+                            b
+                        })
+                    })
+                })
+                .await
+                .context("failed to send channel mode")?;
+            },
+            Ok(DefaultSet) => {
+                chan.say(&ctx, strings.default_mode_set)
+                    .await
+                    .context("failed to send success message")?;
+            },
+            Ok(Marked) => {
+                chan.say(&ctx, strings.channel_marked)
+                    .await
+                    .context("failed to send success message")?;
+            },
+            Ok(Unmarked) => {
+                chan.say(&ctx, strings.channel_unmarked)
+                    .await
+                    .context("failed to send success message")?;
+            },
+            Ok(AliasSet) => {
+                chan.say(&ctx, strings.alias_set)
+                    .await
+                    .context("failed to send success message")?;
+            },
+            Ok(AliasCleared) => {
+                chan.say(&ctx, strings.alias_cleared)
+                    .await
+                    .context("failed to send success message")?;
+            },
+            Err(GuildRequired) => Self::send_guild_required(chan, &ctx, strings).await?,
+            Err(NoPermission(n)) => Self::send_no_permission(chan, &ctx, n, strings).await?,
+            Err(AliasTaken(name)) => Self::send_alias_taken(chan, &ctx, name, strings).await?,
+            Err(Other(e)) => Err(e).context("an unexpected error occurred")?,
+        }
+
+        Ok(())
+    }
+
+    async fn send_ticket_notice(
+        ctx: &Context,
+        ticket: &TicketId,
+        guild: GuildId,
+        to: Recipient,
+        title: impl Display,
+        body: impl Display,
+        strings: &Strings,
+    ) -> Result<()>
+    {
+        let guild_name = guild.name(ctx).await.unwrap_or_else(|| "???".into());
+
+        let (chan, footer) = match to {
+            Recipient::Author(user) => (
+                user.create_dm_channel(ctx)
+                    .await
+                    .context("failed to open DM with ticket author")?
+                    .id,
+                format!(
+                    "{}{}{}{}",
+                    strings.from_moderators.0, ticket, strings.from_moderators.1, guild_name
+                ),
+            ),
+            Recipient::Inbox(chan) => (
+                chan,
+                format!(
+                    "{}{}{}",
+                    strings.from_author.0, ticket, strings.from_author.1
+                ),
+            ),
+        };
+
+        chan.send_message(ctx, |m| {
+            m.embed(|e| e.title(title).description(body).footer(|f| f.text(footer)))
+        })
+        .await
+        .context("failed to send ticket notice")?;
+
+        Ok(())
+    }
+
+    async fn handle_modmail(
+        &self,
+        ctx: Context,
+        src: &CommandSource<'_>,
+        message: Vec<String>,
+    ) -> Result<()>
+    {
+        use modmail::TicketCommandError::NoInbox;
+
+        let chan = src.channel_id;
+        let strings = src.lang.strings();
+
+        let guild = if let Some(guild) = src.guild_id {
+            // Don't leave the sender's name sitting next to their message
+            if let Some(msg) = src.message {
+                msg.delete(&ctx)
+                    .await
+                    .map_err(|e| warn!("failed to delete modmail message: {:?}", e))
+                    .ok();
+            }
+
+            guild
+        } else {
+            let mut guilds = Vec::new();
+
+            for guild in
+                modmail::get_inbox_guilds(&self.pool).context("failed to get modmail guilds")?
+            {
+                if ctx.cache.member(guild, src.author.id).await.is_some() {
+                    guilds.push(guild);
+                }
+            }
+
+            match guilds.as_slice() {
+                [guild] => *guild,
+                [] => {
+                    chan.say(
+                        &ctx,
+                        format!("{} {}", strings.error, strings.no_modmail_guilds),
+                    )
+                    .await
+                    .context("failed to send modmail error message")?;
+
+                    return Ok(());
+                },
+                _ => {
+                    chan.say(
+                        &ctx,
+                        format!("{} {}", strings.error, strings.multiple_modmail_guilds),
+                    )
+                    .await
+                    .context("failed to send modmail error message")?;
+
+                    return Ok(());
+                },
+            }
+        };
+
+        // The ticket belongs to the server, even if it was opened from a DM
+        let strings = self.guild_language(Some(guild)).strings();
+
+        let (ticket, inbox) = match modmail::open_ticket(src.author.id, guild, &self.pool) {
+            Ok(t) => t,
+            Err(NoInbox) => {
+                src.author
+                    .direct_message(&ctx, |m| {
+                        m.content(format!("{} {}", strings.error, strings.no_modmail))
+                    })
+                    .await
+                    .context("failed to send modmail error message")?;
 
-        let cmd = match commands::parse_base(s) {
-            Ok(c) => c,
-            Err(e) => {
-                chan.say(ctx, format!("**ERROR:** {}", self.format_cmd_error(e)))
-                    .await
-                    .context("failed to send command parse error")?;
                 return Ok(());
             },
+            Err(e) => Err(e).context("failed to open ticket")?,
         };
 
-        match cmd {
-            Help(c) => {
-                self.send_help(chan, ctx, BaseCommand::help(c), "Commands")
+        let (inbox_mode, _) = channels::get_channel_mode(inbox, guild, &self.pool)
+            .context("failed to get inbox channel mode")?;
+
+        Self::send_ticket_notice(
+            &ctx,
+            &ticket,
+            guild,
+            Recipient::Inbox(inbox),
+            format!("{}{}", strings.new_ticket, ticket),
+            MessageBuilder::new()
+                .push_line_safe(message.join(" "))
+                .push_line("")
+                .push(strings.reply_with)
+                .push_mono_safer(
+                    self.prefix_command(
+                        format!("tickets reply {} <message...>", ticket),
+                        inbox_mode,
+                    ),
+                ),
+            strings,
+        )
+        .await
+        .context("failed to deliver modmail")?;
+
+        src.author
+            .direct_message(&ctx, |m| {
+                m.content(
+                    MessageBuilder::new()
+                        .push(strings.ticket_sent.0)
+                        .push_mono_safer(&ticket)
+                        .push(strings.ticket_sent.1),
+                )
+            })
+            .await
+            .context("failed to send modmail confirmation")?;
+
+        Ok(())
+    }
+
+    async fn handle_ticket_command(
+        &self,
+        ctx: Context,
+        src: &CommandSource<'_>,
+        cmd: TicketCommand,
+        mode: ChannelMode,
+    ) -> Result<()>
+    {
+        use modmail::{
+            TicketCommandError::{
+                GuildRequired, NoInbox, NoPermission, NoTicket, Other, TicketClosed,
+            },
+            TicketCommandOk::{Closed, Help, Inbox, InboxSet, List, Opened, Relay},
+        };
+
+        let chan = src.channel_id;
+        let strings = src.lang.strings();
+
+        match modmail::execute(
+            cmd,
+            src.author.id,
+            src.guild_id,
+            &self.pool,
+            self.superuser,
+            src.lang,
+        ) {
+            Ok(Help(c)) => {
+                self.send_help(src, ctx, c, strings.subcommands, mode)
+                    .await?
+            },
+            Ok(Inbox(inbox)) => {
+                chan.say(
+                    &ctx,
+                    inbox.map_or_else(
+                        || strings.no_inbox.into(),
+                        |i| {
+                            MessageBuilder::new()
+                                .push(strings.inbox_is.0)
+                                .channel(i)
+                                .push(strings.inbox_is.1)
+                                .build()
+                        },
+                    ),
+                )
+                .await
+                .context("failed to send modmail inbox")?;
+            },
+            Ok(InboxSet) => {
+                chan.say(&ctx, strings.inbox_set)
+                    .await
+                    .context("failed to send success message")?;
+            },
+            Ok(List(tickets)) => {
+                chan.send_message(&ctx, |m| {
+                    m.embed(|e| {
+                        e.title(strings.open_tickets).description({
+                            let mut b = MessageBuilder::new();
+
+                            if tickets.is_empty() {
+                                b.push_italic_line(strings.no_tickets);
+                            }
+
+                            for ticket in &tickets {
+                                b.push(" - ").push_mono_line_safer(ticket);
+                            }
+
+                            b
+                        })
+                    })
+                })
+                .await
+                .context("failed to send ticket list")?;
+            },
+            Ok(Relay {
+                ticket,
+                guild,
+                to,
+                message,
+            }) => {
+                let notice_strings = self.guild_language(Some(guild)).strings();
+
+                Self::send_ticket_notice(
+                    &ctx,
+                    &ticket,
+                    guild,
+                    to,
+                    format!("{}{}", notice_strings.reply_to_ticket, ticket),
+                    MessageBuilder::new().push_safe(message),
+                    notice_strings,
+                )
+                .await?;
+
+                chan.say(&ctx, strings.reply_sent)
+                    .await
+                    .context("failed to send success message")?;
+            },
+            Ok(Opened {
+                ticket,
+                guild,
+                notify,
+            }) => {
+                let notice_strings = self.guild_language(Some(guild)).strings();
+
+                Self::send_ticket_notice(
+                    &ctx,
+                    &ticket,
+                    guild,
+                    notify,
+                    format!(
+                        "{}{}{}",
+                        notice_strings.reopened_title.0, ticket, notice_strings.reopened_title.1
+                    ),
+                    notice_strings.reopened_notice,
+                    notice_strings,
+                )
+                .await?;
+
+                chan.say(&ctx, strings.reopened)
+                    .await
+                    .context("failed to send success message")?;
+            },
+            Ok(Closed {
+                ticket,
+                guild,
+                notify,
+            }) => {
+                let notice_strings = self.guild_language(Some(guild)).strings();
+
+                Self::send_ticket_notice(
+                    &ctx,
+                    &ticket,
+                    guild,
+                    notify,
+                    format!(
+                        "{}{}{}",
+                        notice_strings.closed_title.0, ticket, notice_strings.closed_title.1
+                    ),
+                    notice_strings.closed_notice,
+                    notice_strings,
+                )
+                .await?;
+
+                chan.say(&ctx, strings.closed)
+                    .await
+                    .context("failed to send success message")?;
+            },
+            Err(GuildRequired) => Self::send_guild_required(chan, &ctx, strings).await?,
+            Err(NoPermission(n)) => Self::send_no_permission(chan, &ctx, n, strings).await?,
+            Err(NoInbox) => {
+                chan.say(&ctx, format!("{} {}", strings.error, strings.no_inbox))
+                    .await
+                    .context("failed to send modmail error message")?;
+            },
+            Err(NoTicket(t)) => {
+                chan.say(
+                    &ctx,
+                    MessageBuilder::new()
+                        .push(strings.error)
+                        .push(" ")
+                        .push(strings.no_ticket)
+                        .push_mono_safer(t),
+                )
+                .await
+                .context("failed to send modmail error message")?;
+            },
+            Err(TicketClosed(t)) => {
+                chan.say(
+                    &ctx,
+                    MessageBuilder::new()
+                        .push(strings.error)
+                        .push(" ")
+                        .push(strings.ticket_closed.0)
+                        .push_mono_safer(t)
+                        .push(strings.ticket_closed.1),
+                )
+                .await
+                .context("failed to send modmail error message")?;
+            },
+            Err(Other(e)) => Err(e).context("an unexpected error occurred")?,
+        }
+
+        Ok(())
+    }
+
+    async fn handle_schedule_command(
+        &self,
+        ctx: Context,
+        src: &CommandSource<'_>,
+        cmd: ScheduleCommand,
+        mode: ChannelMode,
+    ) -> Result<()>
+    {
+        use schedule::{
+            ScheduleCommandError::{
+                GuildRequired, InPast, NoAnnouncement, NoPermission, NotAnnouncements, Other,
+            },
+            ScheduleCommandOk::{Added, Help, List, Paused, Removed, Resumed, Show},
+        };
+
+        let chan = src.channel_id;
+        let strings = src.lang.strings();
+
+        let target = match cmd {
+            ScheduleCommand::Add(_, t, ..) => Some(t),
+            _ => None,
+        };
+
+        if let (Some(target), Some(guild)) = (target, src.guild_id) {
+            if !Self::check_guild_channel(&ctx, chan, target, guild, strings).await? {
+                return Ok(());
+            }
+        }
+
+        match schedule::execute(
+            cmd,
+            src.author.id,
+            src.guild_id,
+            &self.pool,
+            self.superuser,
+            src.lang,
+        ) {
+            Ok(Help(c)) => {
+                self.send_help(src, ctx, c, strings.subcommands, mode)
+                    .await?
+            },
+            Ok(List(announcements)) => {
+                chan.send_message(&ctx, |m| {
+                    m.embed(|e| {
+                        e.title(strings.scheduled_announcements).description({
+                            let mut b = MessageBuilder::new();
+
+                            if announcements.is_empty() {
+                                b.push_italic_line(strings.no_announcements);
+                            }
+
+                            for ann in &announcements {
+                                #[allow(clippy::cast_sign_loss)]
+                                b.push(" - ")
+                                    .push_mono_safer(&ann.announcement_id)
+                                    .push(strings.announcement_in)
+                                    .channel(ChannelId(ann.channel_id as u64))
+                                    .push(", ")
+                                    .push(schedule::format_time(&ann.next_run));
+
+                                if let Some(secs) = ann.repeat_secs {
+                                    b.push(strings.every).push(schedule::format_duration(secs));
+                                }
+
+                                if ann.is_paused {
+                                    b.push(strings.paused);
+                                }
+
+                                b.push_line("");
+                            }
+
+                            b
+                        })
+                    })
+                })
+                .await
+                .context("failed to send announcement list")?;
+            },
+            Ok(Show(ann)) => {
+                chan.send_message(&ctx, |m| {
+                    m.embed(|e| {
+                        #[allow(clippy::cast_sign_loss)]
+                        let channel = ChannelId(ann.channel_id as u64);
+
+                        e.title(format!("{}{}", strings.announcement, ann.announcement_id))
+                            .description(MessageBuilder::new().push_safe(&ann.message))
+                            .field(
+                                strings.channel,
+                                MessageBuilder::new().channel(channel),
+                                true,
+                            )
+                            .field(
+                                if ann.is_paused {
+                                    strings.next_run_paused
+                                } else {
+                                    strings.next_run
+                                },
+                                schedule::format_time(&ann.next_run),
+                                true,
+                            )
+                            .field(
+                                strings.repeats,
+                                ann.repeat_secs.map_or_else(
+                                    || strings.never.into(),
+                                    |s| {
+                                        format!(
+                                            "{}{}",
+                                            strings.repeats_every,
+                                            schedule::format_duration(s)
+                                        )
+                                    },
+                                ),
+                                true,
+                            )
+                    })
+                })
+                .await
+                .context("failed to send announcement")?;
+            },
+            Ok(Added(id, time)) => {
+                chan.say(
+                    &ctx,
+                    MessageBuilder::new()
+                        .push(strings.announcement_added.0)
+                        .push_mono_safer(id)
+                        .push(strings.announcement_added.1)
+                        .push(schedule::format_time(&time))
+                        .push("."),
+                )
+                .await
+                .context("failed to send success message")?;
+            },
+            Ok(Removed) => {
+                chan.say(&ctx, strings.announcement_removed)
+                    .await
+                    .context("failed to send success message")?;
+            },
+            Ok(Paused) => {
+                chan.say(&ctx, strings.announcement_paused)
+                    .await
+                    .context("failed to send success message")?;
+            },
+            Ok(Resumed) => {
+                chan.say(&ctx, strings.announcement_resumed)
+                    .await
+                    .context("failed to send success message")?;
+            },
+            Err(GuildRequired) => Self::send_guild_required(chan, &ctx, strings).await?,
+            Err(NoPermission(n)) => Self::send_no_permission(chan, &ctx, n, strings).await?,
+            Err(NoAnnouncement(a)) => {
+                chan.say(
+                    &ctx,
+                    MessageBuilder::new()
+                        .push(strings.error)
+                        .push(" ")
+                        .push(strings.no_announcement)
+                        .push_mono_safer(a),
+                )
+                .await
+                .context("failed to send schedule error message")?;
+            },
+            Err(NotAnnouncements(c)) => {
+                chan.say(
+                    &ctx,
+                    MessageBuilder::new()
+                        .push(strings.error)
+                        .push(" ")
+                        .channel(c)
+                        .push(strings.not_announcements.0)
+                        .push_mono_safer(self.prefix_command("channels mark", mode))
+                        .push(strings.not_announcements.1),
+                )
+                .await
+                .context("failed to send schedule error message")?;
+            },
+            Err(InPast(t)) => {
+                chan.say(
+                    &ctx,
+                    format!(
+                        "{} {}{}",
+                        strings.error,
+                        schedule::format_time(&t),
+                        strings.in_past
+                    ),
+                )
+                .await
+                .context("failed to send schedule error message")?;
+            },
+            Err(Other(e)) => Err(e).context("an unexpected error occurred")?,
+        }
+
+        Ok(())
+    }
+
+    async fn handle_custom_command(
+        &self,
+        ctx: Context,
+        src: &CommandSource<'_>,
+        cmd: CustomCommand,
+        mode: ChannelMode,
+    ) -> Result<()>
+    {
+        use custom::{
+            CustomCommandError::{Exists, GuildRequired, NoCommand, NoPermission, Other, Reserved},
+            CustomCommandOk::{Added, Help, List, Removed},
+        };
+
+        let chan = src.channel_id;
+        let strings = src.lang.strings();
+
+        match custom::execute(
+            cmd,
+            src.author.id,
+            src.guild_id,
+            &self.pool,
+            &self.custom,
+            self.superuser,
+            src.lang,
+        ) {
+            Ok(Help(c)) => {
+                self.send_help(src, ctx, c, strings.subcommands, mode)
+                    .await?
+            },
+            Ok(List(commands)) => {
+                chan.send_message(&ctx, |m| {
+                    m.embed(|e| {
+                        e.title(strings.custom_commands).description({
+                            let mut b = MessageBuilder::new();
+
+                            if commands.is_empty() {
+                                b.push(strings.no_custom_commands);
+                            }
+
+                            for cmd in &commands {
+                                b.push(" - ")
+                                    .push_mono_safer(self.prefix_command(&cmd.name, mode))
+                                    .push(": ")
+                                    .push_line_safe(custom::preview(&cmd.response));
+                            }
+
+                            b
+                        })
+                    })
+                })
+                .await
+                .context("failed to send custom command list")?;
+            },
+            Ok(Added(name)) => {
+                chan.say(
+                    &ctx,
+                    MessageBuilder::new()
+                        .push(strings.custom_added.0)
+                        .push_mono_safer(self.prefix_command(name, mode))
+                        .push(strings.custom_added.1),
+                )
+                .await
+                .context("failed to send success message")?;
+            },
+            Ok(Removed) => {
+                chan.say(&ctx, strings.custom_removed)
+                    .await
+                    .context("failed to send success message")?;
+            },
+            Err(GuildRequired) => Self::send_guild_required(chan, &ctx, strings).await?,
+            Err(NoPermission(n)) => Self::send_no_permission(chan, &ctx, n, strings).await?,
+            Err(Reserved(name)) => {
+                chan.say(
+                    &ctx,
+                    MessageBuilder::new()
+                        .push(strings.error)
+                        .push(" ")
+                        .push_mono_safer(name)
+                        .push(strings.reserved),
+                )
+                .await
+                .context("failed to send custom command error message")?;
+            },
+            Err(Exists(name)) => {
+                chan.say(
+                    &ctx,
+                    MessageBuilder::new()
+                        .push(strings.error)
+                        .push(" ")
+                        .push(strings.custom_exists.0)
+                        .push_mono_safer(&name)
+                        .push(strings.custom_exists.1)
+                        .push_mono_safer(self.prefix_command(format!("custom rm {}", name), mode))
+                        .push(strings.custom_exists_end),
+                )
+                .await
+                .context("failed to send custom command error message")?;
+            },
+            Err(NoCommand(name)) => {
+                chan.say(
+                    &ctx,
+                    MessageBuilder::new()
+                        .push(strings.error)
+                        .push(" ")
+                        .push(strings.no_custom)
+                        .push_mono_safer(name),
+                )
+                .await
+                .context("failed to send custom command error message")?;
+            },
+            Err(Other(e)) => Err(e).context("an unexpected error occurred")?,
+        }
+
+        Ok(())
+    }
+
+    async fn handle_language_command(
+        &self,
+        ctx: Context,
+        src: &CommandSource<'_>,
+        cmd: LanguageCommand,
+        mode: ChannelMode,
+    ) -> Result<()>
+    {
+        use language::{
+            LanguageCommandError::{GuildRequired, NoPermission, Other},
+            LanguageCommandOk::{Help, List, Reset, Set, Show},
+        };
+
+        let chan = src.channel_id;
+        let strings = src.lang.strings();
+
+        match language::execute(
+            cmd,
+            src.author.id,
+            src.guild_id,
+            &self.pool,
+            self.superuser,
+            src.lang,
+        ) {
+            Ok(Help(c)) => {
+                self.send_help(src, ctx, c, strings.subcommands, mode)
                     .await?
             },
+            Ok(List(l)) => self.send_help(src, ctx, l, strings.languages, mode).await?,
+            Ok(Show(lang)) => {
+                chan.say(
+                    &ctx,
+                    MessageBuilder::new()
+                        .push(strings.language_is)
+                        .push_bold_safe(lang.name())
+                        .push("."),
+                )
+                .await
+                .context("failed to send guild language")?;
+            },
+            Ok(Set(lang)) => {
+                // Confirm in the new language, since that's what the server
+                // will see from now on
+                let strings = lang.strings();
+
+                chan.say(
+                    &ctx,
+                    MessageBuilder::new()
+                        .push(strings.language_set)
+                        .push_bold_safe(lang.name())
+                        .push("."),
+                )
+                .await
+                .context("failed to send success message")?;
+            },
+            Ok(Reset) => {
+                chan.say(&ctx, Language::default().strings().language_reset)
+                    .await
+                    .context("failed to send success message")?;
+            },
+            Err(GuildRequired) => Self::send_guild_required(chan, &ctx, strings).await?,
+            Err(NoPermission(n)) => Self::send_no_permission(chan, &ctx, n, strings).await?,
+            Err(Other(e)) => Err(e).context("an unexpected error occurred")?,
+        }
+
+        Ok(())
+    }
+
+    async fn run_scheduler(http: Arc<Http>, pool: DbPool) {
+        let mut interval = time::interval(SCHEDULE_POLL_INTERVAL);
+
+        loop {
+            interval.tick().await;
+
+            Self::deliver_announcements(&http, &pool)
+                .await
+                .map_err(|e| error!("error while delivering announcements: {:?}", e))
+                .ok();
+        }
+    }
+
+    async fn deliver_announcements(http: &Arc<Http>, pool: &DbPool) -> Result<()> {
+        let now = Utc::now();
+
+        // Keep going if one announcement fails, so it can't hold up the rest
+        for ann in schedule::get_due(now, pool).context("failed to get due announcements")? {
+            Self::deliver_announcement(http, pool, &ann, now)
+                .await
+                .map_err(|e| {
+                    error!(
+                        "error while delivering announcement {}: {:?}",
+                        ann.announcement_id, e
+                    )
+                })
+                .ok();
+        }
+
+        Ok(())
+    }
+
+    async fn deliver_announcement(
+        http: &Arc<Http>,
+        pool: &DbPool,
+        ann: &Announcement,
+        now: DateTime<Utc>,
+    ) -> Result<()>
+    {
+        #[allow(clippy::cast_sign_loss)]
+        let (guild, channel) = (
+            GuildId(ann.guild_id as u64),
+            ChannelId(ann.channel_id as u64),
+        );
+
+        let delivered = if schedule::is_missed(ann, now) {
+            warn!(
+                "Announcement {} missed its run at {}",
+                ann.announcement_id, ann.next_run
+            );
+
+            false
+        } else {
+            match channels::get_channel_mode(channel, guild, pool)
+                .context("failed to get announcement channel mode")?
+            {
+                (ChannelMode::Announcements, _) => channel
+                    .say(http, &ann.message)
+                    .await
+                    .map_err(|e| {
+                        error!(
+                            "error while sending announcement {}: {:?}",
+                            ann.announcement_id, e
+                        )
+                    })
+                    .is_ok(),
+                _ => {
+                    warn!(
+                        "Not sending announcement {} to non-announcements channel {}",
+                        ann.announcement_id, channel
+                    );
+
+                    false
+                },
+            }
+        };
+
+        schedule::reschedule(ann, delivered, now, pool).with_context(|| {
+            if delivered {
+                "failed to reschedule announcement after sending it, so it may be sent again"
+            } else {
+                "failed to reschedule announcement"
+            }
+        })
+    }
+
+    /// Snapshot the state needed to resolve names in command arguments
+    async fn arg_context(&self, ctx: &Context, guild: Option<GuildId>) -> ArgContext {
+        let guild = match guild {
+            Some(g) => ctx.cache.guild(g).await,
+            None => None,
+        };
+
+        ArgContext {
+            guild,
+            db: self.pool.clone(),
+        }
+    }
+
+    /// Get the language to reply in, falling back to English for DMs or if
+    /// the guild's setting can't be read
+    fn guild_language(&self, guild: Option<GuildId>) -> Language {
+        guild.map_or_else(Language::default, |guild| {
+            language::get_language(guild, &self.pool)
+                .map_err(|e| error!("error while getting guild language: {:?}", e))
+                .unwrap_or_default()
+        })
+    }
+
+    async fn handle_command<S: AsRef<str>>(
+        &self,
+        s: S,
+        ctx: Context,
+        src: &CommandSource<'_>,
+        mode: ChannelMode,
+    ) -> Result<()>
+    {
+        let arg_ctx = self.arg_context(&ctx, src.guild_id).await;
+
+        let cmd = match commands::parse_base(s.as_ref(), &arg_ctx) {
+            Ok(c) => c,
+            Err(e) => {
+                // Custom commands are only tried if no built-in command matched,
+                // so they can never shadow one
+                if let (
+                    docbot::CommandParseError::BadId(docbot::IdParseError::NoMatch(..), _),
+                    Some(guild),
+                ) = (&e, src.guild_id)
+                {
+                    if self
+                        .handle_custom_response(s.as_ref(), &ctx, src, guild, mode)
+                        .await?
+                    {
+                        return Ok(());
+                    }
+                }
+
+                src.channel_id
+                    .say(
+                        ctx,
+                        format!(
+                            "{} {}",
+                            src.lang.strings().error,
+                            self.format_cmd_error(e, Some(s.as_ref()), mode, src.lang.strings())
+                        ),
+                    )
+                    .await
+                    .context("failed to send command parse error")?;
+                return Ok(());
+            },
+        };
+
+        if let Some(canonical) = commands::expand_abbreviations(s.as_ref(), &cmd) {
+            src.channel_id
+                .say(
+                    &ctx,
+                    MessageBuilder::new()
+                        .push(src.lang.strings().interpreted_as)
+                        .push_mono_safer(self.prefix_command(canonical, mode))
+                        .build(),
+                )
+                .await
+                .context("failed to send interpreted command")?;
+        }
+
+        self.run_command(cmd, ctx, src, mode).await
+    }
+
+    /// Run the custom command named by the input, if there is one.  Returns
+    /// false if no custom command matched, so the original error can be
+    /// reported instead.
+    async fn handle_custom_response(
+        &self,
+        s: &str,
+        ctx: &Context,
+        src: &CommandSource<'_>,
+        guild: GuildId,
+        mode: ChannelMode,
+    ) -> Result<bool>
+    {
+        let custom = self
+            .custom
+            .get(guild, &self.pool)
+            .context("failed to get custom commands")?;
+
+        let content = match custom.parse(docbot::tokenize(s)?) {
+            Ok(cmd) => custom.respond(&cmd, src.author.id, src.channel_id),
+            Err(docbot::CommandParseError::BadId(docbot::IdParseError::NoMatch(..), _)) => {
+                return Ok(false)
+            },
+            Err(e) => format!(
+                "{} {}",
+                src.lang.strings().error,
+                self.format_cmd_error(e, Some(s), mode, src.lang.strings())
+            ),
+        };
+
+        src.channel_id
+            .say(ctx, content)
+            .await
+            .context("failed to send custom command response")?;
+
+        Ok(true)
+    }
+
+    async fn run_command(
+        &self,
+        cmd: BaseCommand,
+        ctx: Context,
+        src: &CommandSource<'_>,
+        mode: ChannelMode,
+    ) -> Result<()>
+    {
+        use BaseCommand::{
+            Channel, Custom, Help, Language, Modmail, Role, Schedule, Ticket, Version,
+        };
+
+        let chan = src.channel_id;
+        let strings = src.lang.strings();
+        let http = Arc::clone(&ctx.http);
+
+        let _d = defer(|| {
+            if thread::panicking() {
+                tokio::task::spawn_blocking(move || {
+                    runtime::Builder::new()
+                        .enable_all()
+                        .basic_scheduler()
+                        .build()
+                        .unwrap()
+                        .block_on(chan.say(http, format!("{} {}", strings.error, strings.panicked)))
+                        .ok()
+                });
+            }
+        });
+
+        if let Some(instead) = cmd.deprecation() {
+            chan.say(
+                &ctx,
+                format!("{} {}{}", strings.note, strings.deprecated, instead),
+            )
+            .await
+            .context("failed to send deprecation warning")?;
+        }
+
+        match cmd {
+            Help(path) => self.handle_help(ctx, src, &path, mode).await?,
             Version => Self::send_version(chan, ctx).await?,
-            Role(c) => self.handle_role_command(ctx, msg, c).await?,
-            Channel(c) => self.handle_channel_command(ctx, msg, c).await?,
-            Schedule(_) => todo!(),
-            Modmail(_message) => todo!(),
+            Role(c) => self.handle_role_command(ctx, src, c, mode).await?,
+            Channel(c) => self.handle_channel_command(ctx, src, c, mode).await?,
+            Schedule(c) => self.handle_schedule_command(ctx, src, c, mode).await?,
+            Custom(c) => self.handle_custom_command(ctx, src, c, mode).await?,
+            Language(c) => self.handle_language_command(ctx, src, c, mode).await?,
+            Modmail(message) => self.handle_modmail(ctx, src, message).await?,
+            Ticket(c) => self.handle_ticket_command(ctx, src, c, mode).await?,
         }
 
         Ok(())
     }
+
+    async fn register_slash_commands(
+        http: Arc<Http>,
+        slash: Arc<SlashClient>,
+        guilds: Vec<GuildId>,
+    ) {
+        let app = stupid_try!(
+            http.get_current_application_info().await,
+            e => error!("failed to get application info: {:?}", e)
+        )
+        .id;
+        let defs = slash::definitions();
+
+        for guild in guilds {
+            slash
+                .register(app, guild, &defs)
+                .await
+                .map_err(|e| error!("failed to register slash commands for {}: {:?}", guild, e))
+                .ok();
+        }
+    }
+
+    async fn handle_interaction(&self, ctx: Context, interaction: &Interaction) -> Result<()> {
+        let (data, chan, author) = match (
+            &interaction.data,
+            interaction.channel_id,
+            interaction.author(),
+        ) {
+            (Some(d), Some(c), Some(a)) if interaction.kind == slash::APPLICATION_COMMAND => {
+                (d, c, a)
+            },
+            _ => return Ok(()),
+        };
+
+        let mode = match interaction.guild_id {
+            Some(guild) => {
+                channels::get_channel_mode(chan, guild, &self.pool)
+                    .context("failed to get channel mode")?
+                    .0
+            },
+            None => ChannelMode::Commands,
+        };
+
+        let lang = self.guild_language(interaction.guild_id);
+
+        if let ChannelMode::Disabled | ChannelMode::Announcements = mode {
+            return self
+                .send_commands_disabled_interaction(interaction, lang.strings())
+                .await;
+        }
+
+        let tokens = slash::command_tokens(data).context("failed to read slash command")?;
+
+        // Interactions must be answered, so reply privately with the text
+        // command being run and send the output to the channel as usual
+        self.slash
+            .respond(
+                interaction,
+                MessageBuilder::new()
+                    .push(lang.strings().running)
+                    .push_mono_safer(self.prefix_command(docbot::join_tokens(&tokens), mode))
+                    .build(),
+            )
+            .await?;
+
+        let src = CommandSource {
+            channel_id: chan,
+            guild_id: interaction.guild_id,
+            author,
+            message: None,
+            lang,
+        };
+
+        let arg_ctx = self.arg_context(&ctx, interaction.guild_id).await;
+
+        match BaseCommand::parse_with(&arg_ctx, tokens) {
+            Ok(cmd) => self.run_command(cmd, ctx, &src, mode).await,
+            Err(e) => {
+                chan.say(
+                    ctx,
+                    format!(
+                        "{} {}",
+                        lang.strings().error,
+                        self.format_cmd_error(e, None, mode, lang.strings())
+                    ),
+                )
+                .await
+                .context("failed to send command parse error")?;
+
+                Ok(())
+            },
+        }
+    }
 }
 
 #[async_trait]
@@ -643,10 +2145,24 @@ impl EventHandler for Handler {
     async fn ready(&self, ctx: Context, ready: Ready) {
         self.me.store(*ready.user.id.as_u64(), Ordering::Release);
 
+        // ready fires again on reconnect, so only start the scheduler once
+        if !self.scheduler_started.swap(true, Ordering::AcqRel) {
+            tokio::spawn(Self::run_scheduler(
+                Arc::clone(&ctx.http),
+                self.pool.clone(),
+            ));
+        }
+
+        tokio::spawn(Self::register_slash_commands(
+            Arc::clone(&ctx.http),
+            Arc::clone(&self.slash),
+            ready.guilds.iter().map(GuildStatus::id).collect(),
+        ));
+
         ctx.set_presence(
             Some(Activity::playing(&format!(
                 "CS:GO | {}",
-                self.prefix_command("help")
+                self.prefix_command("help", ChannelMode::Normal)
             ))),
             OnlineStatus::Online,
         )
@@ -665,21 +2181,79 @@ impl EventHandler for Handler {
             return;
         }
 
+        let mode = match msg.guild_id {
+            Some(guild) => {
+                stupid_try!(
+                    channels::get_channel_mode(msg.channel_id, guild, &self.pool),
+                    e => error!("error while getting channel mode: {:?}", e)
+                )
+                .0
+            },
+            // DMs are always treated as commands
+            None => ChannelMode::Commands,
+        };
+
+        if let ChannelMode::Disabled = mode {
+            return;
+        }
+
+        let command = match self.prefix_re.find(&msg.content) {
+            Some(m) => &msg.content[m.end()..],
+            None if mode == ChannelMode::Commands => msg.content.as_str(),
+            // TODO: identify if the message is Important(tm)
+            None => return,
+        };
+
+        // Only look up the language once it's clear the bot will reply
         let spare_http = Arc::clone(&ctx.http);
-        let result = if let Some(mat) = self.prefix_re.find(&msg.content) {
-            let rest = &msg.content[mat.end()..];
+        let src = CommandSource {
+            lang: self.guild_language(msg.guild_id),
+            ..CommandSource::from(&msg)
+        };
 
-            self.handle_command(rest, ctx, &msg).await
-        } else if let Channel::Private(..) = stupid_try!(
-            msg.channel_id.to_channel(&ctx).await,
-            e => error!("error while getting message channel: {:?}", e)
-        ) {
-            self.handle_command(&msg.content, ctx, &msg).await
+        let result = if let ChannelMode::Announcements = mode {
+            Self::send_commands_disabled(msg.channel_id, &ctx, src.lang.strings()).await
         } else {
-            // TODO: identify if the message is Important(tm)
-            Ok(())
+            self.handle_command(command, ctx, &src, mode).await
         };
 
         stupid_try!(result, e => Self::send_err_message(msg.channel_id, spare_http, e).await);
     }
+
+    async fn guild_create(&self, ctx: Context, guild: Guild, is_new: bool) {
+        if is_new {
+            tokio::spawn(Self::register_slash_commands(
+                Arc::clone(&ctx.http),
+                Arc::clone(&self.slash),
+                vec![guild.id],
+            ));
+        }
+    }
+
+    async fn guild_delete(&self, _: Context, incomplete: GuildUnavailable, _: Option<Guild>) {
+        // Guilds going through an outage are still joined, so only forget the
+        // custom commands of guilds the bot was removed from
+        if !incomplete.unavailable {
+            self.custom.invalidate(incomplete.id);
+        }
+    }
+
+    async fn unknown(&self, ctx: Context, name: String, raw: Value) {
+        if name != "INTERACTION_CREATE" {
+            return;
+        }
+
+        let interaction: Interaction = stupid_try!(
+            serde_json::from_value(raw),
+            e => error!("failed to parse interaction: {:?}", e)
+        );
+        let spare_http = Arc::clone(&ctx.http);
+
+        if let Err(e) = self.handle_interaction(ctx, &interaction).await {
+            match interaction.channel_id {
+                Some(chan) => Self::send_err_message(chan, spare_http, e).await,
+                None => error!("error while handling interaction: {:?}", e),
+            }
+        }
+    }
 }
@@ -0,0 +1,166 @@
+//! Rendering of help topics as Discord messages
+
+use crate::{bot::channels::ChannelMode, event_handler::Handler, locale::Strings};
+use docbot::{render, ArgumentDesc, CommandDesc, CommandUsage, DocSection, HelpRenderer, HelpText};
+use serenity::utils::MessageBuilder;
+use std::fmt::Display;
+
+/// A help topic rendered as a Discord message
+pub struct HelpMessage {
+    /// The text of the message, if any
+    pub content: Option<String>,
+    /// The title and description of the message's embed, if any
+    pub embed: Option<(String, String)>,
+    /// The names and values of any further fields of the embed
+    pub fields: Vec<(String, String)>,
+}
+
+/// Renders help topics as a message with an embed, with referenced commands
+/// written the way they would be run in the channel the help was sent to
+pub struct DiscordRenderer<'a, T> {
+    /// The handler sending the help, used to prefix referenced commands
+    pub handler: &'a Handler,
+    /// The mode of the channel the help is sent to
+    pub mode: ChannelMode,
+    /// The messages used for headings, in the language of the help
+    pub strings: &'static Strings,
+    /// The title of the embed listing a set of commands
+    pub list_title: T,
+}
+
+impl<T: Display> DiscordRenderer<'_, T> {
+    fn text(&self, text: &HelpText) -> String {
+        render::render_text(text, |cmd| self.command_ref(cmd))
+    }
+
+    fn command_ref(&self, cmd: &str) -> String {
+        format!("`{}`", self.handler.prefix_command(cmd, self.mode))
+    }
+
+    fn usage(&self, usage: &CommandUsage, rich: bool) -> String {
+        let line = render::usage_line(usage);
+        let desc = self.text(&usage.desc);
+
+        if rich {
+            format!("**{}**\n{}", line, desc)
+        } else {
+            format!("{}\n{}", line, desc)
+        }
+    }
+}
+
+impl<T: Display> HelpRenderer for DiscordRenderer<'_, T> {
+    type Output = HelpMessage;
+
+    fn command(&self, usage: &CommandUsage, desc: &CommandDesc) -> HelpMessage {
+        enum Block<'a> {
+            Par(&'a HelpText),
+            Head(&'a str),
+            Arg(&'a ArgumentDesc),
+            Example(&'static str),
+        }
+
+        let mut m = MessageBuilder::new();
+
+        for (i, block) in desc
+            .summary
+            .iter()
+            .map(Block::Par)
+            .chain(
+                desc.args
+                    .first()
+                    .map(|_| Block::Head(self.strings.arguments)),
+            )
+            .chain(desc.args.iter().map(Block::Arg))
+            .chain(
+                desc.examples
+                    .first()
+                    .map(|_| Block::Head(self.strings.examples)),
+            )
+            .chain(desc.examples.iter().map(|s| Block::Example(s)))
+            .enumerate()
+        {
+            if i != 0 {
+                m.push('\n');
+            }
+
+            match block {
+                Block::Par(s) => {
+                    m.push_line(self.text(s));
+                },
+                Block::Head(s) => {
+                    m.push_bold_safe(s);
+                },
+                Block::Arg(a) => {
+                    m.push(" - ").push_bold_safe(render::argument_name(a));
+
+                    if !a.is_required {
+                        m.push(" (").push_safe(self.strings.optional).push(")");
+                    }
+
+                    m.push(": ").push(self.text(&a.desc));
+
+                    if let Some(hint) = render::user_hint(a) {
+                        m.push(" (").push_italic_safe(hint.to_string()).push(")");
+                    }
+
+                    m.push('\n');
+                },
+                Block::Example(s) => {
+                    m.push(" - ").push_mono_line_safe(s);
+                },
+            }
+        }
+
+        let fields = desc
+            .sections
+            .iter()
+            .map(|section| match section {
+                DocSection::Text(title, text) => ((*title).into(), self.text(text)),
+                DocSection::SeeAlso(refs) => (
+                    self.strings.see_also.into(),
+                    refs.iter()
+                        .map(|cmd| self.command_ref(cmd))
+                        .collect::<Vec<_>>()
+                        .join(", "),
+                ),
+            })
+            .collect();
+
+        HelpMessage {
+            content: Some(format!(
+                "**{}** {}",
+                self.strings.usage,
+                self.usage(usage, false)
+            )),
+            embed: Some((self.strings.description.into(), m.build())),
+            fields,
+        }
+    }
+
+    fn command_set(&self, summary: Option<&HelpText>, commands: &[&CommandUsage]) -> HelpMessage {
+        let mut m = MessageBuilder::new();
+
+        for (i, cmd) in commands.iter().enumerate() {
+            if i != 0 {
+                m.push('\n');
+            }
+
+            m.push(" - ").push_line(self.usage(cmd, true));
+        }
+
+        HelpMessage {
+            content: summary.map(|s| self.text(s)),
+            embed: Some((self.list_title.to_string(), m.build())),
+            fields: Vec::new(),
+        }
+    }
+
+    fn custom(&self, text: &HelpText) -> HelpMessage {
+        HelpMessage {
+            content: Some(self.text(text)),
+            embed: None,
+            fields: Vec::new(),
+        }
+    }
+}
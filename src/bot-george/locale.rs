@@ -0,0 +1,513 @@
+//! Catalogs of the bot's help and error messages in each supported language
+
+/// The text of the bot's help and error messages in one language.  Messages
+/// which wrap a value, such as a command name, are split into the text before
+/// and after it.
+#[derive(Debug)]
+pub struct Strings {
+    /// Marks a message as an error
+    pub error: &'static str,
+    /// Marks a message as a warning
+    pub note: &'static str,
+
+    // Help
+    /// The title of the list of top-level commands
+    pub commands: &'static str,
+    /// The title of a list of subcommands
+    pub subcommands: &'static str,
+    /// The title of the list of a server's custom commands
+    pub custom_commands: &'static str,
+    /// The title of the list of roles
+    pub roles: &'static str,
+    /// The title of the list of channel modes
+    pub channel_modes: &'static str,
+    /// The title of the list of languages
+    pub languages: &'static str,
+    /// Introduces the usage line of a command
+    pub usage: &'static str,
+    /// The title of a command's description
+    pub description: &'static str,
+    /// The heading of a command's arguments
+    pub arguments: &'static str,
+    /// The heading of a command's examples
+    pub examples: &'static str,
+    /// The heading of a command's related commands
+    pub see_also: &'static str,
+    /// Marks an argument as optional
+    pub optional: &'static str,
+
+    // General errors
+    /// A command requiring a server was run in a DM
+    pub guild_required: &'static str,
+    /// A command was run in an announcements channel
+    pub commands_disabled: &'static str,
+    /// A slash command was run in a channel that does not accept commands
+    pub commands_disabled_here: &'static str,
+    /// A channel argument named a channel in another server
+    pub channel_not_in_guild: &'static str,
+    /// The command handler panicked
+    pub panicked: &'static str,
+    /// Introduces the replacement for a deprecated command
+    pub deprecated: &'static str,
+
+    // Permissions, each completing the `no_permission` message
+    /// Introduces the action the user is not allowed to take
+    pub no_permission: &'static str,
+    pub show_roles: &'static str,
+    /// Followed by the name of a role
+    pub add_role: &'static str,
+    /// Followed by the name of a role
+    pub remove_role: &'static str,
+    pub edit_aliases: &'static str,
+    pub show_channels: &'static str,
+    pub edit_channels: &'static str,
+    pub manage_tickets: &'static str,
+    pub configure_inbox: &'static str,
+    pub show_schedule: &'static str,
+    pub edit_schedule: &'static str,
+    pub edit_custom: &'static str,
+    pub edit_language: &'static str,
+
+    // Command parse errors
+    /// Followed by the unrecognized name
+    pub not_sure: &'static str,
+    /// Introduces a list of suggestions
+    pub did_you_mean: &'static str,
+    /// Introduces the names an ambiguous name could refer to
+    pub could_be: &'static str,
+    pub unterminated_quote: &'static str,
+    pub no_input: &'static str,
+    /// Followed by an argument name
+    pub missing_required: &'static str,
+    /// Surrounds an argument name, followed by the reason it failed
+    pub bad_convert: (&'static str, &'static str),
+    /// Surrounds the first extra argument
+    pub trailing: (&'static str, &'static str),
+    /// Followed by an option name
+    pub unknown_option: &'static str,
+    /// Surrounds an option name
+    pub duplicate_option: (&'static str, &'static str),
+    /// Followed by an option name
+    pub missing_option_value: &'static str,
+    /// Surrounds a subcommand name, followed by its error
+    pub subcommand_failed: (&'static str, &'static str),
+    /// Surrounds a help command to run
+    pub more_info: (&'static str, &'static str),
+    /// Follows a command name and surrounds the name given after it
+    pub no_subcommands: (&'static str, &'static str),
+    /// Surrounds a subcommand name, followed by its help error
+    pub subcommand_help: (&'static str, &'static str),
+    /// Followed by the expanded form of an abbreviated command
+    pub interpreted_as: &'static str,
+    /// Followed by the text command a slash command runs
+    pub running: &'static str,
+
+    // Roles
+    /// The title of a list of assigned roles
+    pub assigned_roles: &'static str,
+    pub no_roles: &'static str,
+    /// Surrounds a page number, followed by the page count
+    pub page: (&'static str, &'static str),
+    pub role_added: &'static str,
+    /// Surrounds a number of roles
+    pub roles_added: (&'static str, &'static str),
+    pub role_removed: &'static str,
+    /// Surrounds a number of roles
+    pub roles_removed: (&'static str, &'static str),
+
+    // Aliases
+    pub alias_set: &'static str,
+    pub alias_cleared: &'static str,
+    /// Surrounds an alias
+    pub alias_taken: (&'static str, &'static str),
+
+    // Channels
+    /// The title of a single channel's mode
+    pub channel_mode: &'static str,
+    /// Followed by a channel mode
+    pub default_mode: &'static str,
+    /// Follows the mode of a channel that has not been marked
+    pub is_default: &'static str,
+    pub no_channels: &'static str,
+    pub default_mode_set: &'static str,
+    pub channel_marked: &'static str,
+    pub channel_unmarked: &'static str,
+
+    // Modmail
+    pub no_modmail_guilds: &'static str,
+    pub multiple_modmail_guilds: &'static str,
+    pub no_modmail: &'static str,
+    pub no_inbox: &'static str,
+    /// Followed by a ticket ID
+    pub no_ticket: &'static str,
+    /// Surrounds a ticket ID
+    pub ticket_closed: (&'static str, &'static str),
+    /// The title of the list of open tickets
+    pub open_tickets: &'static str,
+    pub no_tickets: &'static str,
+    /// Surrounds the ID of a ticket, followed by the name of its server
+    pub from_moderators: (&'static str, &'static str),
+    /// Surrounds the ID of a ticket
+    pub from_author: (&'static str, &'static str),
+    /// Followed by a ticket ID
+    pub new_ticket: &'static str,
+    /// Followed by the command to reply to a ticket
+    pub reply_with: &'static str,
+    /// Surrounds the ID of a newly-opened ticket
+    pub ticket_sent: (&'static str, &'static str),
+    /// Surrounds the inbox channel
+    pub inbox_is: (&'static str, &'static str),
+    pub inbox_set: &'static str,
+    /// Followed by a ticket ID
+    pub reply_to_ticket: &'static str,
+    pub reply_sent: &'static str,
+    /// Surrounds a ticket ID
+    pub reopened_title: (&'static str, &'static str),
+    pub reopened_notice: &'static str,
+    pub reopened: &'static str,
+    /// Surrounds a ticket ID
+    pub closed_title: (&'static str, &'static str),
+    pub closed_notice: &'static str,
+    pub closed: &'static str,
+
+    // Schedule
+    /// Followed by an announcement ID
+    pub no_announcement: &'static str,
+    /// Follows a channel and surrounds the command to mark it
+    pub not_announcements: (&'static str, &'static str),
+    /// Follows a time
+    pub in_past: &'static str,
+    /// The title of the list of announcements
+    pub scheduled_announcements: &'static str,
+    pub no_announcements: &'static str,
+    /// Separates an announcement ID from its channel
+    pub announcement_in: &'static str,
+    /// Followed by how often an announcement repeats
+    pub every: &'static str,
+    /// Follows a paused announcement
+    pub paused: &'static str,
+    /// Followed by an announcement ID
+    pub announcement: &'static str,
+    pub channel: &'static str,
+    pub next_run: &'static str,
+    pub next_run_paused: &'static str,
+    pub repeats: &'static str,
+    pub never: &'static str,
+    /// Followed by how often an announcement repeats
+    pub repeats_every: &'static str,
+    /// Surrounds an announcement ID, followed by the time it will run
+    pub announcement_added: (&'static str, &'static str),
+    pub announcement_removed: &'static str,
+    pub announcement_paused: &'static str,
+    pub announcement_resumed: &'static str,
+
+    // Custom commands
+    /// Follows a command name
+    pub reserved: &'static str,
+    /// Surrounds a command name, followed by the command to remove it
+    pub custom_exists: (&'static str, &'static str),
+    /// Follows the command to remove an existing custom command
+    pub custom_exists_end: &'static str,
+    /// Followed by a command name
+    pub no_custom: &'static str,
+    pub no_custom_commands: &'static str,
+    /// Surrounds a command name
+    pub custom_added: (&'static str, &'static str),
+    pub custom_removed: &'static str,
+
+    // Languages
+    /// Followed by the name of the language
+    pub language_is: &'static str,
+    /// Followed by the name of the language
+    pub language_set: &'static str,
+    pub language_reset: &'static str,
+}
+
+/// English messages, used when no other language is chosen
+pub static ENGLISH: Strings = Strings {
+    error: "**ERROR:**",
+    note: "**NOTE:**",
+
+    commands: "Commands",
+    subcommands: "Subcommands",
+    custom_commands: "Custom commands",
+    roles: "Roles",
+    channel_modes: "Channel modes",
+    languages: "Languages",
+    usage: "Usage:",
+    description: "Description",
+    arguments: "Arguments",
+    examples: "Examples",
+    see_also: "See also",
+    optional: "optional",
+
+    guild_required: "This command cannot be used in a DM channel.",
+    commands_disabled: "Commands cannot be used in an announcements channel.",
+    commands_disabled_here: "Commands cannot be used in this channel.",
+    channel_not_in_guild: "That channel is not part of this server.",
+    panicked: "thread panicked while servicing your request",
+    deprecated: "This command is deprecated, ",
+
+    no_permission: "You do not have permission to ",
+    show_roles: "show assigned roles",
+    add_role: "add the role ",
+    remove_role: "remove the role ",
+    edit_aliases: "change user aliases",
+    show_channels: "show channel modes",
+    edit_channels: "change channel modes",
+    manage_tickets: "manage modmail tickets",
+    configure_inbox: "configure the modmail inbox",
+    show_schedule: "show scheduled announcements",
+    edit_schedule: "schedule announcements",
+    edit_custom: "manage custom commands",
+    edit_language: "change the server language",
+
+    not_sure: "Not sure what you mean by ",
+    did_you_mean: "Did you mean: ",
+    could_be: ", could be ",
+    unterminated_quote: "Unterminated quote",
+    no_input: "Expected a command, got nothing",
+    missing_required: "Missing required argument ",
+    bad_convert: ("Failed to process argument ", ": "),
+    trailing: ("Too many arguments given (starting with ", ")"),
+    unknown_option: "Unknown option ",
+    duplicate_option: ("Option ", " was given more than once"),
+    missing_option_value: "Missing value for option ",
+    subcommand_failed: ("Subcommand ", " failed: "),
+    more_info: ("Run ", " for more info"),
+    no_subcommands: (" has no subcommands (got ", ")"),
+    subcommand_help: ("Subcommand ", ": "),
+    interpreted_as: "Interpreted as ",
+    running: "Running ",
+
+    assigned_roles: "Assigned roles",
+    no_roles: "No roles are assigned.",
+    page: ("Page ", " of "),
+    role_added: "Added 1 role.",
+    roles_added: ("Added ", " roles."),
+    role_removed: "Removed 1 role.",
+    roles_removed: ("Removed ", " roles."),
+
+    alias_set: "Alias updated.",
+    alias_cleared: "Alias removed.",
+    alias_taken: ("The alias ", " is already in use."),
+
+    channel_mode: "Channel mode",
+    default_mode: "Default mode: ",
+    is_default: " (default)",
+    no_channels: "No channels are marked.",
+    default_mode_set: "Default channel mode updated.",
+    channel_marked: "Channel mode updated.",
+    channel_unmarked: "Channel mode reset to default.",
+
+    no_modmail_guilds: "None of your servers accept modmail.",
+    multiple_modmail_guilds: "More than one of your servers accepts modmail.  Please send your \
+                              message from a channel in the server you want to contact.",
+    no_modmail: "This server does not accept modmail.",
+    no_inbox: "No modmail inbox is configured.",
+    no_ticket: "No ticket found with ID ",
+    ticket_closed: ("Ticket ", " is closed"),
+    open_tickets: "Open tickets",
+    no_tickets: "No tickets are open.",
+    from_moderators: ("Ticket ", " \u{2022} From the moderators of "),
+    from_author: ("Ticket ", " \u{2022} From the ticket author"),
+    new_ticket: "New ticket ",
+    reply_with: "Reply with ",
+    ticket_sent: (
+        "Your message was sent to the moderators as ticket ",
+        ".  Any replies will be sent to you here.",
+    ),
+    inbox_is: ("Modmail is delivered to ", "."),
+    inbox_set: "Modmail inbox updated.",
+    reply_to_ticket: "Reply to ticket ",
+    reply_sent: "Reply sent.",
+    reopened_title: ("Ticket ", " reopened"),
+    reopened_notice: "This ticket can be replied to again.",
+    reopened: "Ticket reopened.",
+    closed_title: ("Ticket ", " closed"),
+    closed_notice: "This ticket can no longer be replied to.",
+    closed: "Ticket closed.",
+
+    no_announcement: "No announcement found with ID ",
+    not_announcements: (
+        " is not an announcements channel.  Mark it with ",
+        " first.",
+    ),
+    in_past: " is in the past.",
+    scheduled_announcements: "Scheduled announcements",
+    no_announcements: "No announcements are scheduled.",
+    announcement_in: " in ",
+    every: ", every ",
+    paused: " (paused)",
+    announcement: "Announcement ",
+    channel: "Channel",
+    next_run: "Next run",
+    next_run_paused: "Next run (paused)",
+    repeats: "Repeats",
+    never: "Never",
+    repeats_every: "Every ",
+    announcement_added: ("Scheduled announcement ", " for "),
+    announcement_removed: "Announcement removed.",
+    announcement_paused: "Announcement paused.",
+    announcement_resumed: "Announcement resumed.",
+
+    reserved: " would conflict with a built-in command.",
+    custom_exists: (
+        "A custom command named ",
+        " already exists.  Remove it with ",
+    ),
+    custom_exists_end: " first.",
+    no_custom: "No custom command found named ",
+    no_custom_commands: "No custom commands defined.",
+    custom_added: ("Added custom command ", "."),
+    custom_removed: "Custom command removed.",
+
+    language_is: "This server's language is ",
+    language_set: "Server language set to ",
+    language_reset: "Server language reset to English.",
+};
+
+/// French messages
+pub static FRENCH: Strings = Strings {
+    error: "**ERREUR :**",
+    note: "**REMARQUE :**",
+
+    commands: "Commandes",
+    subcommands: "Sous-commandes",
+    custom_commands: "Commandes personnalisées",
+    roles: "Rôles",
+    channel_modes: "Modes de salon",
+    languages: "Langues",
+    usage: "Utilisation :",
+    description: "Description",
+    arguments: "Arguments",
+    examples: "Exemples",
+    see_also: "Voir aussi",
+    optional: "facultatif",
+
+    guild_required: "Cette commande ne peut pas être utilisée en message privé.",
+    commands_disabled: "Les commandes ne peuvent pas être utilisées dans un salon d'annonces.",
+    commands_disabled_here: "Les commandes ne peuvent pas être utilisées dans ce salon.",
+    channel_not_in_guild: "Ce salon ne fait pas partie de ce serveur.",
+    panicked: "une erreur interne s'est produite lors du traitement de votre demande",
+    deprecated: "Cette commande est obsolète, ",
+
+    no_permission: "Vous n'avez pas la permission de ",
+    show_roles: "afficher les rôles attribués",
+    add_role: "ajouter le rôle ",
+    remove_role: "retirer le rôle ",
+    edit_aliases: "modifier les alias des utilisateurs",
+    show_channels: "afficher les modes de salon",
+    edit_channels: "modifier les modes de salon",
+    manage_tickets: "gérer les tickets de modmail",
+    configure_inbox: "configurer la boîte de réception de modmail",
+    show_schedule: "afficher les annonces programmées",
+    edit_schedule: "programmer des annonces",
+    edit_custom: "gérer les commandes personnalisées",
+    edit_language: "changer la langue du serveur",
+
+    not_sure: "Je ne comprends pas ",
+    did_you_mean: "Vouliez-vous dire : ",
+    could_be: ", cela pourrait être ",
+    unterminated_quote: "Guillemet non fermé",
+    no_input: "Une commande était attendue, rien n'a été reçu",
+    missing_required: "Argument obligatoire manquant : ",
+    bad_convert: ("Impossible de traiter l'argument ", " : "),
+    trailing: ("Trop d'arguments (à partir de ", ")"),
+    unknown_option: "Option inconnue : ",
+    duplicate_option: ("L'option ", " a été donnée plusieurs fois"),
+    missing_option_value: "Valeur manquante pour l'option ",
+    subcommand_failed: ("La sous-commande ", " a échoué : "),
+    more_info: ("Lancez ", " pour plus d'informations"),
+    no_subcommands: (" n'a pas de sous-commandes (reçu ", ")"),
+    subcommand_help: ("Sous-commande ", " : "),
+    interpreted_as: "Interprété comme ",
+    running: "Exécution de ",
+
+    assigned_roles: "Rôles attribués",
+    no_roles: "Aucun rôle n'est attribué.",
+    page: ("Page ", " sur "),
+    role_added: "1 rôle ajouté.",
+    roles_added: ("", " rôles ajoutés."),
+    role_removed: "1 rôle retiré.",
+    roles_removed: ("", " rôles retirés."),
+
+    alias_set: "Alias mis à jour.",
+    alias_cleared: "Alias retiré.",
+    alias_taken: ("L'alias ", " est déjà utilisé."),
+
+    channel_mode: "Mode du salon",
+    default_mode: "Mode par défaut : ",
+    is_default: " (par défaut)",
+    no_channels: "Aucun salon n'est marqué.",
+    default_mode_set: "Mode de salon par défaut mis à jour.",
+    channel_marked: "Mode du salon mis à jour.",
+    channel_unmarked: "Le mode du salon est revenu à la valeur par défaut.",
+
+    no_modmail_guilds: "Aucun de vos serveurs n'accepte le modmail.",
+    multiple_modmail_guilds: "Plusieurs de vos serveurs acceptent le modmail.  Veuillez \
+                              envoyer votre message depuis un salon du serveur que vous \
+                              souhaitez contacter.",
+    no_modmail: "Ce serveur n'accepte pas le modmail.",
+    no_inbox: "Aucune boîte de réception de modmail n'est configurée.",
+    no_ticket: "Aucun ticket trouvé avec l'identifiant ",
+    ticket_closed: ("Le ticket ", " est fermé"),
+    open_tickets: "Tickets ouverts",
+    no_tickets: "Aucun ticket n'est ouvert.",
+    from_moderators: ("Ticket ", " \u{2022} De la part des modérateurs de "),
+    from_author: ("Ticket ", " \u{2022} De la part de l'auteur du ticket"),
+    new_ticket: "Nouveau ticket ",
+    reply_with: "Répondez avec ",
+    ticket_sent: (
+        "Votre message a été envoyé aux modérateurs sous le ticket ",
+        ".  Les réponses vous seront envoyées ici.",
+    ),
+    inbox_is: ("Le modmail est envoyé dans ", "."),
+    inbox_set: "Boîte de réception de modmail mise à jour.",
+    reply_to_ticket: "Réponse au ticket ",
+    reply_sent: "Réponse envoyée.",
+    reopened_title: ("Ticket ", " rouvert"),
+    reopened_notice: "Il est de nouveau possible de répondre à ce ticket.",
+    reopened: "Ticket rouvert.",
+    closed_title: ("Ticket ", " fermé"),
+    closed_notice: "Il n'est plus possible de répondre à ce ticket.",
+    closed: "Ticket fermé.",
+
+    no_announcement: "Aucune annonce trouvée avec l'identifiant ",
+    not_announcements: (
+        " n'est pas un salon d'annonces.  Marquez-le d'abord avec ",
+        ".",
+    ),
+    in_past: " est dans le passé.",
+    scheduled_announcements: "Annonces programmées",
+    no_announcements: "Aucune annonce n'est programmée.",
+    announcement_in: " dans ",
+    every: ", toutes les ",
+    paused: " (en pause)",
+    announcement: "Annonce ",
+    channel: "Salon",
+    next_run: "Prochain envoi",
+    next_run_paused: "Prochain envoi (en pause)",
+    repeats: "Répétition",
+    never: "Jamais",
+    repeats_every: "Toutes les ",
+    announcement_added: ("Annonce ", " programmée pour le "),
+    announcement_removed: "Annonce supprimée.",
+    announcement_paused: "Annonce mise en pause.",
+    announcement_resumed: "Annonce reprise.",
+
+    reserved: " entrerait en conflit avec une commande intégrée.",
+    custom_exists: (
+        "Une commande personnalisée nommée ",
+        " existe déjà.  Supprimez-la d'abord avec ",
+    ),
+    custom_exists_end: ".",
+    no_custom: "Aucune commande personnalisée nommée ",
+    no_custom_commands: "Aucune commande personnalisée n'est définie.",
+    custom_added: ("Commande personnalisée ", " ajoutée."),
+    custom_removed: "Commande personnalisée supprimée.",
+
+    language_is: "La langue de ce serveur est : ",
+    language_set: "Langue du serveur définie sur : ",
+    language_reset: "La langue du serveur est revenue à l'anglais.",
+};
@@ -17,10 +17,13 @@ mod config;
 mod db;
 pub mod error;
 mod event_handler;
+mod help;
+mod locale;
 mod logging;
 pub mod models;
 #[allow(missing_docs)]
 pub mod schema;
+mod slash;
 pub mod util;
 
 use anyhow::Context;
@@ -32,7 +35,9 @@ use lazy_static::lazy_static;
 use log::{error, info, warn};
 use serenity::{client::Client, model::id::UserId};
 use std::{
-    env, io, panic,
+    env,
+    io::{self, Write},
+    panic,
     sync::atomic::{AtomicBool, Ordering},
 };
 use tokio::{runtime, signal};
@@ -71,7 +76,26 @@ async fn main_async() {
     }
 }
 
+/// Write a JSON description of every command to stdout
+fn dump_commands() -> Result<()> {
+    use docbot::Schema;
+
+    let stdout = io::stdout();
+    let mut stdout = stdout.lock();
+
+    serde_json::to_writer_pretty(&mut stdout, &commands::BaseCommand::schema())
+        .context("failed to serialize command schema")?;
+    writeln!(stdout).context("failed to write command schema")?;
+
+    Ok(())
+}
+
 async fn run() -> Result<()> {
+    // Dump the command reference instead of starting the bot if requested
+    if env::args().skip(1).any(|a| a == "--dump-commands") {
+        return dump_commands();
+    }
+
     // Show the MotD
     {
         use atty::Stream;
@@ -121,6 +145,7 @@ async fn run() -> Result<()> {
     // Set up the API client
     let handler = Handler::new(
         conf.general.command_prefix,
+        &conf.auth.token,
         UserId(conf.auth.superuser),
         db_conn,
     )?;
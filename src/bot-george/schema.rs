@@ -1,3 +1,16 @@
+table! {
+    announcements (id) {
+        id -> Uuid,
+        announcement_id -> Varchar,
+        guild_id -> Int8,
+        channel_id -> Int8,
+        message -> Text,
+        next_run -> Timestamptz,
+        repeat_secs -> Nullable<Int8>,
+        is_paused -> Bool,
+    }
+}
+
 table! {
     channel_modes (channel_id, mode) {
         channel_id -> Uuid,
@@ -10,6 +23,16 @@ table! {
         id -> Uuid,
         alias -> Varchar,
         channel_id -> Int8,
+        guild_id -> Int8,
+    }
+}
+
+table! {
+    custom_commands (id) {
+        id -> Uuid,
+        guild_id -> Int8,
+        name -> Varchar,
+        response -> Text,
     }
 }
 
@@ -20,6 +43,31 @@ table! {
     }
 }
 
+table! {
+    guild_languages (guild_id) {
+        guild_id -> Int8,
+        language -> Text,
+    }
+}
+
+table! {
+    modmail_inboxes (guild_id) {
+        guild_id -> Int8,
+        channel_id -> Int8,
+    }
+}
+
+table! {
+    modmail_tickets (id) {
+        id -> Uuid,
+        ticket_id -> Varchar,
+        guild_id -> Int8,
+        user_id -> Int8,
+        is_open -> Bool,
+        opened_at -> Timestamptz,
+    }
+}
+
 table! {
     user_roles (user_id, role) {
         user_id -> Uuid,
@@ -40,9 +88,14 @@ joinable!(channel_modes -> channels (channel_id));
 joinable!(user_roles -> users (user_id));
 
 allow_tables_to_appear_in_same_query!(
+    announcements,
     channel_modes,
     channels,
+    custom_commands,
     default_channel_modes,
+    guild_languages,
+    modmail_inboxes,
+    modmail_tickets,
     user_roles,
     users,
 );
@@ -0,0 +1,381 @@
+//! Registration and routing of Discord slash commands generated from the
+//! command schema
+
+use crate::{commands::BaseCommand, error::Result};
+use anyhow::{anyhow, Context};
+use docbot::{ArgumentKind, ArgumentSchema, ArgumentUsage, CommandSchema, HelpText, Schema};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use serenity::model::{
+    id::{ChannelId, GuildId, UserId},
+    user::User,
+};
+
+const API_BASE: &str = "https://discord.com/api/v8";
+
+/// Discord's limit on the length of command and option descriptions
+const MAX_DESCRIPTION: usize = 100;
+
+/// Discord's limit on the number of choices for an option
+const MAX_CHOICES: usize = 25;
+
+// Application command option types
+const SUB_COMMAND: u8 = 1;
+const SUB_COMMAND_GROUP: u8 = 2;
+const STRING: u8 = 3;
+const BOOLEAN: u8 = 5;
+
+/// Interaction type for an invoked application command
+pub const APPLICATION_COMMAND: u8 = 2;
+
+/// Interaction response type for replying with a message
+const CHANNEL_MESSAGE_WITH_SOURCE: u8 = 4;
+
+/// Message flag for replies only visible to the user who invoked a command
+const EPHEMERAL: u64 = 1 << 6;
+
+/// An interaction received from the gateway
+#[derive(Deserialize, Debug)]
+pub struct Interaction {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub kind: u8,
+    pub data: Option<InteractionData>,
+    pub guild_id: Option<GuildId>,
+    pub channel_id: Option<ChannelId>,
+    pub member: Option<InteractionMember>,
+    pub user: Option<User>,
+    pub token: String,
+}
+
+/// The guild member who created an interaction
+#[derive(Deserialize, Debug)]
+pub struct InteractionMember {
+    pub user: User,
+}
+
+/// The command invoked by an interaction
+#[derive(Deserialize, Debug)]
+pub struct InteractionData {
+    pub name: String,
+    #[serde(default)]
+    pub options: Vec<InteractionOption>,
+}
+
+/// A subcommand or argument value given to an interaction
+#[derive(Deserialize, Debug)]
+pub struct InteractionOption {
+    pub name: String,
+    #[serde(default)]
+    pub value: Option<Value>,
+    #[serde(default)]
+    pub options: Vec<InteractionOption>,
+}
+
+impl Interaction {
+    /// Get the user who created this interaction, whether it was sent from a
+    /// guild or a DM
+    pub fn author(&self) -> Option<&User> {
+        self.member
+            .as_ref()
+            .map(|m| &m.user)
+            .or_else(|| self.user.as_ref())
+    }
+}
+
+/// Client for the parts of the Discord API used for slash commands
+#[derive(Debug)]
+pub struct SlashClient {
+    client: reqwest::Client,
+    token: String,
+}
+
+impl SlashClient {
+    pub fn new(token: impl Into<String>) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            token: token.into(),
+        }
+    }
+
+    /// Replace all slash commands registered in a guild with the given
+    /// definitions
+    pub async fn register(&self, app: UserId, guild: GuildId, defs: &[Value]) -> Result<()> {
+        self.client
+            .put(&format!(
+                "{}/applications/{}/guilds/{}/commands",
+                API_BASE, app, guild
+            ))
+            .header("Authorization", format!("Bot {}", self.token))
+            .json(defs)
+            .send()
+            .await
+            .context("failed to send command registration request")?
+            .error_for_status()
+            .context("command registration request failed")?;
+
+        Ok(())
+    }
+
+    /// Reply to an interaction with a message only the invoking user can see
+    pub async fn respond(
+        &self,
+        interaction: &Interaction,
+        content: impl Into<String>,
+    ) -> Result<()>
+    {
+        self.client
+            .post(&format!(
+                "{}/interactions/{}/{}/callback",
+                API_BASE, interaction.id, interaction.token
+            ))
+            .json(&json!({
+                "type": CHANNEL_MESSAGE_WITH_SOURCE,
+                "data": {
+                    "content": content.into(),
+                    "flags": EPHEMERAL,
+                },
+            }))
+            .send()
+            .await
+            .context("failed to send interaction response")?
+            .error_for_status()
+            .context("interaction response request failed")?;
+
+        Ok(())
+    }
+}
+
+/// Shorten a description to the first paragraph, truncated to fit Discord's
+/// length limit
+fn description(desc: &HelpText) -> String {
+    let desc = desc.to_string();
+    let desc = desc
+        .split("\n\n")
+        .next()
+        .unwrap_or_default()
+        .replace('\n', " ");
+
+    if desc.chars().count() <= MAX_DESCRIPTION {
+        return desc;
+    }
+
+    let end = desc
+        .char_indices()
+        .nth(MAX_DESCRIPTION - 3)
+        .map_or(desc.len(), |(i, _)| i);
+
+    format!("{}...", &desc[..end])
+}
+
+/// Get the slash command option name for an argument
+fn arg_name(usage: &ArgumentUsage) -> String {
+    match usage.option {
+        Some(ref opt) => opt.long.as_ref().map_or_else(
+            || opt.short.map(String::from).unwrap_or_default(),
+            ToString::to_string,
+        ),
+        None => usage.name.to_string(),
+    }
+}
+
+/// Get the token used to pass an option on the command line
+fn option_token(usage: &ArgumentUsage) -> Option<String> {
+    usage.option.as_ref().map(|opt| {
+        opt.long.as_ref().map_or_else(
+            || format!("-{}", opt.short.unwrap_or_default()),
+            |l| format!("--{}", l),
+        )
+    })
+}
+
+/// Get the slash command name for a command.  Slash command names cannot
+/// contain spaces, so the words of multi-word IDs are joined with dashes.
+fn command_name(cmd: &CommandSchema) -> String { cmd.usage.ids[0].replace(' ', "-") }
+
+/// Get the tokens used to invoke a command
+fn command_words(cmd: &CommandSchema) -> impl Iterator<Item = String> {
+    cmd.usage.ids[0].split(' ').map(Into::into)
+}
+
+fn subcommand(cmd: &CommandSchema) -> Option<&docbot::CommandSetSchema> {
+    cmd.args.iter().find_map(|a| match a.kind {
+        ArgumentKind::Subcommand(ref s) => Some(s),
+        _ => None,
+    })
+}
+
+fn value_option(arg: &ArgumentSchema) -> Value {
+    let mut ret = json!({
+        "type": if let ArgumentKind::Flag = arg.kind { BOOLEAN } else { STRING },
+        "name": arg_name(arg.usage),
+        "description": arg.desc.as_ref().map_or_else(|| arg.usage.name.to_string(), description),
+        "required": arg.usage.is_required,
+    });
+
+    if let ArgumentKind::Choice(ref choices) = arg.kind {
+        if choices.len() <= MAX_CHOICES {
+            ret["choices"] = choices
+                .iter()
+                .map(|c| json!({ "name": c, "value": c }))
+                .collect();
+        }
+    }
+
+    ret
+}
+
+/// Describe the arguments of a command as slash command options.  Discord
+/// requires required options to come first.
+fn value_options(cmd: &CommandSchema) -> Vec<Value> {
+    let mut args: Vec<_> = cmd.args.iter().collect();
+    args.sort_by_key(|a| !a.usage.is_required);
+
+    args.into_iter().map(value_option).collect()
+}
+
+/// Describe a nested command as a subcommand, or as a subcommand group if it
+/// has subcommands of its own and `group` is true.  Discord only allows one
+/// level of groups, so anything nested deeper is taken as a string.
+fn subcommand_option(cmd: &CommandSchema, group: bool) -> Value {
+    let (kind, options) = match subcommand(cmd) {
+        Some(sub) if group => (
+            SUB_COMMAND_GROUP,
+            sub.commands
+                .iter()
+                .filter(|c| !c.usage.hidden)
+                .map(|c| subcommand_option(c, false))
+                .collect(),
+        ),
+        _ => (SUB_COMMAND, value_options(cmd)),
+    };
+
+    json!({
+        "type": kind,
+        "name": command_name(cmd),
+        "description": description(&cmd.usage.desc),
+        "options": options,
+    })
+}
+
+/// Build slash command definitions for every base command not marked hidden
+pub fn definitions() -> Vec<Value> {
+    BaseCommand::schema()
+        .commands
+        .iter()
+        .filter(|cmd| !cmd.usage.hidden)
+        .map(|cmd| {
+            let options: Vec<_> = match subcommand(cmd) {
+                Some(sub) => sub
+                    .commands
+                    .iter()
+                    .filter(|c| !c.usage.hidden)
+                    .map(|c| subcommand_option(c, true))
+                    .collect(),
+                None => value_options(cmd),
+            };
+
+            json!({
+                "name": command_name(cmd),
+                "description": description(&cmd.usage.desc),
+                "options": options,
+            })
+        })
+        .collect()
+}
+
+fn value_str(opt: &InteractionOption) -> Result<String> {
+    match opt.value {
+        Some(Value::String(ref s)) => Ok(s.clone()),
+        Some(ref v) => Ok(v.to_string()),
+        None => Err(anyhow!("missing value for option {:?}", opt.name)),
+    }
+}
+
+/// Append the tokens for an invocation of `cmd` with the given options,
+/// ordered so the command parser reads them back as the same arguments
+fn push_tokens(
+    cmd: &CommandSchema,
+    options: &[InteractionOption],
+    tokens: &mut Vec<String>,
+) -> Result<()>
+{
+    let mut positional = Vec::new();
+
+    for arg in &cmd.args {
+        let name = arg_name(arg.usage);
+        let given = options.iter().find(|o| o.name == name);
+
+        match (&arg.kind, option_token(arg.usage)) {
+            (ArgumentKind::Subcommand(set), _) => {
+                let sub = options.iter().find_map(|o| {
+                    set.commands
+                        .iter()
+                        .find(|c| command_name(c) == o.name)
+                        .map(|c| (o, c))
+                });
+
+                if let Some((opt, sub)) = sub {
+                    positional.extend(command_words(sub));
+                    push_tokens(sub, &opt.options, &mut positional)?;
+                } else if let Some(opt) = given {
+                    positional.extend(
+                        docbot::tokenize(&value_str(opt)?)?
+                            .into_iter()
+                            .map(|t| t.text.into_owned()),
+                    );
+                }
+            },
+            (ArgumentKind::Flag, Some(flag)) => {
+                if let Some(Value::Bool(true)) = given.and_then(|o| o.value.as_ref()) {
+                    tokens.push(flag);
+                }
+            },
+            (_, Some(flag)) => {
+                if let Some(opt) = given {
+                    tokens.push(flag);
+                    tokens.push(value_str(opt)?);
+                }
+            },
+            (_, None) => {
+                if let Some(opt) = given {
+                    if arg.usage.is_rest {
+                        positional.extend(
+                            docbot::tokenize(&value_str(opt)?)?
+                                .into_iter()
+                                .map(|t| t.text.into_owned()),
+                        );
+                    } else {
+                        positional.push(value_str(opt)?);
+                    }
+                }
+            },
+        }
+    }
+
+    // Make sure positional arguments starting with a dash aren't read as
+    // options, if the command accepts any
+    if !positional.is_empty() && cmd.args.iter().any(|a| a.usage.option.is_some()) {
+        tokens.push("--".into());
+    }
+
+    tokens.extend(positional);
+
+    Ok(())
+}
+
+/// Convert an invoked slash command back into the tokens of the equivalent
+/// text command
+pub fn command_tokens(data: &InteractionData) -> Result<Vec<String>> {
+    let schema = BaseCommand::schema();
+    let cmd = schema
+        .commands
+        .iter()
+        .find(|c| command_name(c) == data.name)
+        .ok_or_else(|| anyhow!("unknown slash command {:?}", data.name))?;
+
+    let mut tokens: Vec<_> = command_words(cmd).collect();
+    push_tokens(cmd, &data.options, &mut tokens)?;
+
+    Ok(tokens)
+}
@@ -1,7 +1,71 @@
 //! Contains any small utility structures used by the bot
 
 use serenity::utils::MessageBuilder;
-use std::fmt::Display;
+use std::{fmt, fmt::Display, str::FromStr};
+use thiserror::Error;
+use uuid::Uuid;
+
+/// Characters used in short IDs, omitting ones that are easily confused
+const SHORT_ID_CHARS: &[u8] = b"23456789ABCDEFGHJKLMNPQRSTUVWXYZ";
+
+/// A short, human-readable identifier, such as a modmail ticket ID
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ShortId(String);
+
+/// Error type for strings that cannot be a short ID
+#[derive(Error, Debug)]
+#[error("invalid ID {0:?}")]
+pub struct InvalidShortId(String);
+
+impl ShortId {
+    /// Generate a random short ID of the given length
+    ///
+    /// # Panics
+    /// Panics if `len` is greater than 12, as that would exceed the available
+    /// random bits.
+    #[must_use]
+    pub fn random(len: usize) -> Self {
+        assert!(len <= 12, "short IDs cannot be longer than 12 characters");
+
+        // The low 62 bits of a v4 UUID are random
+        let mut bits = Uuid::new_v4().as_u128();
+
+        Self(
+            (0..len)
+                .map(|_| {
+                    #[allow(clippy::cast_possible_truncation)]
+                    let chr = SHORT_ID_CHARS[(bits & 0x1f) as usize];
+                    bits >>= 5;
+                    chr as char
+                })
+                .collect(),
+        )
+    }
+
+    /// Get the string representation of this ID
+    #[must_use]
+    pub fn as_str(&self) -> &str { &self.0 }
+}
+
+impl FromStr for ShortId {
+    type Err = InvalidShortId;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.is_empty() || s.len() > 16 || !s.chars().all(|c| c.is_ascii_alphanumeric()) {
+            return Err(InvalidShortId(s.into()));
+        }
+
+        Ok(Self(s.to_uppercase()))
+    }
+}
+
+impl From<String> for ShortId {
+    fn from(s: String) -> Self { Self(s) }
+}
+
+impl Display for ShortId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result { f.write_str(&self.0) }
+}
 
 /// Because `MessageBuilder` is missing some edge cases
 pub trait MessageBuilderExt {
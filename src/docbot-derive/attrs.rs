@@ -1,6 +1,6 @@
 use crate::{
     docs::{CommandDocs, ParseDocs},
-    opts::{FieldOpts, ParseOpts},
+    opts::{FieldOpts, OuterOpts, ParseOpts, VariantOpts},
     Result,
 };
 use anyhow::{anyhow, Context};
@@ -50,14 +50,12 @@ fn parse_core<O: ParseOpts, D: ParseDocs>(attrs: &[Attribute], span: Span) -> Re
     ))
 }
 
-pub fn parse_outer<D: ParseDocs>(attrs: &[Attribute], span: Span) -> Result<D> {
-    let ((), ret) = parse_core(attrs, span)?;
-    Ok(ret)
+pub fn parse_outer<D: ParseDocs>(attrs: &[Attribute], span: Span) -> Result<(OuterOpts, D)> {
+    parse_core(attrs, span)
 }
 
-pub fn parse_variant(attrs: &[Attribute], span: Span) -> Result<CommandDocs> {
-    let ((), ret) = parse_core(attrs, span)?;
-    Ok(ret)
+pub fn parse_variant(attrs: &[Attribute], span: Span) -> Result<(VariantOpts, CommandDocs)> {
+    parse_core(attrs, span)
 }
 
 pub fn parse_field(attrs: &[Attribute], span: Span) -> Result<FieldOpts> {
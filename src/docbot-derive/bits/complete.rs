@@ -0,0 +1,197 @@
+#[allow(clippy::wildcard_imports)]
+use super::{
+    help::emit_option_usage,
+    id::IdParts,
+    inputs::*,
+    parse::{field_info, FieldInfo, FieldMode},
+};
+use crate::Result;
+use proc_macro2::{Literal, TokenStream};
+use quote::quote_spanned;
+
+pub struct CompleteParts {
+    pub method: TokenStream,
+}
+
+/// Get the type of a single value of an argument, given its field
+pub(super) fn elem_type(span: Span, FieldInfo { opts, ty, mode, .. }: &FieldInfo) -> TokenStream {
+    match mode {
+        // Optional arguments with a default are stored without the Option
+        _ if opts.default.is_some() => quote_spanned! { span => #ty },
+        FieldMode::Required | FieldMode::Flag(_) => quote_spanned! { span => #ty },
+        FieldMode::Optional
+        | FieldMode::RestRequired
+        | FieldMode::RestOptional
+        | FieldMode::Value(_) => {
+            quote_spanned! { span => <#ty as ::std::iter::IntoIterator>::Item }
+        },
+    }
+}
+
+fn complete_arg(span: Span, info: &FieldInfo, prefix: &Ident) -> TokenStream {
+    let elem = elem_type(span, info);
+
+    quote_spanned! { span =>
+        (&::docbot::__private::ArgType::<#elem>::new()).complete_arg(#prefix)
+    }
+}
+
+fn complete_fields(
+    span: Span,
+    Command { docs, fields }: &Command,
+    done: &Ident,
+    last: &Ident,
+) -> Result<TokenStream>
+{
+    let info = field_info(span, &docs.usage, fields)?;
+    let prefix = Ident::new("__prefix", span);
+
+    let opts = docs
+        .usage
+        .options
+        .iter()
+        .map(|(_, opt)| emit_option_usage(span, opt));
+
+    let mut value_arms = Vec::new();
+    let mut pos_arms = Vec::new();
+    let mut rest = None;
+    let mut subcommand = None;
+
+    for info in &info {
+        let FieldInfo { opts, ty, mode, .. } = info;
+
+        match *mode {
+            FieldMode::Required | FieldMode::Optional => {
+                let idx = Literal::usize_unsuffixed(pos_arms.len());
+                let arg = complete_arg(span, info, &prefix);
+
+                pos_arms.push(quote_spanned! { span => #idx => #arg });
+            },
+            FieldMode::RestRequired | FieldMode::RestOptional if opts.subcommand => {
+                let idx = Literal::usize_unsuffixed(pos_arms.len());
+                let call = quote_spanned! { span =>
+                    <#ty as ::docbot::Command>::complete(
+                        #done[__i..]
+                            .iter()
+                            .map(|s| s.as_ref())
+                            .chain(::std::iter::once(#last)),
+                    )
+                };
+
+                subcommand = Some((idx, call));
+            },
+            FieldMode::RestRequired | FieldMode::RestOptional => {
+                rest = Some(complete_arg(span, info, &prefix));
+            },
+            FieldMode::Flag(_) => (),
+            FieldMode::Value(idx) => {
+                let arg = complete_arg(span, info, &prefix);
+                let idx = Literal::usize_unsuffixed(idx);
+
+                value_arms.push(quote_spanned! { span => #idx => #arg });
+            },
+        }
+    }
+
+    let rest = rest.unwrap_or_else(|| quote_spanned! { span => ::std::vec::Vec::new() });
+    let (sub_idx, sub_call) = subcommand.map_or_else(
+        || {
+            (
+                quote_spanned! { span => None },
+                quote_spanned! { span => ::std::vec::Vec::new() },
+            )
+        },
+        |(idx, call)| (quote_spanned! { span => Some(#idx) }, call),
+    );
+
+    Ok(quote_spanned! { span =>
+        match ::docbot::__private::complete_slot(
+            #done,
+            #last,
+            &[#(#opts),*],
+            #sub_idx,
+        ) {
+            ::docbot::__private::Partial::Options(__opts) => __opts,
+            ::docbot::__private::Partial::Value(__i, __lead, #prefix) => {
+                ::docbot::__private::with_lead(__lead, match __i {
+                    #(#value_arms,)*
+                    _ => ::std::vec::Vec::new(),
+                })
+            },
+            ::docbot::__private::Partial::Positional(__i, #prefix) => match __i {
+                #(#pos_arms,)*
+                _ => #rest,
+            },
+            ::docbot::__private::Partial::Subcommand(__i) => #sub_call,
+        }
+    })
+}
+
+pub fn emit(input: &InputData, id_parts: &IdParts) -> Result<CompleteParts> {
+    let done = Ident::new("__done", input.span);
+    let last = Ident::new("__last", input.span);
+    let id_ty = &id_parts.ty;
+
+    let arms: Vec<_> = match input.commands {
+        Commands::Struct(_, ref cmd) => {
+            let body = complete_fields(input.span, cmd, &done, &last)?;
+
+            vec![quote_spanned! { input.span => #id_ty => #body }]
+        },
+        Commands::Enum(_, ref vars) => vars
+            .iter()
+            .map(
+                |CommandVariant {
+                     span,
+                     ident,
+                     command,
+                     ..
+                 }| {
+                    let body = complete_fields(*span, command, &done, &last)?;
+
+                    Ok(quote_spanned! { *span => #id_ty::#ident => #body })
+                },
+            )
+            .collect::<Result<_>>()?,
+    };
+
+    let method = quote_spanned! { input.span =>
+        #[allow(unused_variables)]
+        fn complete<
+            I: IntoIterator<Item = S>,
+            S: AsRef<str>,
+        >(__iter: I) -> ::std::vec::Vec<::std::string::String> {
+            #[allow(unused_imports)]
+            use ::docbot::__private::{IdArg as _, OtherArg as _};
+
+            let __toks: ::std::vec::Vec<S> = __iter.into_iter().collect();
+            let (#last, __toks) = match __toks.split_last() {
+                Some((__last, __toks)) => (__last.as_ref(), __toks),
+                None => ("", &[][..]),
+            };
+
+            // The words typed so far may be the start of an ID spanning
+            // several words
+            let mut __words = ::docbot::__private::complete_id::<#id_ty, S>(__toks, #last);
+
+            if __toks.is_empty() {
+                return __words;
+            }
+
+            // They may also already name a shorter ID, in which case its
+            // arguments can be completed as well
+            let (__id, #done): (#id_ty, _) = match ::docbot::__private::parse_id(__toks) {
+                Ok((__id, __len)) => (__id, &__toks[__len..]),
+                Err(_) => return __words,
+            };
+
+            __words.extend(match __id {
+                #(#arms),*
+            });
+
+            __words
+        }
+    };
+
+    Ok(CompleteParts { method })
+}
@@ -0,0 +1,83 @@
+#[allow(clippy::wildcard_imports)]
+use super::inputs::*;
+use crate::Result;
+use proc_macro2::{Literal, TokenStream};
+use quote::quote_spanned;
+
+pub struct ExamplesParts {
+    pub items: TokenStream,
+}
+
+pub fn emit(input: &InputData) -> Result<ExamplesParts> {
+    // Tests can't name a concrete type to parse if the input is generic
+    if !input.generics.params.is_empty() {
+        return Ok(ExamplesParts {
+            items: TokenStream::new(),
+        });
+    }
+
+    let examples: Vec<_> = match input.commands {
+        Commands::Struct(ref docs, _) => docs.examples.iter().map(|e| (docs.span, e)).collect(),
+        Commands::Enum(_, ref vars) => vars
+            .iter()
+            .flat_map(|v| v.command.docs.examples.iter().map(move |e| (v.span, e)))
+            .collect(),
+    };
+
+    if examples.is_empty() {
+        return Ok(ExamplesParts {
+            items: TokenStream::new(),
+        });
+    }
+
+    // Quote variables
+    let name = input.ty;
+    let module = Ident::new(
+        &format!("__docbot_examples_{}", name.to_string().to_lowercase()),
+        input.span,
+    );
+
+    let tests = examples
+        .into_iter()
+        .enumerate()
+        .map(|(i, (span, example))| {
+            let test = Ident::new(&format!("example_{}", i), span);
+            let example = Literal::string(example);
+
+            quote_spanned! { span =>
+                #[test]
+                fn #test() {
+                    let __ex = #example;
+                    let __toks = ::docbot::tokenize(__ex)
+                        .unwrap_or_else(|e| panic!("failed to tokenize example {:?}: {}", __ex, e));
+
+                    let __cmd = <super::#name as ::docbot::Command>::parse(__toks)
+                        .unwrap_or_else(|e| panic!("failed to parse example {:?}: {}", __ex, e));
+
+                    // Make sure the example survives being written back out
+                    let __out = ::docbot::Command::to_tokens(&__cmd);
+                    let __reparsed = <super::#name as ::docbot::Command>::parse(&__out)
+                        .unwrap_or_else(|e| {
+                            panic!("failed to reparse example {:?} as {:?}: {}", __ex, __out, e)
+                        });
+
+                    assert_eq!(
+                        __out,
+                        ::docbot::Command::to_tokens(&__reparsed),
+                        "example {:?} did not round-trip",
+                        __ex,
+                    );
+                }
+            }
+        });
+
+    let items = quote_spanned! { input.span =>
+        #[cfg(test)]
+        #[doc(hidden)]
+        mod #module {
+            #(#tests)*
+        }
+    };
+
+    Ok(ExamplesParts { items })
+}
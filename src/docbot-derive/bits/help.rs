@@ -1,8 +1,16 @@
 #[allow(clippy::wildcard_imports)]
-use super::inputs::*;
-use crate::Result;
+use super::{
+    complete::elem_type,
+    inputs::*,
+    parse::{field_info, FieldInfo, FieldMode},
+};
+use crate::{attrs, Result};
+use anyhow::anyhow;
+use lazy_static::lazy_static;
 use proc_macro2::{Literal, TokenStream};
 use quote::quote_spanned;
+use regex::Regex;
+use std::rc::Rc;
 
 pub struct HelpParts {
     pub items: Option<TokenStream>,
@@ -17,162 +25,396 @@ fn emit_bool(b: bool) -> TokenStream {
     }
 }
 
-struct ArgUsage<'a> {
-    name: &'a str,
-    required: bool,
-    rest: bool,
+fn emit_option(span: Span, arg: UsageArg) -> TokenStream {
+    if let UsageArg::Option(opt) = arg {
+        let opt = emit_option_usage(span, opt);
+
+        quote_spanned! { span => Some(#opt) }
+    } else {
+        quote_spanned! { span => None }
+    }
 }
 
-fn emit_usage(docs: &CommandDocs) -> TokenStream {
-    let CommandDocs { span, usage, .. } = docs;
-    let ids = usage.ids.iter().map(|i| Literal::string(&i));
-    let args = usage
-        .required
-        .iter()
-        .map(|n| ArgUsage {
-            name: &n,
-            required: true,
-            rest: false,
-        })
-        .chain(usage.optional.iter().map(|n| ArgUsage {
-            name: &n,
-            required: false,
-            rest: false,
-        }))
-        .chain(match usage.rest {
-            RestArg::None => None,
-            RestArg::Optional(ref n) => Some(ArgUsage {
-                name: &n,
-                required: false,
-                rest: true,
-            }),
-            RestArg::Required(ref n) => Some(ArgUsage {
-                name: &n,
-                required: true,
-                rest: true,
-            }),
-        })
-        .map(
-            |ArgUsage {
-                 name,
-                 required,
-                 rest,
-             }| {
-                let name = Literal::string(name);
-                let required = emit_bool(required);
-                let rest = emit_bool(rest);
-
-                quote_spanned! { *span =>
-                    ::docbot::ArgumentUsage {
-                        name: #name,
-                        is_required: #required,
-                        is_rest: #rest,
-                    }
-                }
-            },
-        );
-    let desc = Literal::string(&usage.desc);
+pub(super) fn emit_option_usage(span: Span, opt: &OptionUsage) -> TokenStream {
+    let OptionUsage { short, long, value } = opt;
 
-    quote_spanned! { *span =>
-        ::docbot::CommandUsage {
-            ids: &[#(#ids),*],
-            args: &[#(#args),*],
-            desc: #desc
+    let short = short.map_or_else(
+        || quote_spanned! { span => None },
+        |s| {
+            let s = Literal::character(s);
+            quote_spanned! { span => Some(#s) }
+        },
+    );
+    let long = emit_opt_cow(span, long.as_deref());
+    let value = emit_opt_cow(span, value.as_deref());
+
+    quote_spanned! { span =>
+        ::docbot::OptionUsage {
+            short: #short,
+            long: #long,
+            value: #value,
         }
     }
 }
 
-fn emit_desc(docs: &CommandDocs) -> TokenStream {
-    let summary = docs.summary.as_ref().map_or_else(
-        || quote_spanned! { docs.span => None },
-        |summary| {
-            let summary = Literal::string(&summary);
+fn emit_opt_str(span: Span, s: Option<&str>) -> TokenStream {
+    s.map_or_else(
+        || quote_spanned! { span => None },
+        |s| {
+            let s = Literal::string(s);
+            quote_spanned! { span => Some(#s) }
+        },
+    )
+}
+
+fn emit_cow(span: Span, s: &str) -> TokenStream {
+    let s = Literal::string(s);
+    quote_spanned! { span => ::std::borrow::Cow::Borrowed(#s) }
+}
 
-            quote_spanned! { docs.span => Some(#summary) }
+fn emit_opt_cow(span: Span, s: Option<&str>) -> TokenStream {
+    s.map_or_else(
+        || quote_spanned! { span => None },
+        |s| {
+            let s = emit_cow(span, s);
+            quote_spanned! { span => Some(#s) }
         },
-    );
+    )
+}
+
+/// Split help text into plain text and ``[`command`]()`` links
+fn emit_help_text(span: Span, s: &str) -> TokenStream {
+    lazy_static! {
+        static ref LINK_RE: Regex = Regex::new(r"\[`([^`]+)`\]\(\)").unwrap();
+    }
+
+    let mut nodes = Vec::new();
+    let mut end = 0;
+
+    for caps in LINK_RE.captures_iter(s) {
+        let link = caps.get(0).unwrap();
+
+        if link.start() > end {
+            let text = emit_cow(span, &s[end..link.start()]);
+            nodes.push(quote_spanned! { span => ::docbot::HelpNode::Text(#text) });
+        }
+
+        let cmd = emit_cow(span, &caps[1]);
+        nodes.push(quote_spanned! { span => ::docbot::HelpNode::Link(#cmd) });
+
+        end = link.end();
+    }
 
-    let args = docs.args.iter().map(|(name, required, desc)| {
-        let name = Literal::string(&name);
-        let required = emit_bool(*required);
-        let desc = Literal::string(&desc);
+    if end < s.len() {
+        let text = emit_cow(span, &s[end..]);
+        nodes.push(quote_spanned! { span => ::docbot::HelpNode::Text(#text) });
+    }
+
+    quote_spanned! { span => ::docbot::HelpText(::std::borrow::Cow::Borrowed(&[#(#nodes),*])) }
+}
+
+fn emit_opt_help_text(span: Span, s: Option<&str>) -> TokenStream {
+    s.map_or_else(
+        || quote_spanned! { span => None },
+        |s| {
+            let s = emit_help_text(span, s);
+            quote_spanned! { span => Some(#s) }
+        },
+    )
+}
+
+fn emit_usage(docs: &CommandDocs, opts: &VariantOpts) -> TokenStream {
+    let CommandDocs { span, usage, .. } = docs;
+    let VariantOpts {
+        hidden,
+        deprecated,
+        requires,
+    } = opts;
+    let ids = usage.ids.iter().map(|i| emit_cow(*span, i));
+    let args = usage.args().into_iter().map(|arg| {
+        let name = emit_cow(*span, &arg.name());
+        let required = emit_bool(arg.is_required());
+        let rest = emit_bool(matches!(
+            arg,
+            UsageArg::RestRequired(..) | UsageArg::RestOptional(..)
+        ));
+        let option = emit_option(*span, arg);
 
-        quote_spanned! { docs.span =>
-            ::docbot::ArgumentDesc {
+        quote_spanned! { *span =>
+            ::docbot::ArgumentUsage {
                 name: #name,
                 is_required: #required,
-                desc: #desc,
+                is_rest: #rest,
+                option: #option,
             }
         }
     });
+    let desc = emit_help_text(*span, &usage.desc);
+    let hidden = emit_bool(*hidden);
+    let deprecated = emit_opt_str(*span, deprecated.as_deref());
+    let requires = emit_opt_str(*span, requires.as_deref());
 
-    let examples = docs.examples.as_ref().map_or_else(
-        || quote_spanned! { docs.span => None },
-        |examples| {
-            let examples = Literal::string(&examples);
+    quote_spanned! { *span =>
+        ::docbot::CommandUsage {
+            ids: ::std::borrow::Cow::Borrowed(&[#(#ids),*]),
+            args: ::std::borrow::Cow::Borrowed(&[#(#args),*]),
+            desc: #desc,
+            hidden: #hidden,
+            deprecated: #deprecated,
+            requires: #requires,
+        }
+    }
+}
+
+/// Describe the value accepted by an argument, preferring a declared format
+/// hint over the names or type of its value
+fn emit_value_hint(span: Span, info: &FieldInfo, generic: bool) -> Result<TokenStream> {
+    let FieldInfo { opts, ty, mode, .. } = info;
 
-            quote_spanned! { docs.span => Some(#examples) }
+    let hint = match (mode, &opts.hint) {
+        (FieldMode::Flag(_), Some(_)) => {
+            return Err((
+                anyhow!("flag {:?} cannot have a value hint", info.name),
+                span,
+            ));
         },
-    );
+        (FieldMode::Flag(_), None) => return Ok(quote_spanned! { span => None }),
+        (_, Some(hint)) => {
+            let hint = Literal::string(hint);
+            quote_spanned! { span => ::docbot::ValueHint::Format(#hint) }
+        },
+        // Statics can't refer to the type parameters of a generic command
+        (_, None) if generic => return Ok(quote_spanned! { span => None }),
+        (FieldMode::RestRequired | FieldMode::RestOptional, None) if opts.subcommand => {
+            quote_spanned! { span =>
+                ::docbot::ValueHint::Choice(
+                    <<#ty as ::docbot::Command>::Id as ::docbot::CommandId>::canonical_names(),
+                )
+            }
+        },
+        (_, None) => {
+            let elem = elem_type(span, info);
+
+            quote_spanned! { span => {
+                #[allow(unused_imports)]
+                use ::docbot::__private::{IdArg as _, OtherArg as _};
+
+                (&::docbot::__private::ArgType::<#elem>::new()).value_hint()
+            }}
+        },
+    };
+
+    Ok(quote_spanned! { span => Some(::docbot::ValueHintFn(|| #hint)) })
+}
+
+fn emit_desc(Command { docs, fields }: &Command, generic: bool) -> Result<TokenStream> {
+    let summary = emit_opt_help_text(docs.span, docs.summary.as_deref());
+
+    let args = docs
+        .args
+        .iter()
+        .zip(docs.usage.args())
+        .zip(field_info(docs.span, &docs.usage, fields)?)
+        .map(|(((name, required, desc), arg), info)| {
+            let name = emit_cow(docs.span, name);
+            let required = emit_bool(*required);
+            let option = emit_option(docs.span, arg);
+            let desc = emit_help_text(docs.span, desc);
+            let value = emit_value_hint(docs.span, &info, generic)?;
+
+            Ok(quote_spanned! { docs.span =>
+                ::docbot::ArgumentDesc {
+                    name: #name,
+                    is_required: #required,
+                    option: #option,
+                    desc: #desc,
+                    value: #value,
+                }
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let examples = docs.examples.iter().map(|e| Literal::string(e));
+
+    let sections = docs.sections.iter().map(|section| match section {
+        DocSection::Text(name, text) => {
+            let name = Literal::string(name);
+            let text = emit_help_text(docs.span, text);
 
-    quote_spanned! { docs.span =>
+            quote_spanned! { docs.span => ::docbot::DocSection::Text(#name, #text) }
+        },
+        DocSection::SeeAlso(refs) => {
+            let refs = refs.iter().map(|r| Literal::string(r));
+
+            quote_spanned! { docs.span => ::docbot::DocSection::SeeAlso(&[#(#refs),*]) }
+        },
+    });
+
+    Ok(quote_spanned! { docs.span =>
         ::docbot::CommandDesc {
             summary: #summary,
-            args: &[#(#args),*],
-            examples: #examples,
+            args: ::std::borrow::Cow::Borrowed(&[#(#args),*]),
+            examples: &[#(#examples),*],
+            sections: &[#(#sections),*],
+        }
+    })
+}
+
+/// Find the type of the subcommand field of a command, if it has one
+fn subcommand_type<'a>(
+    span: Span,
+    Command { fields, .. }: &Command<'a>,
+) -> Result<Option<&'a Type>>
+{
+    for field in *fields {
+        if attrs::parse_field(&field.attrs, span)?.subcommand {
+            return Ok(Some(&field.ty));
         }
     }
+
+    Ok(None)
 }
 
-pub fn emit(input: &InputData) -> Result<HelpParts> {
+/// Emit the body of `help_path`, which resolves the first name in the path
+/// and hands the rest to the matching subcommand
+fn emit_help_path(input: &InputData) -> Result<TokenStream> {
+    let span = input.span;
+
+    let arms: Vec<_> = match input.commands {
+        Commands::Struct(_, ref cmd) => subcommand_type(span, cmd)?
+            .map(|ty| (quote_spanned! { span => Self::Id }, ty))
+            .into_iter()
+            .collect(),
+        Commands::Enum(_, ref vars) => vars
+            .iter()
+            .filter_map(|var| {
+                subcommand_type(var.span, &var.command)
+                    .map(|ty| {
+                        let ident = var.ident;
+                        ty.map(|ty| (quote_spanned! { var.span => Self::Id::#ident }, ty))
+                    })
+                    .transpose()
+            })
+            .collect::<Result<_>>()?,
+    };
+
+    let arms = arms.into_iter().map(|(pat, ty)| {
+        quote_spanned! { span =>
+            #pat => <#ty as ::docbot::Help>::help_path_in(__rest, __locale).map_err(|e| {
+                ::docbot::HelpPathError::Subcommand(
+                    ::docbot::CommandId::to_str(&__id),
+                    ::std::boxed::Box::new(e),
+                )
+            })
+        }
+    });
+
+    Ok(quote_spanned! { span =>
+        if __path.is_empty() {
+            return Ok(Self::help_in(None, __locale));
+        }
+
+        let (__id, __len): (Self::Id, usize) =
+            ::docbot::__private::parse_id(__path).map_err(::docbot::HelpPathError::BadId)?;
+        let __rest = &__path[__len..];
+
+        if __rest.is_empty() {
+            return Ok(Self::help_in(Some(__id), __locale));
+        }
+
+        #[allow(unreachable_patterns, clippy::match_single_binding)]
+        match __id {
+            #(#arms,)*
+            _ => Err(::docbot::HelpPathError::NoSubcommands(
+                ::docbot::CommandId::to_str(&__id),
+                __rest[0].as_ref().into(),
+            )),
+        }
+    })
+}
+
+/// List every locale with translated docs anywhere in the input
+fn locales(input: &InputData) -> Vec<String> {
+    let mut ret: Vec<_> = match input.commands {
+        Commands::Struct(ref docs, _) => docs.locales.iter().map(|(l, _)| l.clone()).collect(),
+        Commands::Enum(ref docs, ref vars) => docs
+            .locales
+            .iter()
+            .map(|(l, _)| l.clone())
+            .chain(
+                vars.iter()
+                    .flat_map(|v| v.command.docs.locales.iter().map(|(l, _)| l.clone())),
+            )
+            .collect(),
+    };
+
+    ret.sort();
+    ret.dedup();
+    ret
+}
+
+/// Get a command with its docs as written for the given locale, if any
+fn localize<'a>(cmd: &Command<'a>, locale: Option<&str>) -> Command<'a> {
+    Command {
+        docs: locale.map_or_else(|| Rc::clone(&cmd.docs), |l| Rc::new(cmd.docs.localized(l))),
+        fields: cmd.fields,
+    }
+}
+
+/// Emit the help topics of the input as written for the given locale, or the
+/// original docs if `locale` is `None`, as an expression which looks up
+/// `__topic`
+fn emit_topics(input: &InputData, locale: Option<&str>) -> Result<TokenStream> {
+    let generic = !input.generics.params.is_empty();
     let topic_arms;
     let general_help;
 
     match input.commands {
-        Commands::Struct(ref docs, _) => {
-            let usage = emit_usage(docs);
-            let desc = emit_desc(docs);
+        Commands::Struct(_, ref cmd) => {
+            let cmd = localize(cmd, locale);
+            let usage = emit_usage(&cmd.docs, &VariantOpts::default());
+            let desc = emit_desc(&cmd, generic)?;
 
-            general_help = quote_spanned! { docs.span =>
+            general_help = quote_spanned! { cmd.docs.span =>
                 ::docbot::HelpTopic::Command(#usage, #desc)
             };
 
-            topic_arms = vec![quote_spanned! { docs.span => Some(Self::Id) => &__GENERAL }];
+            topic_arms = vec![quote_spanned! { cmd.docs.span => Some(Self::Id) => &__GENERAL }];
         },
         Commands::Enum(ref docs, ref vars) => {
-            let summary = docs.summary.as_ref().map_or_else(
-                || quote_spanned! { docs.span => None },
-                |summary| {
-                    let summary = Literal::string(&summary);
-
-                    quote_spanned! { docs.span => Some(#summary) }
-                },
+            let summary = emit_opt_help_text(
+                docs.span,
+                locale.map_or(docs.summary.as_deref(), |l| docs.localized(l)),
             );
+            let vars: Vec<_> = vars
+                .iter()
+                .map(|v| (v, localize(&v.command, locale)))
+                .collect();
 
-            let commands = vars.iter().map(
-                |CommandVariant {
-                     command: Command { docs, .. },
-                     ..
-                 }| emit_usage(docs),
-            );
+            let commands = vars
+                .iter()
+                .map(|(CommandVariant { opts, .. }, cmd)| emit_usage(&cmd.docs, opts));
 
             general_help = quote_spanned! { docs.span =>
-                ::docbot::HelpTopic::CommandSet(#summary, &[#(#commands),*])
+                ::docbot::HelpTopic::CommandSet(
+                    #summary,
+                    ::std::borrow::Cow::Borrowed(&[#(#commands),*]),
+                )
             };
 
             topic_arms = vars
                 .iter()
                 .map(
-                    |CommandVariant {
-                         span,
-                         ident,
-                         command: Command { docs, .. },
-                         ..
-                     }| {
-                        let usage = emit_usage(docs);
-                        let desc = emit_desc(docs);
-
-                        quote_spanned! { *span =>
+                    |(
+                        CommandVariant {
+                            span, ident, opts, ..
+                        },
+                        command,
+                    )| {
+                        let usage = emit_usage(&command.docs, opts);
+                        let desc = emit_desc(command, generic)?;
+
+                        Ok(quote_spanned! { *span =>
                             Some(Self::Id::#ident) => {
                                 static __TOPIC: ::docbot::HelpTopic = ::docbot::HelpTopic::Command(
                                     #usage,
@@ -181,13 +423,39 @@ pub fn emit(input: &InputData) -> Result<HelpParts> {
 
                                 &__TOPIC
                             }
-                        }
+                        })
                     },
                 )
-                .collect();
+                .collect::<Result<_>>()?;
         },
     }
 
+    Ok(quote_spanned! { input.span => {
+        static __GENERAL: ::docbot::HelpTopic = #general_help;
+
+        match __topic {
+            #(#topic_arms,)*
+            None => &__GENERAL,
+        }
+    }})
+}
+
+pub fn emit(input: &InputData) -> Result<HelpParts> {
+    let topics = emit_topics(input, None)?;
+    let locales = locales(input);
+    let locale_arms = locales
+        .iter()
+        .enumerate()
+        .map(|(i, locale)| {
+            let topics = emit_topics(input, Some(locale))?;
+
+            Ok(quote_spanned! { input.span => Some(#i) => #topics })
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let locales = locales.iter().map(|l| Literal::string(l));
+
+    let help_path = emit_help_path(input)?;
+
     // Quote variables
     let name = input.ty;
     let (impl_vars, ty_vars, where_clause) = input.generics.split_for_impl();
@@ -195,14 +463,25 @@ pub fn emit(input: &InputData) -> Result<HelpParts> {
     let items = if true {
         Some(quote_spanned! { input.span =>
             impl #impl_vars ::docbot::Help for #name #ty_vars #where_clause {
-                fn help(__topic: Option<Self::Id>) -> &'static ::docbot::HelpTopic {
-                    static __GENERAL: ::docbot::HelpTopic = #general_help;
+                fn help(__topic: Option<Self::Id>) -> &'static ::docbot::HelpTopic #topics
 
-                    match __topic {
-                        #(#topic_arms,)*
-                        None => &__GENERAL,
+                fn help_locales() -> &'static [&'static str] { &[#(#locales),*] }
+
+                fn help_in(__topic: Option<Self::Id>, __locale: &str) -> &'static ::docbot::HelpTopic {
+                    #[allow(clippy::match_single_binding)]
+                    match ::docbot::match_locale(__locale, Self::help_locales()) {
+                        #(#locale_arms,)*
+                        _ => Self::help(__topic),
                     }
                 }
+
+                fn help_path_in<S: AsRef<str>>(
+                    __path: &[S],
+                    __locale: &str,
+                ) -> ::std::result::Result<&'static ::docbot::HelpTopic, ::docbot::HelpPathError>
+                {
+                    #help_path
+                }
             }
         })
     } else {
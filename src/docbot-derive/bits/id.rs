@@ -12,14 +12,19 @@ pub struct IdParts {
 
 fn parse_no_match(span: Span, s: impl ToTokens) -> impl ToTokens {
     quote_spanned! { span =>
-        Err(::docbot::IdParseError::NoMatch(#s.into(), <Self as ::docbot::CommandId>::names()))
+        Err(::docbot::__private::no_match::<Self>(#s))
     }
 }
 
 fn parse_ambiguous(span: Span, s: impl ToTokens, values: Vec<&str>) -> impl ToTokens {
     let expected = values.into_iter().map(|v| Literal::string(v));
 
-    quote_spanned! { span => Err(::docbot::IdParseError::Ambiguous(&[#(#expected),*], #s.into())) }
+    quote_spanned! { span =>
+        Err(::docbot::IdParseError::Ambiguous(
+            vec![#(::std::borrow::Cow::Borrowed(#expected)),*],
+            #s.into(),
+        ))
+    }
 }
 
 fn parse_resolve_ambiguous<'a, 'b, T: Eq + 'b>(values: Vec<&'a (String, T)>) -> Option<&'a T> {
@@ -112,22 +117,24 @@ fn bits<'a>(
     Ok((ty, def, generics, get_fn))
 }
 
-pub fn emit(input: &InputData) -> Result<IdParts> {
-    let (ty, def, generics, get_fn) = bits(input)?;
-
-    let parse_s = Ident::new("__str", input.span);
-    let parse_iter = Ident::new("__iter", input.span);
-
-    let lexer = match input.commands {
+/// Build the lexer matching the (possibly abbreviated) names of each ID
+fn emit_lexer(
+    input: &InputData,
+    ty: &Ident,
+    parse_s: &Ident,
+    parse_iter: &Ident,
+) -> Result<TokenStream>
+{
+    Ok(match input.commands {
         Commands::Struct(_, Command { ref docs, .. }) => {
             Trie::new(docs.usage.ids.iter().map(|i| (i.to_lowercase(), ())))
                 .map_err(|e| (e.context("failed to construct command lexer"), input.span))?
                 .root()
                 .to_lexer(
-                    &parse_iter,
+                    parse_iter,
                     |()| quote_spanned! { input.span => Ok(#ty) },
-                    || parse_no_match(input.span, &parse_s),
-                    |v| parse_ambiguous(input.span, &parse_s, v),
+                    || parse_no_match(input.span, parse_s),
+                    |v| parse_ambiguous(input.span, parse_s, v),
                     parse_resolve_ambiguous,
                 )
         },
@@ -142,16 +149,26 @@ pub fn emit(input: &InputData) -> Result<IdParts> {
         .map_err(|e| (e.context("failed to construct command lexer"), input.span))?
         .root()
         .to_lexer(
-            &parse_iter,
+            parse_iter,
             |i| quote_spanned! { input.span => Ok(#ty::#i) },
-            || parse_no_match(input.span, &parse_s),
-            |v| parse_ambiguous(input.span, &parse_s, v),
+            || parse_no_match(input.span, parse_s),
+            |v| parse_ambiguous(input.span, parse_s, v),
             parse_resolve_ambiguous,
         ),
-    };
+    })
+}
+
+pub fn emit(input: &InputData) -> Result<IdParts> {
+    let (ty, def, generics, get_fn) = bits(input)?;
+
+    let parse_s = Ident::new("__str", input.span);
+    let parse_iter = Ident::new("__iter", input.span);
+    let lexer = emit_lexer(input, &ty, &parse_s, &parse_iter)?;
 
     let to_str_arms;
     let names;
+    let canonical_names: Vec<_>;
+    let name_groups: Vec<_>;
 
     match input.commands {
         Commands::Struct(_, Command { ref docs, .. }) => {
@@ -159,6 +176,8 @@ pub fn emit(input: &InputData) -> Result<IdParts> {
             to_str_arms = vec![quote_spanned! { input.span => Self => #value }];
 
             names = docs.usage.ids.clone();
+            canonical_names = vec![docs.usage.ids[0].clone()];
+            name_groups = vec![quote_spanned! { input.span => &[#(#names),*] }];
         },
         Commands::Enum(_, ref vars) => {
             to_str_arms = vars
@@ -174,6 +193,19 @@ pub fn emit(input: &InputData) -> Result<IdParts> {
                 .flat_map(|v| v.command.docs.usage.ids.iter())
                 .cloned()
                 .collect();
+
+            canonical_names = vars
+                .iter()
+                .map(|v| v.command.docs.usage.ids[0].clone())
+                .collect();
+
+            name_groups = vars
+                .iter()
+                .map(|v| {
+                    let ids = &v.command.docs.usage.ids;
+                    quote_spanned! { input.span => &[#(#ids),*] }
+                })
+                .collect();
         },
     };
 
@@ -208,6 +240,10 @@ pub fn emit(input: &InputData) -> Result<IdParts> {
         impl #impl_vars ::docbot::CommandId for #ty #ty_vars #where_clause {
             fn names() -> &'static [&'static str] { &[#(#names),*] }
 
+            fn canonical_names() -> &'static [&'static str] { &[#(#canonical_names),*] }
+
+            fn name_groups() -> &'static [&'static [&'static str]] { &[#(#name_groups),*] }
+
             fn to_str(&self) -> &'static str {
                 match self {
                     #(#to_str_arms),*
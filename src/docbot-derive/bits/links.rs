@@ -0,0 +1,61 @@
+#[allow(clippy::wildcard_imports)]
+use super::inputs::*;
+use crate::Result;
+use proc_macro2::TokenStream;
+use quote::quote_spanned;
+
+pub struct LinksParts {
+    pub items: TokenStream,
+}
+
+pub fn emit(input: &InputData) -> Result<LinksParts> {
+    if !input.opts.root {
+        return Ok(LinksParts {
+            items: TokenStream::new(),
+        });
+    }
+
+    if !input.generics.params.is_empty() {
+        return Err((
+            anyhow::anyhow!("a generic type cannot be marked as a root command"),
+            input.span,
+        ));
+    }
+
+    // Quote variables
+    let name = input.ty;
+    let module = Ident::new(
+        &format!("__docbot_links_{}", name.to_string().to_lowercase()),
+        input.span,
+    );
+
+    let items = quote_spanned! { input.span =>
+        #[cfg(test)]
+        #[doc(hidden)]
+        mod #module {
+            #[test]
+            fn links() {
+                if let Err(errs) = ::docbot::check_links::<super::#name>() {
+                    for e in &errs {
+                        eprintln!("{}", e);
+                    }
+
+                    panic!("{} invalid link(s) found in help text", errs.len());
+                }
+            }
+
+            #[test]
+            fn references() {
+                if let Err(errs) = ::docbot::check_references::<super::#name>() {
+                    for e in &errs {
+                        eprintln!("{}", e);
+                    }
+
+                    panic!("{} invalid reference(s) found in see also sections", errs.len());
+                }
+            }
+        }
+    };
+
+    Ok(LinksParts { items })
+}
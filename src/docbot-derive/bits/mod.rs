@@ -1,6 +1,11 @@
+pub mod complete;
+pub mod examples;
 pub mod help;
 pub mod id;
+pub mod links;
 pub mod parse;
+pub mod schema;
+pub mod tokens;
 
 pub mod inputs {
     use crate::{attrs, Result};
@@ -10,15 +15,21 @@ pub mod inputs {
     use std::rc::Rc;
     use syn::{spanned::Spanned, Data, DeriveInput};
 
-    pub use crate::docs::{CommandDocs, CommandSetDocs, CommandUsage, RestArg};
+    pub use crate::{
+        docs::{
+            CommandDocs, CommandSetDocs, CommandUsage, DocSection, OptionUsage, RestArg, UsageArg,
+        },
+        opts::{OuterOpts, VariantOpts},
+    };
     pub use proc_macro2::Span;
-    pub use syn::{Fields, Generics, Ident, Variant, Visibility};
+    pub use syn::{Fields, Generics, Ident, Type, Variant, Visibility};
 
     pub struct InputData<'a> {
         pub span: Span,
         pub vis: &'a Visibility,
         pub ty: &'a Ident,
         pub generics: &'a Generics,
+        pub opts: OuterOpts,
 
         pub commands: Commands<'a>,
     }
@@ -37,50 +48,67 @@ pub mod inputs {
         pub span: Span,
         pub ident: &'a Ident,
         pub pat: TokenStream,
+        pub opts: VariantOpts,
         pub command: Command<'a>,
     }
 
     pub fn assemble(input: &DeriveInput) -> Result<InputData> {
-        let commands = match input.data {
+        let (opts, commands) = match input.data {
             Data::Struct(ref s) => {
-                let docs = Rc::new(attrs::parse_outer(&input.attrs, input.span())?);
+                let (opts, docs) = attrs::parse_outer(&input.attrs, input.span())?;
+                let docs = Rc::new(docs);
 
-                Commands::Struct(
-                    Rc::clone(&docs),
-                    Command {
-                        docs,
-                        fields: &s.fields,
-                    },
+                (
+                    opts,
+                    Commands::Struct(
+                        Rc::clone(&docs),
+                        Command {
+                            docs,
+                            fields: &s.fields,
+                        },
+                    ),
                 )
             },
-            Data::Enum(ref e) => Commands::Enum(
-                attrs::parse_outer(&input.attrs, input.span())?,
-                e.variants
-                    .iter()
-                    .map(|v| {
-                        Ok(CommandVariant {
-                            ident: &v.ident,
-                            pat: {
-                                let id = &v.ident;
-                                match v.fields {
-                                    Fields::Named(..) => {
-                                        quote_spanned! { v.span() => Self::#id { .. } }
+            Data::Enum(ref e) => {
+                let (opts, docs) = attrs::parse_outer(&input.attrs, input.span())?;
+
+                (
+                    opts,
+                    Commands::Enum(
+                        docs,
+                        e.variants
+                            .iter()
+                            .map(|v| {
+                                let (opts, docs) = attrs::parse_variant(&v.attrs, v.span())?;
+
+                                Ok(CommandVariant {
+                                    ident: &v.ident,
+                                    pat: {
+                                        let id = &v.ident;
+                                        match v.fields {
+                                            Fields::Named(..) => {
+                                                quote_spanned! { v.span() => Self::#id { .. } }
+                                            },
+                                            Fields::Unnamed(..) => {
+                                                quote_spanned! { v.span() => Self::#id(..) }
+                                            },
+                                            Fields::Unit => {
+                                                quote_spanned! { v.span() => Self::#id }
+                                            },
+                                        }
                                     },
-                                    Fields::Unnamed(..) => {
-                                        quote_spanned! { v.span() => Self::#id(..) }
+                                    opts,
+                                    command: Command {
+                                        docs: Rc::new(docs),
+                                        fields: &v.fields,
                                     },
-                                    Fields::Unit => quote_spanned! { v.span() => Self::#id },
-                                }
-                            },
-                            command: Command {
-                                docs: Rc::new(attrs::parse_variant(&v.attrs, v.span())?),
-                                fields: &v.fields,
-                            },
-                            span: v.span(),
-                        })
-                    })
-                    .collect::<Result<_>>()?,
-            ),
+                                    span: v.span(),
+                                })
+                            })
+                            .collect::<Result<_>>()?,
+                    ),
+                )
+            },
             Data::Union(_) => {
                 return Err((anyhow!("cannot derive Docbot on a union."), input.span()))
             },
@@ -91,6 +119,7 @@ pub mod inputs {
             vis: &input.vis,
             ty: &input.ident,
             generics: &input.generics,
+            opts,
 
             commands,
         })
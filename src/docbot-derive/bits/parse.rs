@@ -1,8 +1,13 @@
 #[allow(clippy::wildcard_imports)]
-use super::{id::IdParts, inputs::*};
+use super::{
+    complete::{elem_type, CompleteParts},
+    id::IdParts,
+    inputs::*,
+    tokens::TokensParts,
+};
 use crate::{attrs, opts::FieldOpts, Result};
 use anyhow::anyhow;
-use proc_macro2::TokenStream;
+use proc_macro2::{Literal, TokenStream};
 use quote::quote_spanned;
 use std::collections::HashMap;
 
@@ -10,38 +15,117 @@ pub struct ParseParts {
     pub items: TokenStream,
 }
 
-enum FieldMode {
+pub(super) enum FieldMode {
     Required,
     Optional,
     RestRequired,
     RestOptional,
+    Flag(usize),
+    Value(usize),
 }
 
-struct FieldInfo<'a> {
-    opts: FieldOpts,
-    name: &'a str,
-    mode: FieldMode,
+/// How a generated parse function converts its arguments
+struct Conv {
+    /// The variable holding a reference to the context
+    ctx: Ident,
+    /// The type of the context
+    ctx_ty: TokenStream,
+    /// Whether subcommands should be parsed with the context
+    with_ctx: bool,
 }
 
-fn collect_rest(span: Span, opts: &FieldOpts, name: &str, iter: &Ident, id: &Ident) -> TokenStream {
+/// Emit an expression converting a string into a single value of an argument,
+/// followed by any checks declared for the field
+fn convert_arg(span: Span, conv: &Conv, info: &FieldInfo, s: TokenStream) -> TokenStream {
+    let Conv { ctx, ctx_ty, .. } = conv;
+    let FieldInfo { opts, .. } = info;
+    let elem = elem_type(span, info);
+
+    let convert = if let Some(ref path) = opts.parse_with {
+        quote_spanned! { span =>
+            #path(#s).map_err(::std::convert::Into::<::docbot::Anyhow>::into)
+        }
+    } else {
+        quote_spanned! { span =>
+            (&::docbot::__private::ArgParse::<#elem, #ctx_ty>::new()).parse_arg(#ctx, #s)
+        }
+    };
+
+    let range = opts.range.as_ref().map(|(range, src)| {
+        let src = Literal::string(src);
+
+        quote_spanned! { span =>
+            if !::std::ops::RangeBounds::contains(&(#range), &__val) {
+                return Err(::docbot::Anyhow::from(::docbot::RangeError(#src)));
+            }
+        }
+    });
+
+    let validate = opts.validate.as_ref().map(|path| {
+        quote_spanned! { span =>
+            #path(&__val).map_err(::std::convert::Into::<::docbot::Anyhow>::into)?;
+        }
+    });
+
+    if range.is_none() && validate.is_none() {
+        return convert;
+    }
+
+    quote_spanned! { span =>
+        #convert.and_then(|__val: #elem| {
+            #range
+            #validate
+            Ok(__val)
+        })
+    }
+}
+
+pub(super) struct FieldInfo<'a> {
+    pub opts: FieldOpts,
+    pub name: String,
+    pub field: String,
+    pub ty: &'a Type,
+    pub mode: FieldMode,
+}
+
+fn collect_rest(
+    span: Span,
+    conv: &Conv,
+    info: &FieldInfo,
+    iter: &Ident,
+    id: &Ident,
+) -> TokenStream
+{
+    let FieldInfo { opts, name, .. } = info;
+
     if opts.subcommand {
+        let parse = if conv.with_ctx {
+            let ctx = &conv.ctx;
+            quote_spanned! { span => ::docbot::Command::parse_with(#ctx, #iter) }
+        } else {
+            quote_spanned! { span => ::docbot::Command::parse(#iter) }
+        };
+
         quote_spanned! { span =>
-            ::docbot::Command::parse(#iter).map_err(|e| ::docbot::CommandParseError::Subcommand(
+            #parse.map_err(|e| ::docbot::CommandParseError::Subcommand(
                 ::docbot::CommandId::to_str(&#id),
                 ::std::boxed::Box::new(e),
             ))
         }
     } else {
+        let convert = convert_arg(span, conv, info, quote_spanned! { span => s.as_ref() });
+
         quote_spanned! { span =>
             #iter
                 .map(|s| {
-                    s.as_ref().parse().map_err(|e| {
+                    #convert.map_err(|e| {
                         ::docbot::CommandParseError::BadConvert(
                             ::docbot::ArgumentName {
-                                cmd: ::docbot::CommandId::to_str(&#id),
-                                arg: #name,
+                                cmd: ::docbot::CommandId::to_str(&#id).into(),
+                                arg: #name.into(),
                             },
-                            ::docbot::Anyhow::from(e)
+                            ::docbot::Spanned::span(&s),
+                            e,
                         )
                     })
                 })
@@ -50,33 +134,87 @@ fn collect_rest(span: Span, opts: &FieldOpts, name: &str, iter: &Ident, id: &Ide
     }
 }
 
-fn field_info<'a>(
+/// Make sure the options given for a field make sense for its argument
+fn check_field(span: Span, info: FieldInfo) -> Result<FieldInfo> {
+    let FieldInfo {
+        opts, name, mode, ..
+    } = &info;
+    let converts = opts.parse_with.is_some() || opts.validate.is_some() || opts.range.is_some();
+
+    if opts.default.is_some() && !matches!(mode, FieldMode::Optional | FieldMode::Value(_)) {
+        return Err((
+            anyhow!(
+                "argument {:?} cannot have a default, only optional arguments and options with \
+                 values can",
+                name
+            ),
+            span,
+        ));
+    }
+
+    if (converts || opts.default.is_some())
+        && (opts.subcommand || matches!(mode, FieldMode::Flag(_)))
+    {
+        return Err((
+            anyhow!(
+                "argument {:?} does not take a value, so it cannot have a parser, default, or \
+                 validator",
+                name
+            ),
+            span,
+        ));
+    }
+
+    Ok(info)
+}
+
+pub(super) fn field_info<'a>(
     span: Span,
-    usage: &'a CommandUsage,
+    usage: &CommandUsage,
     fields: &'a Fields,
 ) -> Result<Vec<FieldInfo<'a>>>
 {
-    let args = usage
-        .required
-        .iter()
-        .map(|n| (FieldMode::Required, n))
-        .chain(usage.optional.iter().map(|n| (FieldMode::Optional, n)))
-        .chain(match usage.rest {
-            RestArg::None => None,
-            RestArg::Optional(ref n) => Some((FieldMode::RestOptional, n)),
-            RestArg::Required(ref n) => Some((FieldMode::RestRequired, n)),
-        });
+    let mut num_opts = 0;
+    let args = usage.args().into_iter().map(|arg| {
+        let mode = match arg {
+            UsageArg::Required(..) => FieldMode::Required,
+            UsageArg::Optional(..) => FieldMode::Optional,
+            UsageArg::RestRequired(..) => FieldMode::RestRequired,
+            UsageArg::RestOptional(..) => FieldMode::RestOptional,
+            UsageArg::Option(opt) => {
+                num_opts += 1;
+
+                if opt.value.is_some() {
+                    FieldMode::Value(num_opts - 1)
+                } else {
+                    FieldMode::Flag(num_opts - 1)
+                }
+            },
+        };
+
+        let field = match arg {
+            UsageArg::Option(opt) => opt.field_name(),
+            arg => arg.name(),
+        };
+
+        (mode, arg.name(), field)
+    });
 
     let args = match fields {
         Fields::Unit => Ok(Vec::new()),
         Fields::Unnamed(u) => args
             .zip(u.unnamed.iter())
-            .map(|((mode, name), field)| {
-                Ok(FieldInfo {
-                    opts: attrs::parse_field(&field.attrs, span)?,
-                    mode,
-                    name,
-                })
+            .map(|((mode, name, field_name), field)| {
+                check_field(
+                    span,
+                    FieldInfo {
+                        opts: attrs::parse_field(&field.attrs, span)?,
+                        mode,
+                        name,
+                        field: field_name,
+                        ty: &field.ty,
+                    },
+                )
             })
             .collect(),
         Fields::Named(n) => {
@@ -86,17 +224,21 @@ fn field_info<'a>(
                 .map(|f| (format!("{}", f.ident.as_ref().unwrap()), f))
                 .collect();
 
-            args.map(|(mode, name)| {
-                Ok(FieldInfo {
-                    opts: attrs::parse_field(
-                        &map.remove(name)
-                            .ok_or_else(|| (anyhow!("could not locate field {:?}", name), span))?
-                            .attrs,
-                        span,
-                    )?,
-                    mode,
-                    name,
-                })
+            args.map(|(mode, name, field)| {
+                let f = map
+                    .remove(&field)
+                    .ok_or_else(|| (anyhow!("could not locate field {:?}", field), span))?;
+
+                check_field(
+                    span,
+                    FieldInfo {
+                        opts: attrs::parse_field(&f.attrs, span)?,
+                        mode,
+                        name,
+                        field,
+                        ty: &f.ty,
+                    },
+                )
             })
             .collect::<Result<_>>()
         },
@@ -109,9 +251,263 @@ fn field_info<'a>(
     Ok(args)
 }
 
+fn option_var(span: Span, idx: usize) -> Ident { Ident::new(&format!("__opt_{}", idx), span) }
+
+/// Emit the declaration of the variable an option is stored in
+fn option_decl(span: Span, i: usize, opt: &OptionUsage) -> TokenStream {
+    let var = option_var(span, i);
+
+    if opt.value.is_some() {
+        quote_spanned! { span =>
+            let mut #var: ::std::option::Option<(
+                ::std::string::String,
+                ::std::option::Option<::std::ops::Range<usize>>,
+            )> = None;
+        }
+    } else {
+        quote_spanned! { span => let mut #var = false; }
+    }
+}
+
+/// Emit the match arm storing an option found by `scan_options`
+fn option_arm(span: Span, i: usize, opt: &OptionUsage, iter: &Ident, id: &Ident) -> TokenStream {
+    let var = option_var(span, i);
+    let name = Literal::string(&opt.name());
+    let pats = opt
+        .short
+        .map(|s| format!("-{}", s))
+        .into_iter()
+        .chain(opt.long.as_ref().map(|l| format!("--{}", l)))
+        .map(|p| Literal::string(&p));
+    let arg_name = quote_spanned! { span =>
+        ::docbot::ArgumentName {
+            cmd: ::docbot::CommandId::to_str(&#id).into(),
+            arg: #name.into(),
+        }
+    };
+
+    if opt.value.is_some() {
+        quote_spanned! { span =>
+            #(#pats)|* => {
+                if #var.is_some() {
+                    return Err(::docbot::CommandParseError::DuplicateOption(
+                        #arg_name,
+                        ::docbot::Spanned::span(&__tok),
+                    ));
+                }
+
+                let __val = match __inline {
+                    Some(__val) => (__val.into(), ::docbot::Spanned::span(&__tok)),
+                    None => match #iter.next() {
+                        Some(__val) => (__val.as_ref().into(), ::docbot::Spanned::span(&__val)),
+                        None => return Err(::docbot::CommandParseError::MissingOptionValue(
+                            #arg_name,
+                            ::docbot::Spanned::span(&__tok),
+                        )),
+                    },
+                };
+
+                #var = Some(__val);
+            }
+        }
+    } else {
+        quote_spanned! { span =>
+            #(#pats)|* if __inline.is_none() => {
+                if #var {
+                    return Err(::docbot::CommandParseError::DuplicateOption(
+                        #arg_name,
+                        ::docbot::Spanned::span(&__tok),
+                    ));
+                }
+
+                #var = true;
+            }
+        }
+    }
+}
+
+/// Emit a loop to pull all options out of the argument iterator, after which
+/// the iterator is rebound to contain only positional arguments
+fn scan_options(
+    span: Span,
+    usage: &CommandUsage,
+    info: &[FieldInfo],
+    iter: &Ident,
+    id: &Ident,
+) -> TokenStream
+{
+    let decls = usage
+        .options
+        .iter()
+        .enumerate()
+        .map(|(i, (_, opt))| option_decl(span, i, opt));
+
+    let arms = usage
+        .options
+        .iter()
+        .enumerate()
+        .map(|(i, (_, opt))| option_arm(span, i, opt, iter, id));
+
+    // Options after a subcommand belong to the subcommand, so stop scanning
+    // once its ID has been seen
+    let subcommand_check = info
+        .iter()
+        .find(|i| {
+            i.opts.subcommand && matches!(i.mode, FieldMode::RestRequired | FieldMode::RestOptional)
+        })
+        .map(|_| {
+            let limit = usage.required.len() + usage.optional.len();
+
+            quote_spanned! { span =>
+                if __pos.len() > #limit {
+                    __only_pos = true;
+                }
+            }
+        });
+
+    quote_spanned! { span =>
+        #(#decls)*
+        let mut __pos = ::std::vec::Vec::new();
+        let mut __only_pos = false;
+
+        while let Some(__tok) = #iter.next() {
+            if __only_pos || !::docbot::__private::is_option(__tok.as_ref()) {
+                __pos.push(__tok);
+                #subcommand_check
+                continue;
+            }
+
+            let __str = __tok.as_ref();
+
+            if __str == "--" {
+                __only_pos = true;
+                continue;
+            }
+
+            let (__name, __inline) = match __str.find('=') {
+                Some(__i) if __str.starts_with("--") => (&__str[..__i], Some(&__str[__i + 1..])),
+                _ => (__str, None),
+            };
+
+            match __name {
+                #(#arms,)*
+                _ => return Err(::docbot::CommandParseError::UnknownOption(
+                    ::docbot::CommandId::to_str(&#id).into(),
+                    __str.into(),
+                    ::docbot::Spanned::span(&__tok),
+                )),
+            }
+        }
+
+        #[allow(unused_mut)]
+        let mut #iter = __pos.into_iter();
+    }
+}
+
+/// Emit an expression producing the value of one field of a command
+fn field_arg(span: Span, conv: &Conv, info: &FieldInfo, iter: &Ident, id: &Ident) -> TokenStream {
+    let FieldInfo {
+        opts, name, mode, ..
+    } = info;
+    let name_lit = Literal::string(&name);
+    let arg_name = quote_spanned! { span =>
+        ::docbot::ArgumentName {
+            cmd: ::docbot::CommandId::to_str(&#id).into(),
+            arg: #name_lit.into(),
+        }
+    };
+
+    let default = opts
+        .default
+        .as_ref()
+        .map(|d| quote_spanned! { span => .unwrap_or_else(|| #d) });
+
+    match *mode {
+        FieldMode::Required => {
+            let convert = convert_arg(span, conv, info, quote_spanned! { span => __arg.as_ref() });
+
+            quote_spanned! { span =>
+                {
+                    #[allow(clippy::or_fun_call)]
+                    let __arg = #iter
+                        .next()
+                        .ok_or(::docbot::CommandParseError::MissingRequired(#arg_name))?;
+
+                    #convert.map_err(|e| ::docbot::CommandParseError::BadConvert(
+                        #arg_name,
+                        ::docbot::Spanned::span(&__arg),
+                        e,
+                    ))?
+                }
+            }
+        },
+        FieldMode::Optional => {
+            let convert = convert_arg(span, conv, info, quote_spanned! { span => s.as_ref() });
+
+            quote_spanned! { span =>
+                #iter
+                    .next()
+                    .map(|s| {
+                        #convert.map_err(|e| {
+                            ::docbot::CommandParseError::BadConvert(
+                                #arg_name,
+                                ::docbot::Spanned::span(&s),
+                                e,
+                            )
+                        })
+                    })
+                    .transpose()?
+                    #default
+            }
+        },
+        FieldMode::RestRequired => {
+            let peekable = Ident::new("__peek", span);
+            let collected = collect_rest(span, conv, info, &peekable, id);
+
+            quote_spanned! { span =>
+                {
+                    let mut #peekable = #iter.peekable();
+
+                    if let Some(..) = #peekable.peek() {
+                        #collected
+                    } else {
+                        Err(::docbot::CommandParseError::MissingRequired(#arg_name))
+                    }
+                }?
+            }
+        },
+        FieldMode::RestOptional => {
+            let collected = collect_rest(span, conv, info, iter, id);
+
+            quote_spanned! { span => #collected? }
+        },
+        FieldMode::Flag(idx) => {
+            let var = option_var(span, idx);
+
+            quote_spanned! { span => #var }
+        },
+        FieldMode::Value(idx) => {
+            let var = option_var(span, idx);
+            let convert = convert_arg(span, conv, info, quote_spanned! { span => &s });
+
+            quote_spanned! { span =>
+                #var
+                    .map(|(s, span)| {
+                        #convert.map_err(|e| {
+                            ::docbot::CommandParseError::BadConvert(#arg_name, span, e)
+                        })
+                    })
+                    .transpose()?
+                    #default
+            }
+        },
+    }
+}
+
 fn ctor_fields(
     span: Span,
     Command { docs, fields }: &Command,
+    conv: &Conv,
     path: TokenStream,
     iter: &Ident,
     id: &Ident,
@@ -119,72 +515,15 @@ fn ctor_fields(
 {
     let info = field_info(span, &docs.usage, fields)?;
 
-    let args = info.into_iter().map(|FieldInfo { opts, name, mode }| {
-        (
-            name,
-            match mode {
-                FieldMode::Required => quote_spanned! { span =>
-                    #[allow(clippy::or_fun_call)]
-                    #iter
-                        .next()
-                        .ok_or(::docbot::CommandParseError::MissingRequired(
-                            ::docbot::ArgumentName {
-                                cmd: ::docbot::CommandId::to_str(&#id),
-                                arg: #name
-                            }
-                        ))?
-                        .as_ref()
-                        .parse()
-                        .map_err(|e| ::docbot::CommandParseError::BadConvert(
-                            ::docbot::ArgumentName {
-                                cmd: ::docbot::CommandId::to_str(&#id),
-                                arg: #name,
-                            },
-                            ::docbot::Anyhow::from(e),
-                        ))?
-                },
-                FieldMode::Optional => quote_spanned! { span =>
-                    #iter
-                        .next()
-                        .map(|s| s.as_ref().parse())
-                        .transpose()
-                        .map_err(|e| ::docbot::CommandParseError::BadConvert(
-                            ::docbot::ArgumentName {
-                                cmd: ::docbot::CommandId::to_str(&#id),
-                                arg: #name,
-                            },
-                            ::docbot::Anyhow::from(e),
-                        ))?
-                },
-                FieldMode::RestRequired => {
-                    let peekable = Ident::new("__peek", span);
-                    let collected = collect_rest(span, &opts, name, &peekable, id);
-
-                    quote_spanned! { span =>
-                        {
-                            let mut #peekable = #iter.peekable();
-
-                            if let Some(..) = #peekable.peek() {
-                                #collected
-                            } else {
-                                Err(::docbot::CommandParseError::MissingRequired(
-                                    ::docbot::ArgumentName {
-                                        cmd: ::docbot::CommandId::to_str(&#id),
-                                        arg: #name,
-                                    }
-                                ))
-                            }
-                        }?
-                    }
-                },
-                FieldMode::RestOptional => {
-                    let collected = collect_rest(span, &opts, name, iter, id);
+    let options = if docs.usage.options.is_empty() {
+        None
+    } else {
+        Some(scan_options(span, &docs.usage, &info, iter, id))
+    };
 
-                    quote_spanned! { span => #collected? }
-                },
-            },
-        )
-    });
+    let args = info
+        .iter()
+        .map(|info| (&info.field, field_arg(span, conv, info, iter, id)));
 
     let ret = match fields {
         Fields::Unit => path,
@@ -196,7 +535,7 @@ fn ctor_fields(
         Fields::Named(..) => {
             let args = args
                 .map(|(name, arg)| {
-                    let id: Ident = syn::parse_str(name).map_err(|e| (e.into(), span))?;
+                    let id: Ident = syn::parse_str(&name).map_err(|e| (e.into(), span))?;
                     Ok(quote_spanned! { span => #id: #arg })
                 })
                 .collect::<Result<Vec<_>>>()?;
@@ -205,12 +544,13 @@ fn ctor_fields(
         },
     };
 
-    Ok(if let RestArg::None = docs.usage.rest {
+    let ret = if let RestArg::None = docs.usage.rest {
         let check = quote_spanned! { span =>
             if let Some(__trail) = #iter.next() {
                 return Err(::docbot::CommandParseError::Trailing(
-                    ::docbot::CommandId::to_str(&#id),
+                    ::docbot::CommandId::to_str(&#id).into(),
                     __trail.as_ref().into(),
+                    ::docbot::Spanned::span(&__trail),
                 ));
             }
         };
@@ -224,10 +564,59 @@ fn ctor_fields(
         }
     } else {
         ret
+    };
+
+    Ok(if let Some(options) = options {
+        quote_spanned! { span =>
+            {
+                #options
+                #ret
+            }
+        }
+    } else {
+        ret
     })
 }
 
-pub fn emit(input: &InputData, id_parts: &IdParts) -> Result<ParseParts> {
+/// Emit a match arm returning the deprecation message of a command, or of its
+/// subcommand if it has one
+fn deprecation_arm(
+    span: Span,
+    path: TokenStream,
+    fields: &Fields,
+    deprecated: Option<&str>,
+) -> Result<TokenStream>
+{
+    if let Some(msg) = deprecated {
+        let msg = Literal::string(msg);
+
+        return Ok(quote_spanned! { span => #path { .. } => Some(#msg) });
+    }
+
+    let sub = Ident::new("__sub", span);
+    let mut subcommand = None;
+
+    for (i, field) in fields.iter().enumerate() {
+        if attrs::parse_field(&field.attrs, span)?.subcommand {
+            subcommand = Some(match field.ident {
+                Some(ref id) => quote_spanned! { span => #path { #id: #sub, .. } },
+                None => {
+                    let idx = Literal::usize_unsuffixed(i);
+                    quote_spanned! { span => #path { #idx: #sub, .. } }
+                },
+            });
+        }
+    }
+
+    Ok(match subcommand {
+        Some(pat) => quote_spanned! { span => #pat => ::docbot::Command::deprecation(#sub) },
+        None => quote_spanned! { span => #path { .. } => None },
+    })
+}
+
+/// Emit the body of a parse function, converting arguments with the given
+/// context
+fn parse_body(input: &InputData, id_parts: &IdParts, conv: &Conv) -> Result<TokenStream> {
     let iter = Ident::new("__iter", input.span);
     let id = Ident::new("__id", input.span);
     let id_ty = &id_parts.ty;
@@ -237,6 +626,7 @@ pub fn emit(input: &InputData, id_parts: &IdParts) -> Result<ParseParts> {
             let ctor = ctor_fields(
                 input.span,
                 cmd,
+                conv,
                 quote_spanned! { input.span => Self },
                 &iter,
                 &id,
@@ -256,6 +646,7 @@ pub fn emit(input: &InputData, id_parts: &IdParts) -> Result<ParseParts> {
                     let ctor = ctor_fields(
                         *span,
                         command,
+                        conv,
                         quote_spanned! { *span => Self::#ident },
                         &iter,
                         &id,
@@ -267,32 +658,134 @@ pub fn emit(input: &InputData, id_parts: &IdParts) -> Result<ParseParts> {
             .collect::<Result<_>>()?,
     };
 
+    Ok(quote_spanned! { input.span =>
+        #[allow(unused_imports)]
+        use ::docbot::__private::{ContextArg as _, PlainArg as _};
+
+        let __toks: ::std::vec::Vec<S> = #iter.into_iter().collect();
+
+        let (#id, __len): (#id_ty, usize) = match __toks.first() {
+            Some(__first) => ::docbot::__private::parse_id(&__toks).map_err(|e| {
+                ::docbot::CommandParseError::BadId(e, ::docbot::Spanned::span(__first))
+            })?,
+            None => return Err(::docbot::CommandParseError::NoInput),
+        };
+
+        let mut #iter = __toks.into_iter().skip(__len).fuse();
+
+        Ok(match #id {
+            #(#ctors),*
+        })
+    })
+}
+
+pub fn emit(
+    input: &InputData,
+    id_parts: &IdParts,
+    complete_parts: &CompleteParts,
+    tokens_parts: &TokensParts,
+) -> Result<ParseParts>
+{
+    let ctx = Ident::new("__ctx", input.span);
+    let unit = quote_spanned! { input.span => () };
+    let ctx_ty = input
+        .opts
+        .context
+        .as_ref()
+        .map_or_else(|| unit.clone(), |c| quote_spanned! { input.span => #c });
+
+    let parse = parse_body(
+        input,
+        id_parts,
+        &Conv {
+            ctx: ctx.clone(),
+            ctx_ty: unit,
+            with_ctx: false,
+        },
+    )?;
+    let deprecation_arms: Vec<_> = match input.commands {
+        Commands::Struct(_, ref cmd) => vec![deprecation_arm(
+            input.span,
+            quote_spanned! { input.span => Self },
+            cmd.fields,
+            None,
+        )?],
+        Commands::Enum(_, ref vars) => vars
+            .iter()
+            .map(
+                |CommandVariant {
+                     span,
+                     ident,
+                     opts,
+                     command,
+                     ..
+                 }| {
+                    deprecation_arm(
+                        *span,
+                        quote_spanned! { *span => Self::#ident },
+                        command.fields,
+                        opts.deprecated.as_deref(),
+                    )
+                },
+            )
+            .collect::<Result<_>>()?,
+    };
+
+    let parse_with = parse_body(
+        input,
+        id_parts,
+        &Conv {
+            ctx: ctx.clone(),
+            ctx_ty: quote_spanned! { input.span => Self::Context },
+            with_ctx: true,
+        },
+    )?;
+
     // Quote variables
+    let iter = Ident::new("__iter", input.span);
     let name = input.ty;
     let (impl_vars, ty_vars, where_clause) = input.generics.split_for_impl();
+    let id_ty = &id_parts.ty;
     let id_get_fn = &id_parts.get_fn;
+    let complete_fn = &complete_parts.method;
+    let tokens_fn = &tokens_parts.method;
 
     let items = quote_spanned! { input.span =>
         impl #impl_vars ::docbot::Command for #name #ty_vars #where_clause {
             type Id = #id_ty;
+            type Context = #ctx_ty;
 
             fn parse<
                 I: IntoIterator<Item = S>,
-                S: AsRef<str>,
+                S: ::docbot::Spanned,
             >(#iter: I) -> ::std::result::Result<Self, ::docbot::CommandParseError> {
-                let mut #iter = #iter.into_iter().fuse();
+                let #ctx = &();
 
-                let #id: #id_ty = match #iter.next() {
-                    Some(__str) => __str.as_ref().parse()?,
-                    None => return Err(::docbot::CommandParseError::NoInput),
-                };
+                #parse
+            }
 
-                Ok(match #id {
-                    #(#ctors),*
-                })
+            fn parse_with<
+                I: IntoIterator<Item = S>,
+                S: ::docbot::Spanned,
+            >(
+                #ctx: &Self::Context,
+                #iter: I,
+            ) -> ::std::result::Result<Self, ::docbot::CommandParseError>
+            {
+                #parse_with
             }
 
+            #complete_fn
+
             fn id(&self) -> Self::Id { #id_get_fn }
+
+            #tokens_fn
+
+            fn deprecation(&self) -> ::std::option::Option<&'static str> {
+                match self {
+                    #(#deprecation_arms),*
+                }
+            }
         }
     };
 
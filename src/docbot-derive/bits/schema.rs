@@ -0,0 +1,97 @@
+#[allow(clippy::wildcard_imports)]
+use super::{
+    complete::elem_type,
+    inputs::*,
+    parse::{field_info, FieldInfo, FieldMode},
+};
+use crate::Result;
+use proc_macro2::TokenStream;
+use quote::quote_spanned;
+
+pub struct SchemaParts {
+    pub items: TokenStream,
+}
+
+fn command_schema(
+    span: Span,
+    Command { docs, fields }: &Command,
+    id: TokenStream,
+) -> Result<TokenStream>
+{
+    let info = field_info(span, &docs.usage, fields)?;
+    let kinds = info.iter().map(|info| match info {
+        FieldInfo {
+            mode: FieldMode::Flag(_),
+            ..
+        } => quote_spanned! { span => ::docbot::ArgumentKind::Flag },
+        FieldInfo {
+            opts,
+            ty,
+            mode: FieldMode::RestRequired | FieldMode::RestOptional,
+            ..
+        } if opts.subcommand => {
+            quote_spanned! { span =>
+                ::docbot::ArgumentKind::Subcommand(<#ty as ::docbot::Schema>::schema())
+            }
+        },
+        info => {
+            let elem = elem_type(span, info);
+
+            quote_spanned! { span =>
+                (&::docbot::__private::ArgType::<#elem>::new()).arg_kind()
+            }
+        },
+    });
+
+    Ok(quote_spanned! { span =>
+        ::docbot::__private::command_schema(
+            <Self as ::docbot::Help>::help(Some(#id)),
+            ::std::vec![#(#kinds),*],
+        )
+    })
+}
+
+pub fn emit(input: &InputData) -> Result<SchemaParts> {
+    let commands: Vec<_> = match input.commands {
+        Commands::Struct(_, ref cmd) => {
+            vec![command_schema(
+                input.span,
+                cmd,
+                quote_spanned! { input.span => Self::Id },
+            )?]
+        },
+        Commands::Enum(_, ref vars) => vars
+            .iter()
+            .map(
+                |CommandVariant {
+                     span,
+                     ident,
+                     command,
+                     ..
+                 }| {
+                    command_schema(*span, command, quote_spanned! { *span => Self::Id::#ident })
+                },
+            )
+            .collect::<Result<_>>()?,
+    };
+
+    // Quote variables
+    let name = input.ty;
+    let (impl_vars, ty_vars, where_clause) = input.generics.split_for_impl();
+
+    let items = quote_spanned! { input.span =>
+        impl #impl_vars ::docbot::Schema for #name #ty_vars #where_clause {
+            fn schema() -> ::docbot::CommandSetSchema {
+                #[allow(unused_imports)]
+                use ::docbot::__private::{IdArg as _, OtherArg as _};
+
+                ::docbot::__private::command_set_schema(
+                    <Self as ::docbot::Help>::help(None),
+                    ::std::vec![#(#commands),*],
+                )
+            }
+        }
+    };
+
+    Ok(SchemaParts { items })
+}
@@ -0,0 +1,170 @@
+#[allow(clippy::wildcard_imports)]
+use super::{
+    id::IdParts,
+    inputs::*,
+    parse::{field_info, FieldInfo, FieldMode},
+};
+use crate::Result;
+use proc_macro2::{Literal, TokenStream};
+use quote::quote_spanned;
+
+pub struct TokensParts {
+    pub method: TokenStream,
+    pub items: TokenStream,
+}
+
+/// Get the token used to pass an option, preferring its long name
+fn option_token(opt: &OptionUsage) -> Literal {
+    Literal::string(&opt.long.as_ref().map_or_else(
+        || format!("-{}", opt.short.unwrap_or_default()),
+        |l| format!("--{}", l),
+    ))
+}
+
+fn tokens_fields(
+    span: Span,
+    Command { docs, fields }: &Command,
+    path: TokenStream,
+) -> Result<TokenStream>
+{
+    let info = field_info(span, &docs.usage, fields)?;
+
+    let vars: Vec<_> = info
+        .iter()
+        .enumerate()
+        .map(|(i, FieldInfo { field, .. })| match fields {
+            Fields::Named(..) => syn::parse_str(field).map_err(|e| (e.into(), span)),
+            Fields::Unnamed(..) | Fields::Unit => Ok(Ident::new(&format!("__field_{}", i), span)),
+        })
+        .collect::<Result<_>>()?;
+
+    let pushes = info.iter().zip(&vars).map(|(info, var)| {
+        let FieldInfo { opts, mode, .. } = info;
+        let to_string = quote_spanned! { span => ::std::string::ToString::to_string };
+
+        match *mode {
+            FieldMode::RestRequired | FieldMode::RestOptional if opts.subcommand => {
+                quote_spanned! { span => __sub = ::docbot::Command::to_tokens(#var); }
+            },
+            FieldMode::Required => quote_spanned! { span => __pos.push(#to_string(#var)); },
+            FieldMode::Optional if opts.default.is_some() => {
+                quote_spanned! { span => __pos.push(#to_string(#var)); }
+            },
+            FieldMode::Optional | FieldMode::RestRequired | FieldMode::RestOptional => {
+                quote_spanned! { span =>
+                    for __val in ::std::iter::IntoIterator::into_iter(#var) {
+                        __pos.push(#to_string(__val));
+                    }
+                }
+            },
+            FieldMode::Flag(idx) => {
+                let tok = option_token(&docs.usage.options[idx].1);
+
+                quote_spanned! { span =>
+                    if *#var {
+                        __opts.push(#tok.into());
+                    }
+                }
+            },
+            FieldMode::Value(idx) => {
+                let tok = option_token(&docs.usage.options[idx].1);
+
+                if opts.default.is_some() {
+                    quote_spanned! { span =>
+                        __opts.push(#tok.into());
+                        __opts.push(#to_string(#var));
+                    }
+                } else {
+                    quote_spanned! { span =>
+                        for __val in ::std::iter::IntoIterator::into_iter(#var) {
+                            __opts.push(#tok.into());
+                            __opts.push(#to_string(__val));
+                        }
+                    }
+                }
+            },
+        }
+    });
+
+    let pat = match fields {
+        Fields::Unit => path,
+        Fields::Unnamed(..) => quote_spanned! { span => #path(#(#vars),*) },
+        Fields::Named(..) => quote_spanned! { span => #path { #(#vars),* } },
+    };
+
+    let opts = if docs.usage.options.is_empty() {
+        quote_spanned! { span => None }
+    } else {
+        quote_spanned! { span => Some(__opts) }
+    };
+
+    Ok(quote_spanned! { span =>
+        #pat => {
+            #[allow(unused_mut)]
+            let mut __opts: ::std::vec::Vec<::std::string::String> = ::std::vec::Vec::new();
+            #[allow(unused_mut)]
+            let mut __pos = ::std::vec::Vec::new();
+            #[allow(unused_mut)]
+            let mut __sub = ::std::vec::Vec::new();
+
+            #(#pushes)*
+
+            ::docbot::__private::command_tokens(__id, #opts, __pos, __sub)
+        }
+    })
+}
+
+pub fn emit(input: &InputData, id_parts: &IdParts) -> Result<TokensParts> {
+    let arms: Vec<_> = match input.commands {
+        Commands::Struct(_, ref cmd) => {
+            vec![tokens_fields(
+                input.span,
+                cmd,
+                quote_spanned! { input.span => Self },
+            )?]
+        },
+        Commands::Enum(_, ref vars) => vars
+            .iter()
+            .map(
+                |CommandVariant {
+                     span,
+                     ident,
+                     command,
+                     ..
+                 }| {
+                    tokens_fields(*span, command, quote_spanned! { *span => Self::#ident })
+                },
+            )
+            .collect::<Result<_>>()?,
+    };
+
+    let method = quote_spanned! { input.span =>
+        fn to_tokens(&self) -> ::std::vec::Vec<::std::string::String> {
+            let __id = ::docbot::CommandId::to_str(&::docbot::Command::id(self));
+
+            match self {
+                #(#arms),*
+            }
+        }
+    };
+
+    // Quote variables
+    let name = input.ty;
+    let (impl_vars, ty_vars, where_clause) = input.generics.split_for_impl();
+
+    // Commands without arguments are their own ID, which already displays as
+    // its canonical name
+    let items = if id_parts.ty == *name {
+        TokenStream::new()
+    } else {
+        quote_spanned! { input.span =>
+            impl #impl_vars ::std::fmt::Display for #name #ty_vars #where_clause {
+                fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                    f.write_str(&::docbot::join_tokens(::docbot::Command::to_tokens(self)))
+                }
+            }
+        }
+    };
+
+    Ok(TokensParts { method, items })
+}
@@ -12,27 +12,179 @@ pub enum RestArg {
     Required(String),
 }
 
+#[derive(Clone, Debug)]
+pub struct OptionUsage {
+    pub short: Option<char>,
+    pub long: Option<String>,
+    pub value: Option<String>,
+}
+
 #[derive(Clone, Debug)]
 pub struct CommandUsage {
     pub ids: Vec<String>,
     pub required: Vec<String>,
     pub optional: Vec<String>,
     pub rest: RestArg,
+    /// Options, each paired with the number of positional arguments preceding
+    /// it in the usage line
+    pub options: Vec<(usize, OptionUsage)>,
     pub desc: String,
 }
 
-#[derive(Debug)]
+#[derive(Clone, Copy, Debug)]
+pub enum UsageArg<'a> {
+    Required(&'a str),
+    Optional(&'a str),
+    RestRequired(&'a str),
+    RestOptional(&'a str),
+    Option(&'a OptionUsage),
+}
+
+impl OptionUsage {
+    /// The name used to refer to this option in documentation, e.g. `--name`
+    pub fn name(&self) -> String {
+        self.long.as_ref().map_or_else(
+            || format!("-{}", self.short.unwrap()),
+            |l| format!("--{}", l),
+        )
+    }
+
+    /// The name of the struct field this option is stored in
+    pub fn field_name(&self) -> String {
+        self.long
+            .as_ref()
+            .map_or_else(|| self.short.unwrap().into(), |l| l.replace('-', "_"))
+    }
+}
+
+impl UsageArg<'_> {
+    pub fn name(&self) -> String {
+        match self {
+            Self::Required(n)
+            | Self::Optional(n)
+            | Self::RestRequired(n)
+            | Self::RestOptional(n) => (*n).into(),
+            Self::Option(o) => o.name(),
+        }
+    }
+
+    pub fn is_required(&self) -> bool {
+        matches!(self, Self::Required(..) | Self::RestRequired(..))
+    }
+}
+
+impl CommandUsage {
+    /// List all positional arguments and options, in the order they appear in
+    /// the usage line
+    pub fn args(&self) -> Vec<UsageArg> {
+        let positional = self
+            .required
+            .iter()
+            .map(|n| UsageArg::Required(n))
+            .chain(self.optional.iter().map(|n| UsageArg::Optional(n)))
+            .chain(match self.rest {
+                RestArg::None => None,
+                RestArg::Optional(ref n) => Some(UsageArg::RestOptional(n)),
+                RestArg::Required(ref n) => Some(UsageArg::RestRequired(n)),
+            });
+
+        let mut options = self.options.iter().peekable();
+        let mut ret = Vec::new();
+
+        for (i, arg) in positional.enumerate() {
+            while let Some((_, opt)) = options.next_if(|(pos, _)| *pos <= i) {
+                ret.push(UsageArg::Option(opt));
+            }
+
+            ret.push(arg);
+        }
+
+        ret.extend(options.map(|(_, o)| UsageArg::Option(o)));
+
+        ret
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum DocSection {
+    Text(String, String),
+    SeeAlso(Vec<String>),
+}
+
+/// Translated documentation for a command, given under a `# Locale` header
+#[derive(Clone, Debug)]
+pub struct LocaleDocs {
+    pub desc: String,
+    pub summary: Option<String>,
+    pub args: Option<Vec<(String, bool, String)>>,
+    pub sections: Vec<DocSection>,
+}
+
+#[derive(Clone, Debug)]
 pub struct CommandDocs {
     pub span: Span,
     pub usage: CommandUsage,
     pub summary: Option<String>,
     pub args: Vec<(String, bool, String)>,
-    pub examples: Option<String>,
+    pub examples: Vec<String>,
+    pub sections: Vec<DocSection>,
+    pub locales: Vec<(String, LocaleDocs)>,
 }
 
 pub struct CommandSetDocs {
     pub span: Span,
     pub summary: Option<String>,
+    pub locales: Vec<(String, String)>,
+}
+
+impl CommandDocs {
+    /// Get these docs as written for the given locale, using the original
+    /// text for anything that was not translated
+    pub fn localized(&self, locale: &str) -> Self {
+        let mut ret = self.clone();
+
+        if let Some((_, docs)) = self.locales.iter().find(|(l, _)| l == locale) {
+            ret.usage.desc.clone_from(&docs.desc);
+
+            if docs.summary.is_some() {
+                ret.summary.clone_from(&docs.summary);
+            }
+
+            if let Some(ref args) = docs.args {
+                ret.args.clone_from(args);
+            }
+
+            // Translated sections replace the original text sections, but
+            // command references are the same in every language
+            if !docs.sections.is_empty() {
+                ret.sections = docs
+                    .sections
+                    .iter()
+                    .cloned()
+                    .chain(
+                        self.sections
+                            .iter()
+                            .filter(|s| matches!(s, DocSection::SeeAlso(_)))
+                            .cloned(),
+                    )
+                    .collect();
+            }
+        }
+
+        ret
+    }
+}
+
+impl CommandSetDocs {
+    /// Get the summary of this command set as written for the given locale,
+    /// falling back to the original summary
+    pub fn localized(&self, locale: &str) -> Option<&str> {
+        self.locales
+            .iter()
+            .find(|(l, _)| l == locale)
+            .map(|(_, s)| s.as_str())
+            .or(self.summary.as_deref())
+    }
 }
 
 pub trait ParseDocs: Sized {
@@ -88,6 +240,16 @@ fn parse_usage_line((input, span): (String, Span), desc: String) -> Result<Comma
             Regex::new(r"^\s*\[([^\]]{0,2}|[^\]]*[^\]\.]{3})\]").unwrap();
         static ref REST_ARG_RE: Regex =
             Regex::new(r"^\s*(?:<([^>]+)...>|\[([^\]]+)...\])").unwrap();
+        static ref OPTION_RE: Regex = {
+            let name = r"(?:-[[:alnum:]]|--[[:alnum:]][\w-]*)";
+            let names = format!(r"{0}(?:\s*\|\s*{0})*", name);
+
+            Regex::new(&format!(
+                r"^\s*(?:\[\s*({0})(?:\s+<([^>]+)>)?\s*\]|({0})(?:\s+<([^>]+)>)?)",
+                names
+            ))
+            .unwrap()
+        };
         static ref TRAILING_RE: Regex = Regex::new(r"\S").unwrap();
     }
 
@@ -101,7 +263,12 @@ fn parse_usage_line((input, span): (String, Span), desc: String) -> Result<Comma
     })?;
 
     let ids = if let Some(cap) = ids_match.get(2) {
-        PIPE_RE.split(cap.as_str()).map(|s| s.into()).collect()
+        // IDs may contain several words, which are matched against separate
+        // tokens
+        PIPE_RE
+            .split(cap.as_str())
+            .map(|s| s.split_whitespace().collect::<Vec<_>>().join(" "))
+            .collect()
     } else {
         vec![ids_match[1].into()]
     };
@@ -109,28 +276,58 @@ fn parse_usage_line((input, span): (String, Span), desc: String) -> Result<Comma
     input = &input[ids_match.get(0).unwrap().end()..];
 
     let mut required = vec![];
-    while let Some(req) = REQUIRED_ARG_RE.captures(input) {
-        required.push(req[1].into());
-
-        input = &input[req.get(0).unwrap().end()..];
-    }
-
     let mut optional = vec![];
-    while let Some(opt) = OPTIONAL_ARG_RE.captures(input) {
-        optional.push(opt[1].into());
+    let mut rest = RestArg::None;
+    let mut options = vec![];
+
+    loop {
+        let positional =
+            required.len() + optional.len() + if let RestArg::None = rest { 0 } else { 1 };
+
+        if let Some(opt) = OPTION_RE.captures(input) {
+            let (names, value) = if let Some(names) = opt.get(1) {
+                (names.as_str(), opt.get(2))
+            } else {
+                (&opt[3], opt.get(4))
+            };
+
+            let mut usage = OptionUsage {
+                short: None,
+                long: None,
+                value: value.map(|v| v.as_str().into()),
+            };
+
+            for name in PIPE_RE.split(names) {
+                if let Some(long) = name.strip_prefix("--") {
+                    if usage.long.replace(long.into()).is_some() {
+                        return Err((anyhow!("multiple long names for option {:?}", names), span));
+                    }
+                } else if usage.short.replace(name.chars().nth(1).unwrap()).is_some() {
+                    return Err((anyhow!("multiple short names for option {:?}", names), span));
+                }
+            }
 
-        input = &input[opt.get(0).unwrap().end()..];
+            options.push((positional, usage));
+            input = &input[opt.get(0).unwrap().end()..];
+        } else if let (Some(req), true, RestArg::None) =
+            (REQUIRED_ARG_RE.captures(input), optional.is_empty(), &rest)
+        {
+            required.push(req[1].into());
+            input = &input[req.get(0).unwrap().end()..];
+        } else if let (Some(opt), RestArg::None) = (OPTIONAL_ARG_RE.captures(input), &rest) {
+            optional.push(opt[1].into());
+            input = &input[opt.get(0).unwrap().end()..];
+        } else if let (Some(cap), RestArg::None) = (REST_ARG_RE.captures(input), &rest) {
+            rest = cap.get(2).map_or_else(
+                || RestArg::Required(cap[1].into()),
+                |c| RestArg::Optional(c.as_str().into()),
+            );
+            input = &input[cap.get(0).unwrap().end()..];
+        } else {
+            break;
+        }
     }
 
-    let rest = REST_ARG_RE.captures(input).map_or(RestArg::None, |rest| {
-        input = &input[rest.get(0).unwrap().end()..];
-
-        rest.get(2).map_or_else(
-            || RestArg::Required(rest[1].into()),
-            |cap| RestArg::Optional(cap.as_str().into()),
-        )
-    });
-
     if TRAILING_RE.is_match(input) {
         return Err((anyhow!("trailing string {:?}", input), span));
     }
@@ -140,6 +337,7 @@ fn parse_usage_line((input, span): (String, Span), desc: String) -> Result<Comma
         required,
         optional,
         rest,
+        options,
         desc,
     })
 }
@@ -193,19 +391,13 @@ fn parse_argument_lines(
     }
 
     let expected_args: Vec<_> = usage
-        .required
-        .iter()
-        .map(|a| (a, true))
-        .chain(usage.optional.iter().map(|a| (a, false)))
-        .chain(match usage.rest {
-            RestArg::None => None,
-            RestArg::Optional(ref a) => Some((a, false)),
-            RestArg::Required(ref a) => Some((a, true)),
-        })
+        .args()
+        .into_iter()
+        .map(|a| (a.name(), a.is_required()))
         .collect();
 
     for (arg, _) in &expected_args {
-        if args.get(*arg).is_none() {
+        if args.get(arg).is_none() {
             return Err((
                 anyhow!(
                     "missing documentation for argument {:?} (have documentation for {:?})",
@@ -231,7 +423,7 @@ fn parse_argument_lines(
     let args = expected_args
         .into_iter()
         .map(|(arg, req)| {
-            let (key, val) = args.remove_entry(arg).unwrap();
+            let (key, val) = args.remove_entry(&arg).unwrap();
             (key, req, val)
         })
         .collect();
@@ -239,6 +431,224 @@ fn parse_argument_lines(
     Ok(args)
 }
 
+fn parse_example_lines(
+    span: Span,
+    usage: &CommandUsage,
+    s: impl AsRef<str>,
+) -> Result<Vec<String>>
+{
+    s.as_ref()
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty())
+        .map(|line| {
+            let id = line.split_whitespace().next().unwrap().to_lowercase();
+
+            if usage.ids.iter().any(|i| i.to_lowercase().starts_with(&id)) {
+                Ok(line.into())
+            } else {
+                Err((
+                    anyhow!(
+                        "example {:?} does not start with a command ID (expected one of {:?})",
+                        line,
+                        usage.ids,
+                    ),
+                    span,
+                ))
+            }
+        })
+        .collect()
+}
+
+/// Section headers with a special meaning
+const KNOWN_HEADERS: &[&str] = &[
+    "Description",
+    "Overview",
+    "Summary",
+    "Arguments",
+    "Parameters",
+    "Examples",
+    "See also",
+    "Locale",
+];
+
+/// The largest number of edits for which an unknown header is considered a
+/// misspelling of a known one
+const MAX_HEADER_TYPO: usize = 2;
+
+/// Compute the Levenshtein distance between two strings
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<_> = b.chars().collect();
+    let mut row: Vec<_> = (0..=b.len()).collect();
+
+    for (i, ca) in a.chars().enumerate() {
+        let mut prev = row[0];
+        row[0] = i + 1;
+
+        for (j, cb) in b.iter().enumerate() {
+            let next = (prev + usize::from(ca != *cb))
+                .min(row[j] + 1)
+                .min(row[j + 1] + 1);
+            prev = row[j + 1];
+            row[j + 1] = next;
+        }
+    }
+
+    row[b.len()]
+}
+
+/// Parse the commands listed under `# See also`, either one per line or
+/// separated by commas, and optionally written as links or in backticks
+fn parse_see_also_lines(span: Span, s: impl AsRef<str>) -> Result<Vec<String>> {
+    lazy_static! {
+        static ref REFERENCE_RE: Regex =
+            Regex::new(r"^(?:\[`([^`]+)`\]\(\)|`([^`]+)`|([^`\[\]]+))$").unwrap();
+    }
+
+    let refs = s
+        .as_ref()
+        .lines()
+        .map(|l| l.trim().trim_start_matches(&['-', '*'][..]))
+        .flat_map(|l| l.split(','))
+        .map(str::trim)
+        .filter(|r| !r.is_empty())
+        .map(|r| {
+            let caps = REFERENCE_RE
+                .captures(r)
+                .ok_or_else(|| (anyhow!("invalid command reference {:?}", r), span))?;
+
+            Ok((1..=3)
+                .find_map(|i| caps.get(i))
+                .unwrap()
+                .as_str()
+                .split_whitespace()
+                .collect::<Vec<_>>()
+                .join(" "))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    if refs.is_empty() {
+        return Err((anyhow!("empty see also section"), span));
+    }
+
+    Ok(refs)
+}
+
+/// Parse a `# Locale <code>` header, returning the normalized locale code
+fn parse_locale_header(span: Span, header: &str) -> Result<Option<String>> {
+    lazy_static! {
+        static ref LOCALE_HEADER_RE: Regex = RegexBuilder::new(r"^locale:?\s+(\S+)$")
+            .case_insensitive(true)
+            .build()
+            .unwrap();
+        static ref LOCALE_RE: Regex =
+            Regex::new(r"^[A-Za-z]{2,3}(?:[-_][A-Za-z0-9]{2,8})*$").unwrap();
+    }
+
+    let code = match LOCALE_HEADER_RE.captures(header) {
+        Some(c) => c.get(1).unwrap().as_str(),
+        None => return Ok(None),
+    };
+
+    if !LOCALE_RE.is_match(code) {
+        return Err((
+            anyhow!(
+                "invalid locale {:?}, expected a code such as fr or pt-BR",
+                code
+            ),
+            span,
+        ));
+    }
+
+    Ok(Some(code.replace('_', "-").to_lowercase()))
+}
+
+/// The sections of a command's docs, either in the original language or
+/// translated under a locale header
+#[derive(Default)]
+struct DocBlock {
+    summary: Option<String>,
+    args: Option<Vec<(String, bool, String)>>,
+    examples: Option<Vec<String>>,
+    sections: Vec<DocSection>,
+}
+
+impl DocBlock {
+    fn parse_section(
+        &mut self,
+        span: Span,
+        usage: &CommandUsage,
+        header: String,
+        rest: &str,
+    ) -> Result<()>
+    {
+        let is_duplicate = |name: &str| {
+            self.sections.iter().any(|s| match s {
+                DocSection::Text(n, _) => n.eq_ignore_ascii_case(name),
+                DocSection::SeeAlso(_) => name.eq_ignore_ascii_case("see also"),
+            })
+        };
+
+        match header.to_lowercase().as_ref() {
+            "description" | "overview" | "summary" => {
+                if self.summary.is_some() {
+                    return Err((anyhow!("multiple summary sections found"), span));
+                }
+
+                self.summary = Some(relax_lines(rest));
+            },
+            "arguments" | "parameters" => {
+                if self.args.is_some() {
+                    return Err((anyhow!("multiple arguments sections found"), span));
+                }
+
+                self.args = Some(parse_argument_lines(span, usage, rest)?);
+            },
+            "examples" => {
+                if self.examples.is_some() {
+                    return Err((anyhow!("multiple examples sections found"), span));
+                }
+
+                self.examples = Some(parse_example_lines(span, usage, rest)?);
+            },
+            "see also" => {
+                if is_duplicate(&header) {
+                    return Err((anyhow!("multiple see also sections found"), span));
+                }
+
+                self.sections
+                    .push(DocSection::SeeAlso(parse_see_also_lines(span, rest)?));
+            },
+            lower => {
+                // Any other header is kept as-is, unless it looks like a
+                // misspelling of one with a special meaning
+                if let Some(known) = KNOWN_HEADERS
+                    .iter()
+                    .find(|h| edit_distance(lower, &h.to_lowercase()) <= MAX_HEADER_TYPO)
+                {
+                    return Err((
+                        anyhow!(
+                            "unknown section header {:?}, did you mean {:?}?",
+                            header,
+                            known
+                        ),
+                        span,
+                    ));
+                }
+
+                if is_duplicate(&header) {
+                    return Err((anyhow!("multiple {} sections found", header), span));
+                }
+
+                self.sections
+                    .push(DocSection::Text(header, relax_lines(rest)));
+            },
+        }
+
+        Ok(())
+    }
+}
+
 impl ParseDocs for CommandDocs {
     fn parse_docs(docs: Vec<(String, Span)>) -> Result<Self> {
         let span = docs
@@ -258,58 +668,95 @@ impl ParseDocs for CommandDocs {
 
         let usage = parse_usage_line(usage, desc)?;
 
-        let mut summary = None;
-        let mut args = None;
-        let mut examples = None;
+        let mut main = DocBlock::default();
+        let mut locales: Vec<(String, String, DocBlock)> = Vec::new();
 
         while let Some(par) = take_paragraph(&mut docs, true) {
             lazy_static! {
-                static ref HEADER_RE: Regex = Regex::new(r"^\s*#\s*(\S+)\s*\n").unwrap();
+                static ref HEADER_RE: Regex = Regex::new(r"^\s*#\s*([^\n]*\S)\s*\n").unwrap();
             }
 
             let header_caps = HEADER_RE
                 .captures(&par)
                 .ok_or_else(|| (anyhow!("paragraph missing header"), span))?;
+            let header = header_caps[1]
+                .split_whitespace()
+                .collect::<Vec<_>>()
+                .join(" ");
             let rest = &par[header_caps.get(0).unwrap().end()..];
 
-            match header_caps[1].to_lowercase().as_ref() {
-                "description" | "overview" | "summary" => {
-                    if summary.is_some() {
-                        return Err((anyhow!("multiple summary sections found"), span));
-                    }
+            // Everything following a locale header translates the docs above
+            // it, starting with the command description
+            if let Some(locale) = parse_locale_header(span, &header)? {
+                if locales.iter().any(|(l, _, _)| *l == locale) {
+                    return Err((anyhow!("multiple sections for locale {:?}", locale), span));
+                }
 
-                    summary = Some(relax_lines(rest))
-                },
-                "arguments" | "parameters" => {
-                    if args.is_some() {
-                        return Err((anyhow!("multiple arguments sections found"), span));
-                    }
+                let desc = relax_lines(rest);
 
-                    args = Some(parse_argument_lines(span, &usage, rest)?)
-                },
-                "examples" => {
-                    if examples.is_some() {
-                        return Err((anyhow!("multiple examples sections found"), span));
-                    }
+                if desc.is_empty() {
+                    return Err((
+                        anyhow!("missing command description for locale {:?}", locale),
+                        span,
+                    ));
+                }
 
-                    examples = Some(relax_lines(rest))
-                },
-                _ => (),
+                locales.push((locale, desc, DocBlock::default()));
+                continue;
             }
+
+            let block = locales.last_mut().map_or(&mut main, |(_, _, b)| b);
+
+            block.parse_section(span, &usage, header, rest)?;
         }
 
         if usage.desc.trim().is_empty() {
             return Err((anyhow!("missing command description"), span));
         }
 
-        let args = args.map_or_else(|| parse_argument_lines(span, &usage, ""), Ok)?;
+        let locales = locales
+            .into_iter()
+            .map(|(locale, desc, block)| {
+                if block.examples.is_some()
+                    || block
+                        .sections
+                        .iter()
+                        .any(|s| matches!(s, DocSection::SeeAlso(_)))
+                {
+                    return Err((
+                        anyhow!(
+                            "examples and see also sections cannot be translated (found in \
+                             locale {:?})",
+                            locale
+                        ),
+                        span,
+                    ));
+                }
+
+                Ok((
+                    locale,
+                    LocaleDocs {
+                        desc,
+                        summary: block.summary,
+                        args: block.args,
+                        sections: block.sections,
+                    },
+                ))
+            })
+            .collect::<Result<_>>()?;
+
+        let args = main
+            .args
+            .map_or_else(|| parse_argument_lines(span, &usage, ""), Ok)?;
 
         Ok(Self {
             span,
             usage,
-            summary,
+            summary: main.summary,
             args,
-            examples,
+            examples: main.examples.unwrap_or_default(),
+            sections: main.sections,
+            locales,
         })
     }
 
@@ -328,15 +775,41 @@ impl ParseDocs for CommandSetDocs {
             .unwrap()
             .unwrap();
 
+        lazy_static! {
+            static ref HEADER_RE: Regex = Regex::new(r"^\s*#\s*([^\n]*\S)\s*\n").unwrap();
+        }
+
         let mut docs = docs.into_iter();
         let mut summary = String::new();
+        let mut locales: Vec<(String, String)> = Vec::new();
+
+        while let Some(par) = take_paragraph(&mut docs, true) {
+            let mut par = par.as_str();
+
+            // A locale header starts a translation of the summary
+            if let Some(caps) = HEADER_RE.captures(par) {
+                if let Some(locale) = parse_locale_header(span, &caps[1])? {
+                    if locales.iter().any(|(l, _)| *l == locale) {
+                        return Err((anyhow!("multiple sections for locale {:?}", locale), span));
+                    }
 
-        while let Some(par) = take_paragraph(&mut docs, false) {
-            if !summary.is_empty() {
-                summary.push('\n');
+                    locales.push((locale, String::new()));
+                    par = &par[caps.get(0).unwrap().end()..];
+                }
             }
 
-            summary.push_str(&par);
+            let text = locales.last_mut().map_or(&mut summary, |(_, s)| s);
+            let par = relax_lines(par);
+
+            if par.is_empty() {
+                continue;
+            }
+
+            if !text.is_empty() {
+                text.push('\n');
+            }
+
+            text.push_str(&par);
         }
 
         let summary = summary.trim();
@@ -346,13 +819,18 @@ impl ParseDocs for CommandSetDocs {
             Some(summary.into())
         };
 
-        Ok(Self { span, summary })
+        Ok(Self {
+            span,
+            summary,
+            locales,
+        })
     }
 
     fn no_docs() -> Result<Self, anyhow::Error> {
         Ok(Self {
             span: Span::call_site(),
             summary: None,
+            locales: Vec::new(),
         })
     }
 }
@@ -11,7 +11,10 @@ mod docs;
 mod opts;
 mod trie;
 
-use bits::{help::HelpParts, id::IdParts, parse::ParseParts};
+use bits::{
+    examples::ExamplesParts, help::HelpParts, id::IdParts, links::LinksParts, parse::ParseParts,
+    schema::SchemaParts, tokens::TokensParts,
+};
 use proc_macro::TokenStream as TokenStream1;
 use proc_macro2::{Span, TokenStream};
 use quote::quote_spanned;
@@ -39,19 +42,39 @@ pub fn derive_docbot(input: TokenStream1) -> TokenStream1 {
 fn derive_docbot_impl(input: &DeriveInput) -> Result<TokenStream> {
     let inputs = bits::inputs::assemble(input)?;
     let id_parts = bits::id::emit(&inputs)?;
-    let parse_parts = bits::parse::emit(&inputs, &id_parts)?;
+    let complete_parts = bits::complete::emit(&inputs, &id_parts)?;
+    let tokens_parts = bits::tokens::emit(&inputs, &id_parts)?;
+    let parse_parts = bits::parse::emit(&inputs, &id_parts, &complete_parts, &tokens_parts)?;
     let help_parts = bits::help::emit(&inputs)?;
+    let schema_parts = bits::schema::emit(&inputs)?;
+    let examples_parts = bits::examples::emit(&inputs)?;
+    let links_parts = bits::links::emit(&inputs)?;
 
     // Quote variables
     let IdParts {
         items: id_items, ..
     } = id_parts;
     let ParseParts { items: parse_items } = parse_parts;
+    let TokensParts {
+        items: tokens_items,
+        ..
+    } = tokens_parts;
     let HelpParts { items: help_items } = help_parts;
+    let SchemaParts {
+        items: schema_items,
+    } = schema_parts;
+    let ExamplesParts {
+        items: examples_items,
+    } = examples_parts;
+    let LinksParts { items: links_items } = links_parts;
 
     Ok(quote_spanned! { input.span() =>
         #id_items
         #parse_items
+        #tokens_items
         #help_items
+        #schema_items
+        #examples_items
+        #links_items
     })
 }
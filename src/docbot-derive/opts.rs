@@ -1,10 +1,39 @@
 use crate::Result;
 use anyhow::anyhow;
-use syn::{spanned::Spanned, Attribute, Meta, NestedMeta};
+use syn::{
+    spanned::Spanned, Attribute, Expr, Lit, LitStr, Meta, MetaNameValue, NestedMeta, Path, Type,
+};
 
 #[derive(Debug)]
 pub struct FieldOpts {
     pub subcommand: bool,
+    pub hint: Option<String>,
+    /// A function converting a string into the argument's value, used in
+    /// place of `FromStr`
+    pub parse_with: Option<Path>,
+    /// The value of an optional argument if it is omitted
+    pub default: Option<Expr>,
+    /// A function checking the argument's value after it is converted
+    pub validate: Option<Path>,
+    /// The range the argument's value must lie in, with its source text
+    pub range: Option<(Expr, String)>,
+}
+
+#[derive(Debug)]
+pub struct VariantOpts {
+    pub hidden: bool,
+    pub deprecated: Option<String>,
+    pub requires: Option<String>,
+}
+
+#[derive(Debug)]
+pub struct OuterOpts {
+    pub root: bool,
+    pub context: Option<Type>,
+}
+
+fn parse_lit<T: syn::parse::Parse>(s: &LitStr) -> Result<T> {
+    s.parse().map_err(|e| (e.into(), s.span()))
 }
 
 pub trait ParseOpts: Sized {
@@ -29,6 +58,64 @@ impl ParseOpts for FieldOpts {
 
                             ret.subcommand = true;
                         },
+                        NestedMeta::Meta(Meta::NameValue(MetaNameValue {
+                            path,
+                            lit: Lit::Str(s),
+                            ..
+                        })) if path.is_ident("hint") => {
+                            if ret.hint.is_some() {
+                                return Err((anyhow!("duplicate hint specifier"), path.span()));
+                            }
+
+                            ret.hint = Some(s.value());
+                        },
+                        NestedMeta::Meta(Meta::NameValue(MetaNameValue {
+                            path,
+                            lit: Lit::Str(s),
+                            ..
+                        })) if path.is_ident("parse_with") => {
+                            if ret.parse_with.is_some() {
+                                return Err((
+                                    anyhow!("duplicate parse_with specifier"),
+                                    path.span(),
+                                ));
+                            }
+
+                            ret.parse_with = Some(parse_lit(&s)?);
+                        },
+                        NestedMeta::Meta(Meta::NameValue(MetaNameValue {
+                            path,
+                            lit: Lit::Str(s),
+                            ..
+                        })) if path.is_ident("default") => {
+                            if ret.default.is_some() {
+                                return Err((anyhow!("duplicate default specifier"), path.span()));
+                            }
+
+                            ret.default = Some(parse_lit(&s)?);
+                        },
+                        NestedMeta::Meta(Meta::NameValue(MetaNameValue {
+                            path,
+                            lit: Lit::Str(s),
+                            ..
+                        })) if path.is_ident("validate") => {
+                            if ret.validate.is_some() {
+                                return Err((anyhow!("duplicate validate specifier"), path.span()));
+                            }
+
+                            ret.validate = Some(parse_lit(&s)?);
+                        },
+                        NestedMeta::Meta(Meta::NameValue(MetaNameValue {
+                            path,
+                            lit: Lit::Str(s),
+                            ..
+                        })) if path.is_ident("range") => {
+                            if ret.range.is_some() {
+                                return Err((anyhow!("duplicate range specifier"), path.span()));
+                            }
+
+                            ret.range = Some((parse_lit(&s)?, s.value()));
+                        },
                         i => {
                             return Err((
                                 anyhow!("unexpected value in #[docbot] attribute"),
@@ -53,13 +140,147 @@ impl ParseOpts for FieldOpts {
 }
 
 impl Default for FieldOpts {
-    fn default() -> Self { Self { subcommand: false } }
+    fn default() -> Self {
+        Self {
+            subcommand: false,
+            hint: None,
+            parse_with: None,
+            default: None,
+            validate: None,
+            range: None,
+        }
+    }
 }
 
-impl ParseOpts for () {
+impl ParseOpts for OuterOpts {
     fn parse_opts(attr: &Attribute) -> Result<Self> {
-        Err((anyhow!("unexpected #[docbot] attribute"), attr.span()))
+        let meta = attr.parse_meta().map_err(|e| (e.into(), attr.span()))?;
+        let mut ret = Self::default();
+
+        match meta {
+            Meta::List(l) => {
+                for item in l.nested {
+                    match item {
+                        NestedMeta::Meta(Meta::Path(p)) if p.is_ident("root") => {
+                            if ret.root {
+                                return Err((anyhow!("duplicate root specifier"), p.span()));
+                            }
+
+                            ret.root = true;
+                        },
+                        NestedMeta::Meta(Meta::NameValue(MetaNameValue {
+                            path,
+                            lit: Lit::Str(s),
+                            ..
+                        })) if path.is_ident("context") => {
+                            if ret.context.is_some() {
+                                return Err((anyhow!("duplicate context specifier"), path.span()));
+                            }
+
+                            ret.context = Some(parse_lit(&s)?);
+                        },
+                        i => {
+                            return Err((
+                                anyhow!("unexpected value in #[docbot] attribute"),
+                                i.span(),
+                            ))
+                        },
+                    }
+                }
+            },
+            _ => {
+                return Err((
+                    anyhow!("invalid #[docbot] attribute format, expected #[docbot(...)]",),
+                    attr.span(),
+                ))
+            },
+        }
+
+        Ok(ret)
     }
 
-    fn no_opts() -> Result<Self, anyhow::Error> { Ok(()) }
+    fn no_opts() -> Result<Self, anyhow::Error> { Ok(OuterOpts::default()) }
+}
+
+impl Default for OuterOpts {
+    fn default() -> Self {
+        Self {
+            root: false,
+            context: None,
+        }
+    }
+}
+
+impl ParseOpts for VariantOpts {
+    fn parse_opts(attr: &Attribute) -> Result<Self> {
+        let meta = attr.parse_meta().map_err(|e| (e.into(), attr.span()))?;
+        let mut ret = Self::default();
+
+        match meta {
+            Meta::List(l) => {
+                for item in l.nested {
+                    match item {
+                        NestedMeta::Meta(Meta::Path(p)) if p.is_ident("hidden") => {
+                            if ret.hidden {
+                                return Err((anyhow!("duplicate hidden specifier"), p.span()));
+                            }
+
+                            ret.hidden = true;
+                        },
+                        NestedMeta::Meta(Meta::NameValue(MetaNameValue {
+                            path,
+                            lit: Lit::Str(s),
+                            ..
+                        })) if path.is_ident("deprecated") => {
+                            if ret.deprecated.is_some() {
+                                return Err((
+                                    anyhow!("duplicate deprecated specifier"),
+                                    path.span(),
+                                ));
+                            }
+
+                            ret.deprecated = Some(s.value());
+                        },
+                        NestedMeta::Meta(Meta::NameValue(MetaNameValue {
+                            path,
+                            lit: Lit::Str(s),
+                            ..
+                        })) if path.is_ident("requires") => {
+                            if ret.requires.is_some() {
+                                return Err((anyhow!("duplicate requires specifier"), path.span()));
+                            }
+
+                            ret.requires = Some(s.value());
+                        },
+                        i => {
+                            return Err((
+                                anyhow!("unexpected value in #[docbot] attribute"),
+                                i.span(),
+                            ))
+                        },
+                    }
+                }
+            },
+            _ => {
+                return Err((
+                    anyhow!("invalid #[docbot] attribute format, expected #[docbot(...)]",),
+                    attr.span(),
+                ))
+            },
+        }
+
+        Ok(ret)
+    }
+
+    fn no_opts() -> Result<Self, anyhow::Error> { Ok(VariantOpts::default()) }
+}
+
+impl Default for VariantOpts {
+    fn default() -> Self {
+        Self {
+            hidden: false,
+            deprecated: None,
+            requires: None,
+        }
+    }
 }
@@ -0,0 +1,859 @@
+use crate::{
+    __private::is_option, ArgumentDesc, ArgumentName, ArgumentUsage, CommandDesc,
+    CommandParseError, CommandUsage, HelpNode, HelpText, HelpTopic, IdParseError, OptionUsage,
+    Spanned,
+};
+use lazy_static::lazy_static;
+use regex::Regex;
+use std::{borrow::Cow, collections::HashMap};
+use thiserror::Error;
+
+/// Error type for an invalid command given to a [`DynamicCommandSetBuilder`]
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum UsageError {
+    /// The usage line did not start with a command ID
+    #[error("invalid command ID specifier in {0:?}, expected e.g. 'foo' or '(foo|bar)'")]
+    BadIds(String),
+    /// An option in the usage line had more than one long or short name
+    #[error("multiple {1} names for option {0:?}")]
+    OptionNames(String, &'static str),
+    /// The usage line contained something other than arguments after the ID
+    #[error("trailing string {0:?}")]
+    Trailing(String),
+    /// A name was used by more than one command
+    #[error("multiple entries for identifier {0:?}")]
+    DuplicateId(String),
+    /// A description was given for an argument not in the usage line
+    #[error("no argument named {0:?} in the usage line")]
+    UnknownArgument(String),
+}
+
+enum Rest {
+    None,
+    Optional(String),
+    Required(String),
+}
+
+struct Opt {
+    short: Option<char>,
+    long: Option<String>,
+    value: Option<String>,
+}
+
+impl Opt {
+    fn name(&self) -> String {
+        self.long.as_ref().map_or_else(
+            || format!("-{}", self.short.unwrap()),
+            |l| format!("--{}", l),
+        )
+    }
+}
+
+/// A usage line, as written in the doc comment of a derived command
+struct Usage {
+    ids: Vec<String>,
+    required: Vec<String>,
+    optional: Vec<String>,
+    rest: Rest,
+    /// Options, each paired with the number of positional arguments preceding
+    /// it in the usage line
+    options: Vec<(usize, Opt)>,
+}
+
+enum UsageArg<'a> {
+    Required(&'a str),
+    Optional(&'a str),
+    RestRequired(&'a str),
+    RestOptional(&'a str),
+    Option(&'a Opt),
+}
+
+impl UsageArg<'_> {
+    fn name(&self) -> String {
+        match self {
+            Self::Required(n)
+            | Self::Optional(n)
+            | Self::RestRequired(n)
+            | Self::RestOptional(n) => (*n).into(),
+            Self::Option(o) => o.name(),
+        }
+    }
+
+    fn usage(&self) -> ArgumentUsage {
+        ArgumentUsage {
+            name: self.name().into(),
+            is_required: matches!(self, Self::Required(_) | Self::RestRequired(_)),
+            is_rest: matches!(self, Self::RestRequired(_) | Self::RestOptional(_)),
+            option: match self {
+                Self::Option(o) => Some(OptionUsage {
+                    short: o.short,
+                    long: o.long.clone().map(Into::into),
+                    value: o.value.clone().map(Into::into),
+                }),
+                _ => None,
+            },
+        }
+    }
+}
+
+impl Usage {
+    /// List all positional arguments and options, in the order they appear in
+    /// the usage line
+    fn args(&self) -> Vec<UsageArg> {
+        let positional = self
+            .required
+            .iter()
+            .map(|n| UsageArg::Required(n))
+            .chain(self.optional.iter().map(|n| UsageArg::Optional(n)))
+            .chain(match self.rest {
+                Rest::None => None,
+                Rest::Optional(ref n) => Some(UsageArg::RestOptional(n)),
+                Rest::Required(ref n) => Some(UsageArg::RestRequired(n)),
+            });
+
+        let mut options = self.options.iter().peekable();
+        let mut ret = Vec::new();
+
+        for (i, arg) in positional.enumerate() {
+            while let Some((_, opt)) = options.next_if(|(pos, _)| *pos <= i) {
+                ret.push(UsageArg::Option(opt));
+            }
+
+            ret.push(arg);
+        }
+
+        ret.extend(options.map(|(_, o)| UsageArg::Option(o)));
+
+        ret
+    }
+}
+
+/// Parse a usage line with the same grammar as `#[derive(Docbot)]`
+fn parse_usage_line(line: &str) -> Result<Usage, UsageError> {
+    lazy_static! {
+        static ref COMMAND_IDS_RE: Regex =
+            Regex::new(r"^\s*(?:([^\(]\S*)|\(\s*([^\)]*)\))").unwrap();
+        static ref PIPE_RE: Regex = Regex::new(r"\s*\|\s*").unwrap();
+        static ref REQUIRED_ARG_RE: Regex =
+            Regex::new(r"^\s*<([^>]{0,2}|[^>]*[^>\.]{3})>").unwrap();
+        static ref OPTIONAL_ARG_RE: Regex =
+            Regex::new(r"^\s*\[([^\]]{0,2}|[^\]]*[^\]\.]{3})\]").unwrap();
+        static ref REST_ARG_RE: Regex =
+            Regex::new(r"^\s*(?:<([^>]+)...>|\[([^\]]+)...\])").unwrap();
+        static ref OPTION_RE: Regex = {
+            let name = r"(?:-[[:alnum:]]|--[[:alnum:]][\w-]*)";
+            let names = format!(r"{0}(?:\s*\|\s*{0})*", name);
+
+            Regex::new(&format!(
+                r"^\s*(?:\[\s*({0})(?:\s+<([^>]+)>)?\s*\]|({0})(?:\s+<([^>]+)>)?)",
+                names
+            ))
+            .unwrap()
+        };
+        static ref TRAILING_RE: Regex = Regex::new(r"\S").unwrap();
+    }
+
+    let mut input = line;
+
+    let ids_match = COMMAND_IDS_RE
+        .captures(input)
+        .ok_or_else(|| UsageError::BadIds(line.into()))?;
+
+    let ids = if let Some(cap) = ids_match.get(2) {
+        PIPE_RE
+            .split(cap.as_str())
+            .map(|s| s.split_whitespace().collect::<Vec<_>>().join(" "))
+            .collect()
+    } else {
+        vec![ids_match[1].into()]
+    };
+
+    input = &input[ids_match.get(0).unwrap().end()..];
+
+    let mut required = vec![];
+    let mut optional = vec![];
+    let mut rest = Rest::None;
+    let mut options = vec![];
+
+    loop {
+        let positional =
+            required.len() + optional.len() + if let Rest::None = rest { 0 } else { 1 };
+
+        if let Some(opt) = OPTION_RE.captures(input) {
+            let (names, value) = if let Some(names) = opt.get(1) {
+                (names.as_str(), opt.get(2))
+            } else {
+                (&opt[3], opt.get(4))
+            };
+
+            let mut usage = Opt {
+                short: None,
+                long: None,
+                value: value.map(|v| v.as_str().into()),
+            };
+
+            for name in PIPE_RE.split(names) {
+                if let Some(long) = name.strip_prefix("--") {
+                    if usage.long.replace(long.into()).is_some() {
+                        return Err(UsageError::OptionNames(names.into(), "long"));
+                    }
+                } else if usage.short.replace(name.chars().nth(1).unwrap()).is_some() {
+                    return Err(UsageError::OptionNames(names.into(), "short"));
+                }
+            }
+
+            options.push((positional, usage));
+            input = &input[opt.get(0).unwrap().end()..];
+        } else if let (Some(req), true, Rest::None) =
+            (REQUIRED_ARG_RE.captures(input), optional.is_empty(), &rest)
+        {
+            required.push(req[1].into());
+            input = &input[req.get(0).unwrap().end()..];
+        } else if let (Some(opt), Rest::None) = (OPTIONAL_ARG_RE.captures(input), &rest) {
+            optional.push(opt[1].into());
+            input = &input[opt.get(0).unwrap().end()..];
+        } else if let (Some(cap), Rest::None) = (REST_ARG_RE.captures(input), &rest) {
+            rest = cap.get(2).map_or_else(
+                || Rest::Required(cap[1].into()),
+                |c| Rest::Optional(c.as_str().into()),
+            );
+            input = &input[cap.get(0).unwrap().end()..];
+        } else {
+            break;
+        }
+    }
+
+    if TRAILING_RE.is_match(input) {
+        return Err(UsageError::Trailing(input.into()));
+    }
+
+    Ok(Usage {
+        ids,
+        required,
+        optional,
+        rest,
+        options,
+    })
+}
+
+/// Split help text into plain text and ``[`command`]()`` links
+fn help_text(s: &str) -> HelpText {
+    lazy_static! {
+        static ref LINK_RE: Regex = Regex::new(r"\[`([^`]+)`\]\(\)").unwrap();
+    }
+
+    let mut nodes = Vec::new();
+    let mut end = 0;
+
+    for caps in LINK_RE.captures_iter(s) {
+        let link = caps.get(0).unwrap();
+
+        if link.start() > end {
+            nodes.push(HelpNode::Text(s[end..link.start()].to_owned().into()));
+        }
+
+        nodes.push(HelpNode::Link(caps[1].to_owned().into()));
+
+        end = link.end();
+    }
+
+    if end < s.len() {
+        nodes.push(HelpNode::Text(s[end..].to_owned().into()));
+    }
+
+    HelpText(nodes.into())
+}
+
+struct CommandDef {
+    usage: Usage,
+    desc: String,
+    args: Vec<(String, String)>,
+}
+
+/// Builder for a [`DynamicCommandSet`]
+#[derive(Default)]
+pub struct DynamicCommandSetBuilder {
+    summary: Option<String>,
+    commands: Vec<CommandDef>,
+}
+
+impl std::fmt::Debug for DynamicCommandSetBuilder {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("DynamicCommandSetBuilder")
+            .field("summary", &self.summary)
+            .field("commands", &self.commands.len())
+            .finish()
+    }
+}
+
+impl DynamicCommandSetBuilder {
+    /// Set the summary shown at the top of the command list
+    #[must_use]
+    pub fn summary(mut self, summary: impl Into<String>) -> Self {
+        self.summary = Some(summary.into());
+        self
+    }
+
+    /// Add a command, described by a usage line such as
+    /// `(add|new) [-f|--force] <name> [args...]`, a short description, and a
+    /// description for any of its arguments, by name.
+    /// # Errors
+    /// Returns an error if the usage line is invalid, if a name of the
+    /// command is already used by another command, or if a description is
+    /// given for an argument not in the usage line.
+    pub fn command(
+        mut self,
+        usage: &str,
+        desc: &str,
+        args: &[(&str, &str)],
+    ) -> Result<Self, UsageError>
+    {
+        let usage = parse_usage_line(usage)?;
+
+        for (i, id) in usage.ids.iter().enumerate() {
+            let id = id.to_lowercase();
+
+            if self
+                .commands
+                .iter()
+                .flat_map(|c| &c.usage.ids)
+                .chain(&usage.ids[..i])
+                .any(|i| i.to_lowercase() == id)
+            {
+                return Err(UsageError::DuplicateId(id));
+            }
+        }
+
+        let names: Vec<_> = usage.args().iter().map(UsageArg::name).collect();
+
+        if let Some((name, _)) = args.iter().find(|(n, _)| !names.iter().any(|m| m == n)) {
+            return Err(UsageError::UnknownArgument((*name).into()));
+        }
+
+        self.commands.push(CommandDef {
+            usage,
+            desc: desc.into(),
+            args: args
+                .iter()
+                .map(|(n, d)| ((*n).into(), (*d).into()))
+                .collect(),
+        });
+
+        Ok(self)
+    }
+
+    /// Build the command set
+    #[must_use]
+    pub fn build(self) -> DynamicCommandSet {
+        let mut commands = Vec::new();
+        let mut usages = Vec::new();
+        let mut names = Vec::new();
+
+        for (i, CommandDef { usage, desc, args }) in self.commands.into_iter().enumerate() {
+            let arg_usages: Vec<_> = usage.args().iter().map(UsageArg::usage).collect();
+
+            let arg_descs: Vec<_> = arg_usages
+                .iter()
+                .map(|a| ArgumentDesc {
+                    name: a.name.clone(),
+                    is_required: a.is_required,
+                    option: a.option.clone(),
+                    desc: args
+                        .iter()
+                        .find(|(n, _)| *n == a.name)
+                        .map_or(HelpText(Cow::Borrowed(&[])), |(_, d)| help_text(d)),
+                    value: None,
+                })
+                .collect();
+
+            let cmd_usage = CommandUsage {
+                ids: usage.ids.iter().cloned().map(Into::into).collect(),
+                args: arg_usages.into(),
+                desc: help_text(&desc),
+                hidden: false,
+                deprecated: None,
+                requires: None,
+            };
+
+            names.extend(cmd_usage.ids.iter().map(|n| (n.to_lowercase(), i)));
+
+            commands.push(DynamicUsage {
+                id: cmd_usage.ids[0].to_string(),
+                required: usage.required,
+                optional: usage.optional,
+                rest: match usage.rest {
+                    Rest::None => None,
+                    Rest::Optional(n) => Some((n, false)),
+                    Rest::Required(n) => Some((n, true)),
+                },
+                options: cmd_usage
+                    .args
+                    .iter()
+                    .filter_map(|a| a.option.as_ref().map(|o| (a.name.to_string(), o.clone())))
+                    .collect(),
+                topic: HelpTopic::Command(
+                    cmd_usage.clone(),
+                    CommandDesc {
+                        summary: None,
+                        args: arg_descs.into(),
+                        examples: &[],
+                        sections: &[],
+                    },
+                ),
+            });
+
+            usages.push(cmd_usage);
+        }
+
+        DynamicCommandSet {
+            ids: id_table(&names),
+            names: names.iter().map(|(n, _)| n.clone()).collect(),
+            name_groups: usages
+                .iter()
+                .map(|u| u.ids.iter().map(|n| n.to_lowercase().into()).collect())
+                .collect(),
+            max_words: names
+                .iter()
+                .map(|(n, _)| n.split(' ').count())
+                .max()
+                .unwrap_or(1),
+            topic: HelpTopic::CommandSet(self.summary.as_deref().map(help_text), usages.into()),
+            commands,
+        }
+    }
+}
+
+/// Map every prefix of every name to the command it abbreviates, or to the
+/// names it could stand for if it is ambiguous.  A name which is a prefix of
+/// another is matched exactly, as with derived commands.
+fn id_table(names: &[(String, usize)]) -> HashMap<String, Result<usize, Vec<Cow<'static, str>>>> {
+    let mut ret = HashMap::new();
+
+    for (name, _) in names {
+        for (end, _) in name.char_indices().skip(1).chain(Some((name.len(), ' '))) {
+            let prefix = &name[..end];
+
+            if ret.contains_key(prefix) {
+                continue;
+            }
+
+            // Names starting with the prefix, leaving out any which extend
+            // another such name
+            let matches: Vec<_> = names
+                .iter()
+                .filter(|(n, _)| n.starts_with(prefix))
+                .filter(|(n, _)| {
+                    !names.iter().any(|(m, _)| {
+                        m.len() < n.len() && m.starts_with(prefix) && n.starts_with(m)
+                    })
+                })
+                .collect();
+
+            let value = if matches.iter().all(|(_, i)| *i == matches[0].1) {
+                Ok(matches[0].1)
+            } else {
+                Err(matches.iter().map(|(n, _)| n.clone().into()).collect())
+            };
+
+            ret.insert(prefix.into(), value);
+        }
+    }
+
+    ret
+}
+
+/// How to parse a command of a [`DynamicCommandSet`]
+struct DynamicUsage {
+    id: String,
+    required: Vec<String>,
+    optional: Vec<String>,
+    rest: Option<(String, bool)>,
+    options: Vec<(String, OptionUsage)>,
+    topic: HelpTopic,
+}
+
+impl DynamicUsage {
+    /// Find the option passed by the given name, which may only be a flag if
+    /// no value was given with it inline
+    fn find_option(&self, name: &str, inline: bool) -> Option<&(String, OptionUsage)> {
+        self.options.iter().find(|(_, o)| {
+            (o.value.is_some() || !inline)
+                && (o
+                    .long
+                    .as_deref()
+                    .map_or(false, |l| name.strip_prefix("--") == Some(l))
+                    || o.short.map_or(false, |c| {
+                        let mut chars = name.chars();
+                        chars.next() == Some('-')
+                            && chars.next() == Some(c)
+                            && chars.next().is_none()
+                    }))
+        })
+    }
+}
+
+/// A set of commands defined at runtime, such as user-defined commands loaded
+/// from a database.  Commands use the same usage-line grammar as
+/// `#[derive(Docbot)]`, and parse into a [`DynamicCommand`] holding the
+/// arguments as strings.
+pub struct DynamicCommandSet {
+    commands: Vec<DynamicUsage>,
+    ids: HashMap<String, Result<usize, Vec<Cow<'static, str>>>>,
+    names: Vec<String>,
+    name_groups: Vec<Vec<Cow<'static, str>>>,
+    max_words: usize,
+    topic: HelpTopic,
+}
+
+impl std::fmt::Debug for DynamicCommandSet {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("DynamicCommandSet")
+            .field("names", &self.names)
+            .finish()
+    }
+}
+
+impl DynamicCommandSet {
+    /// Start building a new command set
+    #[must_use]
+    pub fn builder() -> DynamicCommandSetBuilder { DynamicCommandSetBuilder::default() }
+
+    /// List all names of the commands in this set, including aliases
+    #[must_use]
+    pub fn names(&self) -> &[String] { &self.names }
+
+    fn parse_name(&self, s: &str) -> Result<usize, IdParseError> {
+        match self.ids.get(&s.to_lowercase()) {
+            Some(Ok(i)) => Ok(*i),
+            Some(Err(names)) => Err(IdParseError::Ambiguous(names.clone(), s.into())),
+            None => Err(IdParseError::NoMatch(s.into(), self.name_groups.clone())),
+        }
+    }
+
+    /// Find the command named at the start of a list of words, taking the
+    /// longest match as with derived commands
+    fn parse_id<S: AsRef<str>>(&self, words: &[S]) -> Result<(usize, usize), IdParseError> {
+        for len in (2..=self.max_words.min(words.len())).rev() {
+            let joined = words[..len]
+                .iter()
+                .map(AsRef::as_ref)
+                .collect::<Vec<_>>()
+                .join(" ");
+
+            if let Ok(i) = self.parse_name(&joined) {
+                return Ok((i, len));
+            }
+        }
+
+        self.parse_name(words[0].as_ref()).map(|i| (i, 1))
+    }
+
+    /// Retrieve the help topic for the command with the given name, or the
+    /// list of all commands in the set if `name` is `None`
+    /// # Errors
+    /// Returns an error if the name does not match a command.
+    pub fn help(&self, name: Option<&str>) -> Result<&HelpTopic, IdParseError> {
+        match name {
+            Some(name) => self.parse_name(name).map(|i| &self.commands[i].topic),
+            None => Ok(&self.topic),
+        }
+    }
+
+    /// Try to parse a sequence of arguments as a command of this set
+    /// # Errors
+    /// Returns an error if no command matched, or if the arguments did not
+    /// fit the command's usage line.
+    pub fn parse<I: IntoIterator<Item = S>, S: Spanned>(
+        &self,
+        iter: I,
+    ) -> Result<DynamicCommand, CommandParseError>
+    {
+        let toks: Vec<S> = iter.into_iter().collect();
+
+        let (idx, len) = match toks.first() {
+            Some(first) => self
+                .parse_id(&toks)
+                .map_err(|e| CommandParseError::BadId(e, first.span()))?,
+            None => return Err(CommandParseError::NoInput),
+        };
+
+        let cmd = &self.commands[idx];
+        let arg_name = |arg: &str| ArgumentName {
+            cmd: cmd.id.clone().into(),
+            arg: arg.to_owned().into(),
+        };
+        let mut values = HashMap::new();
+        let mut pos = Vec::new();
+        let mut only_pos = cmd.options.is_empty();
+        let mut iter = toks.into_iter().skip(len);
+
+        while let Some(tok) = iter.next() {
+            if only_pos || !is_option(tok.as_ref()) {
+                pos.push(tok);
+                continue;
+            }
+
+            let s = tok.as_ref();
+
+            if s == "--" {
+                only_pos = true;
+                continue;
+            }
+
+            let (name, inline) = match s.find('=') {
+                Some(i) if s.starts_with("--") => (&s[..i], Some(&s[i + 1..])),
+                _ => (s, None),
+            };
+
+            let (opt_name, opt) = cmd.find_option(name, inline.is_some()).ok_or_else(|| {
+                CommandParseError::UnknownOption(cmd.id.clone().into(), s.into(), tok.span())
+            })?;
+
+            if values.contains_key(opt_name) {
+                return Err(CommandParseError::DuplicateOption(
+                    arg_name(opt_name),
+                    tok.span(),
+                ));
+            }
+
+            let value = match (&opt.value, inline) {
+                (None, _) => Vec::new(),
+                (Some(_), Some(val)) => vec![val.into()],
+                (Some(_), None) => match iter.next() {
+                    Some(val) => vec![val.as_ref().into()],
+                    None => {
+                        return Err(CommandParseError::MissingOptionValue(
+                            arg_name(opt_name),
+                            tok.span(),
+                        ))
+                    },
+                },
+            };
+
+            values.insert(opt_name.clone(), value);
+        }
+
+        let mut pos = pos.into_iter();
+
+        for name in &cmd.required {
+            let tok = pos
+                .next()
+                .ok_or_else(|| CommandParseError::MissingRequired(arg_name(name)))?;
+
+            values.insert(name.clone(), vec![tok.as_ref().into()]);
+        }
+
+        for name in &cmd.optional {
+            if let Some(tok) = pos.next() {
+                values.insert(name.clone(), vec![tok.as_ref().into()]);
+            }
+        }
+
+        match cmd.rest {
+            Some((ref name, required)) => {
+                let rest: Vec<String> = pos.map(|t| t.as_ref().into()).collect();
+
+                if rest.is_empty() {
+                    if required {
+                        return Err(CommandParseError::MissingRequired(arg_name(name)));
+                    }
+                } else {
+                    values.insert(name.clone(), rest);
+                }
+            },
+            None => {
+                if let Some(tok) = pos.next() {
+                    return Err(CommandParseError::Trailing(
+                        cmd.id.clone().into(),
+                        tok.as_ref().into(),
+                        tok.span(),
+                    ));
+                }
+            },
+        }
+
+        Ok(DynamicCommand {
+            id: cmd.id.clone(),
+            values,
+        })
+    }
+}
+
+/// A command parsed by a [`DynamicCommandSet`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DynamicCommand {
+    id: String,
+    values: HashMap<String, Vec<String>>,
+}
+
+impl DynamicCommand {
+    /// Get the canonical name of the command
+    #[must_use]
+    pub fn id(&self) -> &str { &self.id }
+
+    /// Get the value of an argument or option by the name it has in the usage
+    /// line, such as `name` or `--count`.  For a rest argument, this is its
+    /// first value.
+    #[must_use]
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.values
+            .get(name)
+            .and_then(|v| v.first())
+            .map(String::as_str)
+    }
+
+    /// Get every value given for an argument or option
+    #[must_use]
+    pub fn get_all(&self, name: &str) -> &[String] {
+        self.values.get(name).map_or(&[], Vec::as_slice)
+    }
+
+    /// Check whether an argument or option was given, which for a flag is
+    /// whether it is set
+    #[must_use]
+    pub fn is_present(&self, name: &str) -> bool { self.values.contains_key(name) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tokenize;
+
+    fn set() -> DynamicCommandSet {
+        DynamicCommandSet::builder()
+            .command("jam <song>", "Play a song", &[])
+            .unwrap()
+            .command("jams [genre]", "List songs", &[])
+            .unwrap()
+            .command(
+                "(add|new) [-f|--force] [--tag <tag>] <name> [args...]",
+                "Add a thing",
+                &[],
+            )
+            .unwrap()
+            .command("alter <name>", "Change a thing", &[])
+            .unwrap()
+            .command("(list all|la)", "List every thing", &[])
+            .unwrap()
+            .command("(list|ls) [page]", "List things", &[])
+            .unwrap()
+            .command("ping", "Check the bot is alive", &[])
+            .unwrap()
+            .build()
+    }
+
+    fn parse(s: &str) -> Result<DynamicCommand, CommandParseError> {
+        set().parse(tokenize(s).unwrap())
+    }
+
+    #[test]
+    fn name_prefix_of_another() {
+        assert_eq!(parse("jam x").unwrap().id(), "jam");
+        assert_eq!(parse("jams").unwrap().id(), "jams");
+
+        // An abbreviation of both prefers the shorter name
+        assert_eq!(parse("ja x").unwrap().id(), "jam");
+    }
+
+    #[test]
+    fn ambiguous_prefix() {
+        match parse("a x") {
+            Err(CommandParseError::BadId(IdParseError::Ambiguous(names, s), span)) => {
+                assert_eq!(names, ["add", "alter"]);
+                assert_eq!(s, "a");
+                assert_eq!(span, Some(0..1));
+            },
+            r => panic!("expected an ambiguous ID, got {:?}", r),
+        }
+
+        assert_eq!(parse("ad x").unwrap().id(), "add");
+        assert_eq!(parse("NEW x").unwrap().id(), "add");
+    }
+
+    #[test]
+    fn multi_word_id() {
+        let cmd = parse("list all").unwrap();
+        assert_eq!(cmd.id(), "list all");
+        assert_eq!(cmd.get("page"), None);
+
+        assert_eq!(parse("la").unwrap().id(), "list all");
+        assert_eq!(parse("list al").unwrap().id(), "list all");
+
+        let cmd = parse("list 2").unwrap();
+        assert_eq!(cmd.id(), "list");
+        assert_eq!(cmd.get("page"), Some("2"));
+    }
+
+    #[test]
+    fn options() {
+        let cmd = parse("add --tag=a=b foo -f bar baz").unwrap();
+        assert_eq!(cmd.get("--tag"), Some("a=b"));
+        assert!(cmd.is_present("--force"));
+        assert_eq!(cmd.get("name"), Some("foo"));
+        assert_eq!(cmd.get_all("args"), ["bar", "baz"]);
+
+        let cmd = parse("add --tag x foo").unwrap();
+        assert_eq!(cmd.get("--tag"), Some("x"));
+        assert!(!cmd.is_present("--force"));
+        assert!(cmd.get_all("args").is_empty());
+
+        // Flags can't be given a value inline
+        assert!(matches!(
+            parse("add --force=yes foo"),
+            Err(CommandParseError::UnknownOption(_, _, Some(_)))
+        ));
+        assert!(matches!(
+            parse("add -f -f foo"),
+            Err(CommandParseError::DuplicateOption(_, Some(_)))
+        ));
+        assert!(matches!(
+            parse("add foo --tag"),
+            Err(CommandParseError::MissingOptionValue(_, Some(_)))
+        ));
+    }
+
+    #[test]
+    fn double_dash_ends_options() {
+        let cmd = parse("add -- -f --tag=x").unwrap();
+        assert!(!cmd.is_present("--force"));
+        assert!(!cmd.is_present("--tag"));
+        assert_eq!(cmd.get("name"), Some("-f"));
+        assert_eq!(cmd.get_all("args"), ["--tag=x"]);
+
+        let cmd = parse("add -f -- --").unwrap();
+        assert!(cmd.is_present("--force"));
+        assert_eq!(cmd.get("name"), Some("--"));
+    }
+
+    #[test]
+    fn trailing_arguments() {
+        match parse("ping  extra more") {
+            Err(CommandParseError::Trailing(id, tok, span)) => {
+                assert_eq!(id, "ping");
+                assert_eq!(tok, "extra");
+                assert_eq!(span, Some(6..11));
+            },
+            r => panic!("expected trailing arguments, got {:?}", r),
+        }
+
+        // Commands without options take option-like words as arguments
+        assert!(matches!(
+            parse("ping --help"),
+            Err(CommandParseError::Trailing(_, _, _))
+        ));
+        assert_eq!(parse("alter --x").unwrap().get("name"), Some("--x"));
+    }
+
+    #[test]
+    fn missing_arguments() {
+        assert!(matches!(parse(""), Err(CommandParseError::NoInput)));
+        assert!(matches!(
+            parse("jam"),
+            Err(CommandParseError::MissingRequired(_))
+        ));
+        assert!(matches!(
+            parse("nope"),
+            Err(CommandParseError::BadId(IdParseError::NoMatch(..), Some(_)))
+        ));
+    }
+}
@@ -4,34 +4,66 @@
 
 //! Create a chatbot command interface using a docopt-like API
 
+mod dynamic;
+pub mod render;
+mod schema;
+mod suggest;
+mod text;
+mod tokenize;
+
 use std::{
+    borrow::Cow,
     convert::Infallible,
     fmt,
     fmt::{Display, Formatter},
+    ops::Range,
     str::FromStr,
 };
 use thiserror::Error;
 
+pub use dynamic::{DynamicCommand, DynamicCommandSet, DynamicCommandSetBuilder, UsageError};
+pub use render::HelpRenderer;
+pub use schema::{ArgumentKind, ArgumentSchema, CommandSchema, CommandSetSchema, Schema};
+pub use text::{check_links, check_references, HelpNode, HelpText, LinkError, ReferenceError};
+pub use tokenize::{join_tokens, quote, tokenize, Spanned, Token, TokenizeError};
+
 /// Error type for failures when parsing a command ID
 #[derive(Error, Debug)]
 pub enum IdParseError {
-    /// No IDs matched the given string
+    /// No IDs matched the given string.  Also holds the names of every
+    /// command it could have been, grouped by command.
     #[error("no ID match for {0:?}")]
-    NoMatch(String, &'static [&'static str]),
+    NoMatch(String, Vec<Vec<Cow<'static, str>>>),
     /// Multiple IDs could match the given string
     ///
     /// Usually a result of specifying too few characters
     #[error("ambiguous ID {0:?}, could be any of {}", .0.join(", "))]
-    Ambiguous(&'static [&'static str], String),
+    Ambiguous(Vec<Cow<'static, str>>, String),
+}
+
+impl IdParseError {
+    /// List up to `limit` IDs the user may have meant, best first.  For an
+    /// unmatched ID, these are the names scoring at least `threshold` out of
+    /// 1 for similarity, where typos on neighboring keys and swapped
+    /// characters count as closer than other mistakes.  Only the closest name
+    /// of each command is suggested.  For an ambiguous ID, these are the names
+    /// it could refer to.
+    #[must_use]
+    pub fn suggestions(&self, limit: usize, threshold: f64) -> Vec<&str> {
+        match self {
+            Self::NoMatch(s, names) => suggest::rank(s, names, limit, threshold),
+            Self::Ambiguous(names, _) => names.iter().map(AsRef::as_ref).take(limit).collect(),
+        }
+    }
 }
 
 /// Identifies an argument to a command
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Debug)]
 pub struct ArgumentName {
     /// The ID of the command
-    pub cmd: &'static str,
+    pub cmd: Cow<'static, str>,
     /// The name of the argument
-    pub arg: &'static str,
+    pub arg: Cow<'static, str>,
 }
 
 impl Display for ArgumentName {
@@ -40,29 +72,105 @@ impl Display for ArgumentName {
     }
 }
 
+/// Error type for an argument outside of the range declared with
+/// `#[docbot(range = "...")]`
+#[derive(Error, Debug)]
+#[error("value must be in the range {0}")]
+pub struct RangeError(pub &'static str);
+
 /// Error type for failures when parsing a command
+///
+/// Where an error can be traced back to a single input token, the error
+/// carries the byte range of that token if the input was [`Spanned`].
 #[derive(Error, Debug)]
 pub enum CommandParseError {
+    /// The input could not be split into tokens
+    #[error("failed to tokenize command")]
+    Tokenize(#[from] TokenizeError),
     /// The iterator returned None immediately
     #[error("no values in command parse input")]
     NoInput,
     /// The command ID could not be parsed
     #[error("failed to parse command ID")]
-    BadId(#[from] IdParseError),
+    BadId(#[source] IdParseError, Option<Range<usize>>),
     /// A required argument was missing
     #[error("missing required argument {0}")]
     MissingRequired(ArgumentName),
     /// `TryFrom::try_from` failed for an argument
     #[error("failed to convert argument {0} from a string")]
-    BadConvert(ArgumentName, anyhow::Error),
+    BadConvert(ArgumentName, Option<Range<usize>>, anyhow::Error),
     /// Extra arguments were provided
     #[error("trailing argument {1:?} of {0:?}")]
-    Trailing(&'static str, String),
+    Trailing(Cow<'static, str>, String, Option<Range<usize>>),
+    /// An option was given that the command does not accept
+    #[error("unknown option {1:?} of {0:?}")]
+    UnknownOption(Cow<'static, str>, String, Option<Range<usize>>),
+    /// An option was given more than once
+    #[error("duplicate option {0}")]
+    DuplicateOption(ArgumentName, Option<Range<usize>>),
+    /// An option requiring a value was given without one
+    #[error("missing value for option {0}")]
+    MissingOptionValue(ArgumentName, Option<Range<usize>>),
     /// A subcommand failed to parse
     #[error("failed to parse subcommand {0:?}")]
     Subcommand(&'static str, Box<CommandParseError>),
 }
 
+/// Error type for failures when resolving a help topic from a path of command
+/// names
+#[derive(Error, Debug)]
+pub enum HelpPathError {
+    /// A name in the path did not match a command
+    #[error("failed to parse command ID")]
+    BadId(#[source] IdParseError),
+    /// A name was given after a command with no subcommands
+    #[error("command {0:?} has no subcommands, got {1:?}")]
+    NoSubcommands(&'static str, String),
+    /// Resolving the rest of the path under a subcommand failed
+    #[error("failed to resolve help for subcommand {0:?}")]
+    Subcommand(&'static str, Box<HelpPathError>),
+}
+
+impl CommandParseError {
+    /// Get the byte range of the input token responsible for this error, if
+    /// known
+    #[must_use]
+    pub fn span(&self) -> Option<Range<usize>> {
+        match self {
+            Self::Tokenize(TokenizeError::UnterminatedQuote(s)) => Some(s.clone()),
+            Self::BadId(_, s)
+            | Self::BadConvert(_, s, _)
+            | Self::Trailing(_, _, s)
+            | Self::UnknownOption(_, _, s)
+            | Self::DuplicateOption(_, s)
+            | Self::MissingOptionValue(_, s) => s.clone(),
+            Self::Subcommand(_, e) => e.span(),
+            Self::NoInput | Self::MissingRequired(_) => None,
+        }
+    }
+
+    /// List up to `limit` commands the user may have meant if this error was
+    /// caused by an unrecognized command ID, as described in
+    /// [`IdParseError::suggestions`].  Suggestions for a subcommand include
+    /// the IDs of the commands leading to it, such as `roles add`.
+    #[must_use]
+    pub fn suggestions(&self, limit: usize, threshold: f64) -> Vec<String> {
+        match self {
+            Self::BadId(e, _) => e
+                .suggestions(limit, threshold)
+                .into_iter()
+                .map(Into::into)
+                .collect(),
+            Self::Subcommand(id, e) => e
+                .suggestions(limit, threshold)
+                .into_iter()
+                .map(|s| format!("{} {}", id, s))
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+}
+
 impl From<Infallible> for CommandParseError {
     fn from(_: Infallible) -> CommandParseError { unreachable!() }
 }
@@ -75,88 +183,728 @@ pub trait Command: Sized {
     /// The type of the command ID
     type Id;
 
-    /// Try to parse a sequence of arguments as a command
+    /// The context arguments are parsed with by [`parse_with`](Self::parse_with),
+    /// set with `#[docbot(context = "...")]`.  Defaults to `()`.
+    type Context: ?Sized;
+
+    /// Try to parse a sequence of arguments as a command, without a context.
+    /// Arguments are converted with [`ParseWith<()>`](ParseWith) if their
+    /// type implements it, or [`FromStr`] otherwise.
+    /// # Errors
+    /// Should return an error for syntax or command-not-found errors, or for
+    /// any errors while parsing arguments.
+    fn parse<I: IntoIterator<Item = S>, S: Spanned>(iter: I) -> Result<Self, CommandParseError>;
+
+    /// Try to parse a sequence of arguments as a command.  Arguments are
+    /// converted with [`ParseWith<Self::Context>`](ParseWith) if their type
+    /// implements it, or [`FromStr`] otherwise.
     /// # Errors
     /// Should return an error for syntax or command-not-found errors, or for
     /// any errors while parsing arguments.
-    fn parse<I: IntoIterator<Item = S>, S: AsRef<str>>(iter: I) -> Result<Self, CommandParseError>;
+    fn parse_with<I: IntoIterator<Item = S>, S: Spanned>(
+        ctx: &Self::Context,
+        iter: I,
+    ) -> Result<Self, CommandParseError>;
+
+    /// List the possible values of the last token of a partial command.
+    ///
+    /// The last item of `tokens` is treated as the token being typed, so an
+    /// empty string should be passed as the last item to list candidates for
+    /// a new token.  Returns an empty list if no suggestions can be made.
+    fn complete<I: IntoIterator<Item = S>, S: AsRef<str>>(tokens: I) -> Vec<String>;
 
     /// Return an ID uniquely describing the base type of this command.
     fn id(&self) -> Self::Id;
+
+    /// Convert this command back into arguments, using the canonical ID of
+    /// each command and subcommand.  Parsing the result gives back an equal
+    /// command, and [`join_tokens`] can be used to write it as a string.  The
+    /// derive macro also implements [`Display`] this way.
+    fn to_tokens(&self) -> Vec<String>;
+
+    /// Get what to use instead of this command, if it or the subcommand it
+    /// runs is deprecated.  Deprecated commands still parse, so this can be
+    /// shown to the user as a warning.
+    fn deprecation(&self) -> Option<&'static str>;
+}
+
+/// Conversion of a command argument from a string using a context, such as a
+/// cache of names the argument may refer to
+pub trait ParseWith<Ctx: ?Sized>: Sized {
+    /// The type of error returned if conversion fails
+    type Err: Into<Anyhow>;
+
+    /// Try to convert a string into a value of this type
+    /// # Errors
+    /// Should return an error if the string does not describe a valid value.
+    fn parse_with(ctx: &Ctx, s: &str) -> Result<Self, Self::Err>;
 }
 
 /// A command ID, convertible to and from a string
 pub trait CommandId: FromStr + Display {
-    /// List all possible valid names that can be parsed, including aliases
+    /// List all possible valid names that can be parsed, including aliases.
+    /// Names spanning several words have them separated by single spaces.
     fn names() -> &'static [&'static str];
 
+    /// List the canonical name of each ID, without aliases
+    fn canonical_names() -> &'static [&'static str];
+
+    /// List the names of each ID, with the canonical name first
+    fn name_groups() -> &'static [&'static [&'static str]];
+
     /// Get the canonical name for an ID
     fn to_str(&self) -> &'static str;
 }
 
+/// Usage description for a command option, such as `-f`, `--flag`, or
+/// `--name <value>`
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct OptionUsage {
+    /// The single-character name of the option, without the leading `-`
+    pub short: Option<char>,
+    /// The long name of the option, without the leading `--`
+    pub long: Option<Cow<'static, str>>,
+    /// The name of the value the option takes, or `None` for a flag
+    pub value: Option<Cow<'static, str>>,
+}
+
 /// Usage description for an argument
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct ArgumentUsage {
     /// The name of the argument
-    pub name: &'static str,
+    pub name: Cow<'static, str>,
     /// Whether the argument is required
     pub is_required: bool,
     /// Whether the argument is a rest parameter
     pub is_rest: bool,
+    /// The names and value of the argument if it is an option rather than a
+    /// positional argument
+    pub option: Option<OptionUsage>,
 }
 
 /// Usage description for a command
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct CommandUsage {
     /// The possible IDs of this command
-    pub ids: &'static [&'static str],
+    pub ids: Cow<'static, [Cow<'static, str>]>,
     /// Usage descriptions for this command's arguments
-    pub args: &'static [ArgumentUsage],
+    pub args: Cow<'static, [ArgumentUsage]>,
     /// A short description
-    pub desc: &'static str,
+    pub desc: HelpText,
+    /// Whether this command is left out of command lists
+    pub hidden: bool,
+    /// What to use instead of this command, if it is deprecated
+    pub deprecated: Option<&'static str>,
+    /// The permission needed to run this command, if any.  What permissions
+    /// exist is up to the application.
+    pub requires: Option<&'static str>,
 }
 
 /// Detailed description of a command argument
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct ArgumentDesc {
     /// The name of the argument
-    pub name: &'static str,
+    pub name: Cow<'static, str>,
     /// Whether the argument is required
     pub is_required: bool,
+    /// The names and value of the argument if it is an option rather than a
+    /// positional argument
+    pub option: Option<OptionUsage>,
     /// A detailed description of the argument
-    pub desc: &'static str,
+    pub desc: HelpText,
+    /// The kind of value the argument accepts, or `None` for a flag
+    pub value: Option<ValueHintFn>,
+}
+
+/// The kind of value an argument accepts, for display in help
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum ValueHint {
+    /// One of a fixed set of names, such as the ID of a command
+    Choice(&'static [&'static str]),
+    /// A value in a format given by a `#[docbot(hint = "...")]` attribute,
+    /// such as `user mention` or `duration`
+    Format(&'static str),
+    /// A value of the given Rust type, as named by [`std::any::type_name`]
+    Type(&'static str),
+}
+
+/// Strip the module paths from a type name
+fn short_type_name(name: &str) -> String {
+    let mut ret = String::new();
+
+    for (i, part) in name.split("::").enumerate() {
+        if i != 0 {
+            let len = ret
+                .trim_end_matches(|c: char| c.is_alphanumeric() || c == '_')
+                .len();
+            ret.truncate(len);
+        }
+
+        ret.push_str(part);
+    }
+
+    ret
+}
+
+impl Display for ValueHint {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::Choice(names) => write!(f, "one of: {}", names.join(", ")),
+            Self::Format(hint) => f.write_str(hint),
+            Self::Type(name) => f.write_str(&short_type_name(name)),
+        }
+    }
+}
+
+/// Produces the [`ValueHint`] for an argument.  The hint is computed on
+/// request, since the names accepted by an argument's type are not known when
+/// its help topic is constructed.
+#[derive(Clone, Copy)]
+pub struct ValueHintFn(pub fn() -> ValueHint);
+
+impl ValueHintFn {
+    /// Compute the hint
+    #[must_use]
+    pub fn get(&self) -> ValueHint { (self.0)() }
+}
+
+impl fmt::Debug for ValueHintFn {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.debug_tuple("ValueHintFn").field(&self.get()).finish()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for ValueHintFn {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.get().serialize(serializer)
+    }
 }
 
 /// Detailed description of a command
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct CommandDesc {
     /// A detailed summary of the command's behavior
-    pub summary: Option<&'static str>,
+    pub summary: Option<HelpText>,
     /// Descriptions of the command's arguments
-    pub args: &'static [ArgumentDesc],
-    /// Example uses of the command
-    pub examples: Option<&'static str>,
+    pub args: Cow<'static, [ArgumentDesc]>,
+    /// Example uses of the command, one invocation per item
+    pub examples: &'static [&'static str],
+    /// Any further sections of the command's documentation, in the order they
+    /// were written
+    pub sections: &'static [DocSection],
+}
+
+/// An additional named section of a command's documentation
+#[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum DocSection {
+    /// A section of text under the given heading, such as `Notes`
+    Text(&'static str, HelpText),
+    /// A `See also` section listing the paths of related commands
+    SeeAlso(&'static [&'static str]),
+}
+
+impl DocSection {
+    /// Get the heading of this section
+    #[must_use]
+    pub fn title(&self) -> &'static str {
+        match self {
+            Self::Text(title, _) => title,
+            Self::SeeAlso(_) => "See also",
+        }
+    }
 }
 
 /// A generic help topic
 #[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize),
+    serde(rename_all = "snake_case")
+)]
 pub enum HelpTopic {
     /// A help topic referring to a single command
     Command(CommandUsage, CommandDesc),
     /// A help topic referring to a set of commands, prefaced by an optional
     /// summary
-    CommandSet(Option<&'static str>, &'static [CommandUsage]),
+    CommandSet(Option<HelpText>, Cow<'static, [CommandUsage]>),
     /// A custom help topic
-    Custom(&'static str),
+    Custom(HelpText),
+}
+
+impl HelpTopic {
+    /// List the commands of a command set that should be shown to a user,
+    /// leaving out hidden commands and commands requiring a permission for
+    /// which `allowed` returns false.  Other topics list no commands.
+    pub fn visible_commands(&self, allowed: impl Fn(&str) -> bool) -> Vec<&CommandUsage> {
+        match self {
+            Self::CommandSet(_, cmds) => cmds
+                .iter()
+                .filter(|c| !c.hidden && c.requires.map_or(true, &allowed))
+                .collect(),
+            Self::Command(..) | Self::Custom(_) => Vec::new(),
+        }
+    }
 }
 
 /// A command with associated help topics
 pub trait Help: Command {
     /// Retrieve the help topic corresponding to the given ID.
     fn help(topic: Option<Self::Id>) -> &'static HelpTopic;
+
+    /// List the locales this command has translated help for, as lowercase
+    /// codes such as `fr` or `pt-br`.  Does not include subcommands.
+    fn help_locales() -> &'static [&'static str];
+
+    /// Retrieve the help topic corresponding to the given ID, translated for
+    /// the given locale.  Anything without a translation, including all help
+    /// for an unknown locale, is given in the original language.
+    fn help_in(topic: Option<Self::Id>, locale: &str) -> &'static HelpTopic;
+
+    /// Retrieve the help topic for a path of command names, such as
+    /// `["roles", "add"]`, following subcommands.  An empty path gives the
+    /// general help topic.
+    /// # Errors
+    /// Returns an error if a name does not match a command, or if a name
+    /// follows a command with no subcommands.
+    fn help_path<S: AsRef<str>>(path: &[S]) -> Result<&'static HelpTopic, HelpPathError> {
+        Self::help_path_in(path, "")
+    }
+
+    /// Retrieve the help topic for a path of command names, translated for the
+    /// given locale.  See [`help_path`](Self::help_path) and
+    /// [`help_in`](Self::help_in).
+    /// # Errors
+    /// Returns an error if a name does not match a command, or if a name
+    /// follows a command with no subcommands.
+    fn help_path_in<S: AsRef<str>>(
+        path: &[S],
+        locale: &str,
+    ) -> Result<&'static HelpTopic, HelpPathError>;
+}
+
+/// Find the best match for a locale in a list of available locales, such as
+/// those given by [`Help::help_locales`].  An exact match is preferred,
+/// followed by a locale of the same language (e.g. `pt` for `pt-BR`).
+/// Comparison ignores case and treats `_` the same as `-`.
+#[must_use]
+pub fn match_locale(locale: &str, available: &[&str]) -> Option<usize> {
+    let locale = locale.replace('_', "-").to_lowercase();
+    let lang = locale.split('-').next().unwrap_or_default();
+
+    if lang.is_empty() {
+        return None;
+    }
+
+    let normalize = |l: &str| l.replace('_', "-").to_lowercase();
+
+    available
+        .iter()
+        .position(|l| normalize(l) == locale)
+        .or_else(|| {
+            available
+                .iter()
+                .position(|l| normalize(l).split('-').next() == Some(lang))
+        })
+}
+
+/// Match a command ID against the start of a list of words, the same way a
+/// command parser would.  Returns the number of words the ID spans and whether
+/// it was abbreviated (i.e. did not spell out any of its names in full), or
+/// `None` if no ID matches.
+#[must_use]
+pub fn match_id<T: CommandId + FromStr<Err = IdParseError>, S: AsRef<str>>(
+    words: &[S],
+) -> Option<(usize, bool)>
+{
+    if words.is_empty() {
+        return None;
+    }
+
+    let (_, len) = __private::parse_id::<T, S>(words).ok()?;
+    let joined = words[..len]
+        .iter()
+        .map(|w| w.as_ref().to_lowercase())
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    Some((len, !T::names().contains(&joined.as_str())))
+}
+
+/// Helpers for code generated by the derive macro.  Not public API.
+#[doc(hidden)]
+pub mod __private {
+    use super::{
+        Anyhow, ArgumentKind, ArgumentSchema, CommandId, CommandSchema, CommandSetSchema,
+        HelpTopic, IdParseError, OptionUsage, ParseWith, ValueHint,
+    };
+    use std::{borrow::Cow, error::Error, marker::PhantomData, str::FromStr};
+
+    /// Returns true if a token should be treated as an option rather than a
+    /// positional argument.  Negative numbers and a lone `-` are positional.
+    #[must_use]
+    pub fn is_option(s: &str) -> bool {
+        s.len() > 1
+            && s.starts_with('-')
+            && !s[1..].starts_with(|c: char| c.is_ascii_digit() || c == '.')
+    }
+
+    /// Assemble the arguments of a command from its ID, its options, its
+    /// positional arguments, and the arguments of its subcommand.  `options`
+    /// is `None` if the command takes no options, otherwise positional
+    /// arguments that look like options are preceded by `--`.
+    #[must_use]
+    pub fn command_tokens(
+        id: &str,
+        options: Option<Vec<String>>,
+        positional: Vec<String>,
+        subcommand: Vec<String>,
+    ) -> Vec<String>
+    {
+        let mut ret: Vec<String> = id.split(' ').map(Into::into).collect();
+
+        if let Some(options) = options {
+            ret.extend(options);
+
+            if positional.iter().any(|p| p == "--" || is_option(p)) {
+                ret.push("--".into());
+            }
+        }
+
+        ret.extend(positional);
+        ret.extend(subcommand);
+
+        ret
+    }
+
+    /// The argument a partial token is being typed into
+    #[derive(Debug)]
+    pub enum Partial<'a> {
+        /// The token is an option name, with the given candidates
+        Options(Vec<String>),
+        /// The token is the value of the option at the given index, to be
+        /// prefixed with the given string, with the given partial value
+        Value(usize, &'a str, &'a str),
+        /// The token is the positional argument at the given index
+        Positional(usize, &'a str),
+        /// The tokens starting at the given index belong to a subcommand
+        Subcommand(usize),
+    }
+
+    fn option_names(opt: &OptionUsage) -> impl Iterator<Item = String> {
+        opt.short
+            .map(|s| format!("-{}", s))
+            .into_iter()
+            .chain(opt.long.as_ref().map(|l| format!("--{}", l)))
+    }
+
+    fn find_option(options: &[OptionUsage], name: &str) -> Option<usize> {
+        options
+            .iter()
+            .position(|o| option_names(o).any(|n| n == name))
+    }
+
+    /// Determine which argument the last token of a partial command fills,
+    /// mirroring the option scanning performed by the generated parser.
+    /// `subcommand` is the positional index of the subcommand, if any.
+    pub fn complete_slot<'a, S: AsRef<str>>(
+        done: &[S],
+        last: &'a str,
+        options: &[OptionUsage],
+        subcommand: Option<usize>,
+    ) -> Partial<'a>
+    {
+        let mut seen = vec![false; options.len()];
+        let mut pos = 0;
+        let mut only_pos = false;
+        let mut iter = done.iter().map(AsRef::as_ref).enumerate();
+
+        while let Some((i, tok)) = iter.next() {
+            if only_pos || !is_option(tok) {
+                if subcommand == Some(pos) {
+                    return Partial::Subcommand(i);
+                }
+
+                pos += 1;
+                continue;
+            }
+
+            if tok == "--" {
+                only_pos = true;
+                continue;
+            }
+
+            let (name, inline) = match tok.find('=') {
+                Some(i) if tok.starts_with("--") => (&tok[..i], Some(())),
+                _ => (tok, None),
+            };
+
+            if let Some(idx) = find_option(options, name) {
+                seen[idx] = true;
+
+                if options[idx].value.is_some() && inline.is_none() && iter.next().is_none() {
+                    return Partial::Value(idx, "", last);
+                }
+            }
+        }
+
+        if !only_pos && (last == "-" || is_option(last)) {
+            if let Some(i) = last.find('=').filter(|_| last.starts_with("--")) {
+                return match find_option(options, &last[..i]) {
+                    Some(idx) if options[idx].value.is_some() => {
+                        Partial::Value(idx, &last[..=i], &last[i + 1..])
+                    },
+                    _ => Partial::Options(Vec::new()),
+                };
+            }
+
+            return Partial::Options(
+                options
+                    .iter()
+                    .zip(seen)
+                    .filter(|(_, s)| !s)
+                    .flat_map(|(o, _)| option_names(o))
+                    .filter(|n| n.starts_with(last))
+                    .collect(),
+            );
+        }
+
+        if subcommand == Some(pos) {
+            Partial::Subcommand(done.len())
+        } else {
+            Partial::Positional(pos, last)
+        }
+    }
+
+    /// Parse a command ID from the start of a list of words.  IDs may span
+    /// several words, in which case the longest match is taken.  Returns the
+    /// ID and the number of words it took.
+    /// # Errors
+    /// Returns the error from parsing the first word alone if no longer ID
+    /// matches.
+    ///
+    /// # Panics
+    /// Panics if `words` is empty.
+    pub fn parse_id<T: CommandId + FromStr<Err = IdParseError>, S: AsRef<str>>(
+        words: &[S],
+    ) -> Result<(T, usize), IdParseError>
+    {
+        let max = T::names()
+            .iter()
+            .map(|n| n.split(' ').count())
+            .max()
+            .unwrap_or(1)
+            .min(words.len());
+
+        for len in (2..=max).rev() {
+            let joined = words[..len]
+                .iter()
+                .map(AsRef::as_ref)
+                .collect::<Vec<_>>()
+                .join(" ");
+
+            if let Ok(id) = joined.parse() {
+                return Ok((id, len));
+            }
+        }
+
+        words[0].as_ref().parse().map(|id| (id, 1))
+    }
+
+    /// List the next word of each name of a command ID starting with the
+    /// given words, the last of which may be partial
+    #[must_use]
+    pub fn complete_id<T: CommandId, S: AsRef<str>>(done: &[S], last: &str) -> Vec<String> {
+        let mut prefix = String::new();
+
+        for word in done {
+            prefix.push_str(&word.as_ref().to_lowercase());
+            prefix.push(' ');
+        }
+
+        prefix.push_str(&last.to_lowercase());
+
+        let mut ret: Vec<String> = Vec::new();
+
+        for word in T::names()
+            .iter()
+            .filter(|n| n.starts_with(&prefix))
+            .filter_map(|n| n.split(' ').nth(done.len()))
+        {
+            if !ret.iter().any(|r| r == word) {
+                ret.push(word.into());
+            }
+        }
+
+        ret
+    }
+
+    /// Prepend a string to a list of completions
+    #[must_use]
+    pub fn with_lead(lead: &str, completions: Vec<String>) -> Vec<String> {
+        if lead.is_empty() {
+            return completions;
+        }
+
+        completions
+            .into_iter()
+            .map(|c| format!("{}{}", lead, c))
+            .collect()
+    }
+
+    /// Marker used to select [`IdArg`] for argument types that are command
+    /// IDs, falling back to [`OtherArg`] for anything else
+    #[derive(Debug)]
+    pub struct ArgType<T>(PhantomData<T>);
+
+    impl<T> ArgType<T> {
+        #[must_use]
+        pub fn new() -> Self { Self(PhantomData) }
+    }
+
+    impl<T> Default for ArgType<T> {
+        fn default() -> Self { Self::new() }
+    }
+
+    pub trait IdArg {
+        fn complete_arg(&self, prefix: &str) -> Vec<String>;
+
+        fn arg_kind(&self) -> ArgumentKind;
+
+        fn value_hint(&self) -> ValueHint;
+    }
+
+    impl<T: CommandId> IdArg for ArgType<T> {
+        fn complete_arg(&self, prefix: &str) -> Vec<String> { complete_id::<T, &str>(&[], prefix) }
+
+        fn arg_kind(&self) -> ArgumentKind { ArgumentKind::Choice(T::canonical_names().to_vec()) }
+
+        fn value_hint(&self) -> ValueHint { ValueHint::Choice(T::canonical_names()) }
+    }
+
+    pub trait OtherArg {
+        fn complete_arg(&self, _prefix: &str) -> Vec<String> { Vec::new() }
+
+        fn arg_kind(&self) -> ArgumentKind { ArgumentKind::Text }
+
+        fn value_hint(&self) -> ValueHint;
+    }
+
+    impl<T> OtherArg for &ArgType<T> {
+        fn value_hint(&self) -> ValueHint { ValueHint::Type(std::any::type_name::<T>()) }
+    }
+
+    /// Marker used to select [`ContextArg`] for argument types that can be
+    /// parsed with a context of type `C`, falling back to [`PlainArg`] for
+    /// types that implement [`FromStr`]
+    #[derive(Debug)]
+    pub struct ArgParse<T, C: ?Sized>(PhantomData<fn(&C) -> T>);
+
+    impl<T, C: ?Sized> ArgParse<T, C> {
+        #[must_use]
+        pub fn new() -> Self { Self(PhantomData) }
+    }
+
+    impl<T, C: ?Sized> Default for ArgParse<T, C> {
+        fn default() -> Self { Self::new() }
+    }
+
+    pub trait ContextArg<T, C: ?Sized> {
+        fn parse_arg(&self, ctx: &C, s: &str) -> Result<T, Anyhow>;
+    }
+
+    impl<T: ParseWith<C>, C: ?Sized> ContextArg<T, C> for ArgParse<T, C> {
+        fn parse_arg(&self, ctx: &C, s: &str) -> Result<T, Anyhow> {
+            T::parse_with(ctx, s).map_err(Into::into)
+        }
+    }
+
+    pub trait PlainArg<T, C: ?Sized> {
+        fn parse_arg(&self, ctx: &C, s: &str) -> Result<T, Anyhow>;
+    }
+
+    impl<T: FromStr, C: ?Sized> PlainArg<T, C> for &ArgParse<T, C>
+    where
+        T::Err: Error + Send + Sync + 'static,
+    {
+        fn parse_arg(&self, _: &C, s: &str) -> Result<T, Anyhow> { s.parse().map_err(Into::into) }
+    }
+
+    /// Construct the error for a name matching no ID of the given type
+    #[must_use]
+    pub fn no_match<T: CommandId>(s: &str) -> IdParseError {
+        IdParseError::NoMatch(
+            s.into(),
+            T::name_groups()
+                .iter()
+                .map(|g| g.iter().map(|n| Cow::Borrowed(*n)).collect())
+                .collect(),
+        )
+    }
+
+    /// Assemble the schema for a command from its help topic and the kinds of
+    /// its arguments
+    #[must_use]
+    pub fn command_schema(topic: &'static HelpTopic, kinds: Vec<ArgumentKind>) -> CommandSchema {
+        let (usage, desc) = match topic {
+            HelpTopic::Command(usage, desc) => (usage, desc),
+            _ => unreachable!("command help topic was not a command"),
+        };
+
+        CommandSchema {
+            usage,
+            desc,
+            args: usage
+                .args
+                .iter()
+                .zip(kinds)
+                .map(|(usage, kind)| ArgumentSchema {
+                    usage,
+                    desc: desc
+                        .args
+                        .iter()
+                        .find(|d| d.name == usage.name)
+                        .map(|d| d.desc.clone()),
+                    kind,
+                })
+                .collect(),
+        }
+    }
+
+    /// Assemble the schema for a command set from its general help topic
+    #[must_use]
+    pub fn command_set_schema(
+        topic: &'static HelpTopic,
+        commands: Vec<CommandSchema>,
+    ) -> CommandSetSchema
+    {
+        CommandSetSchema {
+            summary: match topic {
+                HelpTopic::Command(_, desc) => desc.summary.clone(),
+                HelpTopic::CommandSet(summary, _) => summary.clone(),
+                HelpTopic::Custom(_) => None,
+            },
+            commands,
+        }
+    }
 }
 
 /// Common traits and types used with this crate
 pub mod prelude {
-    pub use super::{Command, CommandId, Docbot, Help};
+    pub use super::{Command, CommandId, Docbot, Help, ParseWith, Schema};
 }
@@ -0,0 +1,516 @@
+//! Formatting of help topics for display
+
+use crate::{
+    ArgumentDesc, ArgumentKind, ArgumentUsage, CommandDesc, CommandSetSchema, CommandUsage,
+    DocSection, HelpNode, HelpText, HelpTopic, OptionUsage, ValueHint,
+};
+use std::fmt::Write;
+
+/// Formats help topics for display
+pub trait HelpRenderer {
+    /// The rendered form of a help topic
+    type Output;
+
+    /// Render the help topic for a single command
+    fn command(&self, usage: &CommandUsage, desc: &CommandDesc) -> Self::Output;
+
+    /// Render a list of commands, prefaced by an optional summary
+    fn command_set(&self, summary: Option<&HelpText>, commands: &[&CommandUsage]) -> Self::Output;
+
+    /// Render a custom help topic
+    fn custom(&self, text: &HelpText) -> Self::Output;
+
+    /// Render a help topic, listing every command not marked hidden
+    fn render(&self, topic: &HelpTopic) -> Self::Output { self.render_visible(topic, &|_| true) }
+
+    /// Render a help topic, listing only the commands returned by
+    /// [`HelpTopic::visible_commands`] for the given permission check
+    fn render_visible(&self, topic: &HelpTopic, allowed: &dyn Fn(&str) -> bool) -> Self::Output {
+        match topic {
+            HelpTopic::Command(usage, desc) => self.command(usage, desc),
+            HelpTopic::CommandSet(summary, _) => {
+                self.command_set(summary.as_ref(), &topic.visible_commands(allowed))
+            },
+            HelpTopic::Custom(text) => self.custom(text),
+        }
+    }
+}
+
+/// Render every command in a command tree not marked hidden, paired with the
+/// input naming it from the root, such as `roles add`.  Useful for generating
+/// a command reference.
+pub fn reference<R: HelpRenderer>(
+    renderer: &R,
+    schema: &CommandSetSchema,
+) -> Vec<(String, R::Output)>
+{
+    fn visit<R: HelpRenderer>(
+        renderer: &R,
+        schema: &CommandSetSchema,
+        path: &str,
+        out: &mut Vec<(String, R::Output)>,
+    ) {
+        for cmd in schema.commands.iter().filter(|c| !c.usage.hidden) {
+            let path = if path.is_empty() {
+                cmd.usage.ids[0].to_string()
+            } else {
+                format!("{} {}", path, cmd.usage.ids[0])
+            };
+
+            out.push((path.clone(), renderer.command(cmd.usage, cmd.desc)));
+
+            for arg in &cmd.args {
+                if let ArgumentKind::Subcommand(ref set) = arg.kind {
+                    visit(renderer, set, &path, out);
+                }
+            }
+        }
+    }
+
+    let mut ret = Vec::new();
+    visit(renderer, schema, "", &mut ret);
+
+    ret
+}
+
+/// Format an option as it appears in a usage line, e.g. `-f|--file <path>`,
+/// with its names joined by `sep`
+#[must_use]
+pub fn option_usage(usage: &OptionUsage, sep: &str) -> String {
+    let mut ret: Vec<_> = usage
+        .short
+        .map(|s| format!("-{}", s))
+        .into_iter()
+        .chain(usage.long.as_ref().map(|l| format!("--{}", l)))
+        .collect();
+
+    if let (Some(value), Some(last)) = (&usage.value, ret.last_mut()) {
+        write!(last, " <{}>", value).unwrap();
+    }
+
+    ret.join(sep)
+}
+
+/// Format an argument as it appears in a usage line, e.g. `<name>`,
+/// `[args...]`, or `[-f|--force]`
+#[must_use]
+pub fn argument_usage(usage: &ArgumentUsage) -> String {
+    if let Some(ref opt) = usage.option {
+        return format!("[{}]", option_usage(opt, "|"));
+    }
+
+    let (open, close) = if usage.is_required {
+        ('<', '>')
+    } else {
+        ('[', ']')
+    };
+
+    format!(
+        "{}{}{}{}",
+        open,
+        usage.name,
+        if usage.is_rest { "..." } else { "" },
+        close
+    )
+}
+
+/// Format the IDs of a command as they appear in a usage line, e.g. `add` or
+/// `(remove|rm)`
+#[must_use]
+pub fn ids_usage<S: AsRef<str>>(ids: &[S]) -> String {
+    if ids.len() == 1 && !ids[0].as_ref().contains(char::is_whitespace) {
+        ids[0].as_ref().into()
+    } else {
+        format!(
+            "({})",
+            ids.iter().map(AsRef::as_ref).collect::<Vec<_>>().join("|")
+        )
+    }
+}
+
+/// Format the usage line of a command, as it would be written in its doc
+/// comment
+#[must_use]
+pub fn usage_line(usage: &CommandUsage) -> String {
+    let mut ret = ids_usage(&usage.ids);
+
+    for arg in usage.args.iter() {
+        ret.push(' ');
+        ret.push_str(&argument_usage(arg));
+    }
+
+    ret
+}
+
+/// Format the name of an argument for a list of descriptions, e.g. `name` or
+/// `-f, --file <path>`
+#[must_use]
+pub fn argument_name(desc: &ArgumentDesc) -> String {
+    desc.option
+        .as_ref()
+        .map_or_else(|| desc.name.to_string(), |o| option_usage(o, ", "))
+}
+
+/// Get the value hint of an argument worth showing to users.  Rust type names
+/// mean nothing to users, so only names and declared formats are returned.
+#[must_use]
+pub fn user_hint(desc: &ArgumentDesc) -> Option<ValueHint> {
+    match desc.value.map(|v| v.get()) {
+        Some(ValueHint::Type(_)) | None => None,
+        hint => hint,
+    }
+}
+
+/// Render help text, formatting each link with the given function
+pub fn render_text(text: &HelpText, link: impl Fn(&str) -> String) -> String {
+    let mut ret = String::new();
+
+    for node in text.0.iter() {
+        match node {
+            HelpNode::Text(s) => ret.push_str(s),
+            HelpNode::Link(l) => ret.push_str(&link(l)),
+        }
+    }
+
+    ret
+}
+
+/// Renders help as plain text, in the style of a command-line `--help`
+#[derive(Debug, Clone, Default)]
+pub struct PlainText {
+    /// Prepended to commands referenced by help text, e.g. `!`
+    pub prefix: String,
+}
+
+impl PlainText {
+    fn text(&self, text: &HelpText) -> String {
+        render_text(text, |l| format!("'{}{}'", self.prefix, l))
+    }
+}
+
+impl HelpRenderer for PlainText {
+    type Output = String;
+
+    fn command(&self, usage: &CommandUsage, desc: &CommandDesc) -> String {
+        let mut ret = format!(
+            "Usage: {}{}\n{}\n",
+            self.prefix,
+            usage_line(usage),
+            self.text(&usage.desc)
+        );
+
+        if let Some(ref summary) = desc.summary {
+            write!(ret, "\n{}\n", self.text(summary)).unwrap();
+        }
+
+        if !desc.args.is_empty() {
+            ret.push_str("\nArguments:\n");
+
+            for arg in desc.args.iter() {
+                write!(ret, "  {}", argument_name(arg)).unwrap();
+
+                if !arg.is_required {
+                    ret.push_str(" (optional)");
+                }
+
+                write!(ret, ": {}", self.text(&arg.desc)).unwrap();
+
+                if let Some(hint) = user_hint(arg) {
+                    write!(ret, " ({})", hint).unwrap();
+                }
+
+                ret.push('\n');
+            }
+        }
+
+        if !desc.examples.is_empty() {
+            ret.push_str("\nExamples:\n");
+
+            for example in desc.examples {
+                writeln!(ret, "  {}{}", self.prefix, example).unwrap();
+            }
+        }
+
+        for section in desc.sections {
+            write!(ret, "\n{}:\n", section.title()).unwrap();
+
+            match section {
+                DocSection::Text(_, text) => writeln!(ret, "{}", self.text(text)).unwrap(),
+                DocSection::SeeAlso(refs) => {
+                    for cmd in *refs {
+                        writeln!(ret, "  {}{}", self.prefix, cmd).unwrap();
+                    }
+                },
+            }
+        }
+
+        ret
+    }
+
+    fn command_set(&self, summary: Option<&HelpText>, commands: &[&CommandUsage]) -> String {
+        let mut ret = String::new();
+
+        if let Some(summary) = summary {
+            write!(ret, "{}\n\n", self.text(summary)).unwrap();
+        }
+
+        ret.push_str("Commands:\n");
+
+        for cmd in commands {
+            write!(ret, "  {}\n    {}\n", usage_line(cmd), self.text(&cmd.desc)).unwrap();
+        }
+
+        ret
+    }
+
+    fn custom(&self, text: &HelpText) -> String { format!("{}\n", self.text(text)) }
+}
+
+/// Renders help as Markdown
+#[derive(Debug, Clone, Default)]
+pub struct Markdown {
+    /// Prepended to commands referenced by help text, e.g. `!`
+    pub prefix: String,
+}
+
+impl Markdown {
+    fn text(&self, text: &HelpText) -> String {
+        render_text(text, |l| format!("`{}{}`", self.prefix, l))
+    }
+}
+
+impl HelpRenderer for Markdown {
+    type Output = String;
+
+    fn command(&self, usage: &CommandUsage, desc: &CommandDesc) -> String {
+        let mut ret = format!(
+            "**Usage:** `{}{}`\n\n{}\n",
+            self.prefix,
+            usage_line(usage),
+            self.text(&usage.desc)
+        );
+
+        if let Some(ref summary) = desc.summary {
+            write!(ret, "\n{}\n", self.text(summary)).unwrap();
+        }
+
+        if !desc.args.is_empty() {
+            ret.push_str("\n### Arguments\n\n");
+
+            for arg in desc.args.iter() {
+                write!(ret, "- **{}**", argument_name(arg)).unwrap();
+
+                if !arg.is_required {
+                    ret.push_str(" (optional)");
+                }
+
+                write!(ret, ": {}", self.text(&arg.desc)).unwrap();
+
+                if let Some(hint) = user_hint(arg) {
+                    write!(ret, " (_{}_)", hint).unwrap();
+                }
+
+                ret.push('\n');
+            }
+        }
+
+        if !desc.examples.is_empty() {
+            ret.push_str("\n### Examples\n\n");
+
+            for example in desc.examples {
+                writeln!(ret, "- `{}{}`", self.prefix, example).unwrap();
+            }
+        }
+
+        for section in desc.sections {
+            write!(ret, "\n### {}\n\n", section.title()).unwrap();
+
+            match section {
+                DocSection::Text(_, text) => writeln!(ret, "{}", self.text(text)).unwrap(),
+                DocSection::SeeAlso(refs) => {
+                    for cmd in *refs {
+                        writeln!(ret, "- `{}{}`", self.prefix, cmd).unwrap();
+                    }
+                },
+            }
+        }
+
+        ret
+    }
+
+    fn command_set(&self, summary: Option<&HelpText>, commands: &[&CommandUsage]) -> String {
+        let mut ret = String::new();
+
+        if let Some(summary) = summary {
+            write!(ret, "{}\n\n", self.text(summary)).unwrap();
+        }
+
+        for cmd in commands {
+            writeln!(ret, "- **`{}`**: {}", usage_line(cmd), self.text(&cmd.desc)).unwrap();
+        }
+
+        ret
+    }
+
+    fn custom(&self, text: &HelpText) -> String { format!("{}\n", self.text(text)) }
+}
+
+/// Renders help as a Unix manual page, in `roff` format
+#[derive(Debug, Clone, Default)]
+pub struct ManPage {
+    /// The name of the program, used in page titles
+    pub name: String,
+    /// Prepended to commands referenced by help text, e.g. `!`
+    pub prefix: String,
+}
+
+/// Escape text for `roff`, so it is not read as requests or escapes
+fn roff(s: &str) -> String {
+    let s = s.replace('\\', "\\e").replace('-', "\\-");
+
+    s.lines()
+        .map(|l| {
+            if l.starts_with(&['.', '\''][..]) {
+                format!("\\&{}", l)
+            } else {
+                l.into()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+impl ManPage {
+    fn text(&self, text: &HelpText) -> String {
+        let mut ret = String::new();
+
+        for node in text.0.iter() {
+            match node {
+                HelpNode::Text(s) => ret.push_str(&roff(s)),
+                HelpNode::Link(l) => {
+                    write!(ret, "\\fB{}\\fR", roff(&format!("{}{}", self.prefix, l))).unwrap();
+                },
+            }
+        }
+
+        ret
+    }
+
+    fn title(&self, page: &str) -> String {
+        let title = if self.name.is_empty() {
+            page.into()
+        } else if page.is_empty() {
+            self.name.clone()
+        } else {
+            format!("{}-{}", self.name, page.replace(' ', "-"))
+        };
+
+        format!(".TH \"{}\" 1\n", roff(&title.to_uppercase()))
+    }
+}
+
+impl HelpRenderer for ManPage {
+    type Output = String;
+
+    fn command(&self, usage: &CommandUsage, desc: &CommandDesc) -> String {
+        let mut ret = self.title(&usage.ids[0]);
+
+        write!(
+            ret,
+            ".SH NAME\n{} \\- {}\n.SH SYNOPSIS\n.B {}\n",
+            roff(&usage.ids.join(", ")),
+            self.text(&usage.desc),
+            roff(&format!("{}{}", self.prefix, ids_usage(&usage.ids)))
+        )
+        .unwrap();
+
+        for arg in usage.args.iter() {
+            writeln!(ret, "{}", roff(&argument_usage(arg))).unwrap();
+        }
+
+        if let Some(ref summary) = desc.summary {
+            write!(ret, ".SH DESCRIPTION\n{}\n", self.text(summary)).unwrap();
+        }
+
+        if !desc.args.is_empty() {
+            ret.push_str(".SH ARGUMENTS\n");
+
+            for arg in desc.args.iter() {
+                write!(ret, ".TP\n.B {}\n", roff(&argument_name(arg))).unwrap();
+
+                if !arg.is_required {
+                    ret.push_str("(optional) ");
+                }
+
+                ret.push_str(&self.text(&arg.desc));
+
+                if let Some(hint) = user_hint(arg) {
+                    write!(ret, " (\\fI{}\\fR)", roff(&hint.to_string())).unwrap();
+                }
+
+                ret.push('\n');
+            }
+        }
+
+        if !desc.examples.is_empty() {
+            ret.push_str(".SH EXAMPLES\n.nf\n");
+
+            for example in desc.examples {
+                writeln!(ret, "{}", roff(&format!("{}{}", self.prefix, example))).unwrap();
+            }
+
+            ret.push_str(".fi\n");
+        }
+
+        for section in desc.sections {
+            writeln!(ret, ".SH {}", roff(&section.title().to_uppercase())).unwrap();
+
+            match section {
+                DocSection::Text(_, text) => writeln!(ret, "{}", self.text(text)).unwrap(),
+                DocSection::SeeAlso(refs) => {
+                    let pages: Vec<_> = refs
+                        .iter()
+                        .map(|cmd| {
+                            let page = match self.name.as_str() {
+                                "" => cmd.replace(' ', "-"),
+                                name => format!("{}-{}", name, cmd.replace(' ', "-")),
+                            };
+
+                            format!("\\fB{}\\fR(1)", roff(&page))
+                        })
+                        .collect();
+
+                    writeln!(ret, "{}", pages.join(",\n")).unwrap();
+                },
+            }
+        }
+
+        ret
+    }
+
+    fn command_set(&self, summary: Option<&HelpText>, commands: &[&CommandUsage]) -> String {
+        let mut ret = self.title("");
+
+        if let Some(summary) = summary {
+            write!(ret, ".SH DESCRIPTION\n{}\n", self.text(summary)).unwrap();
+        }
+
+        ret.push_str(".SH COMMANDS\n");
+
+        for cmd in commands {
+            write!(
+                ret,
+                ".TP\n.B {}\n{}\n",
+                roff(&usage_line(cmd)),
+                self.text(&cmd.desc)
+            )
+            .unwrap();
+        }
+
+        ret
+    }
+
+    fn custom(&self, text: &HelpText) -> String {
+        format!("{}.SH DESCRIPTION\n{}\n", self.title(""), self.text(text))
+    }
+}
@@ -0,0 +1,113 @@
+use crate::{ArgumentUsage, CommandDesc, CommandUsage, DocSection, Help, HelpText};
+
+/// The kind of value accepted by a command argument
+#[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum ArgumentKind {
+    /// Free-form text, converted to the argument's type when parsed
+    Text,
+    /// An option which is either present or absent
+    Flag,
+    /// One of a fixed set of names, such as the ID of a command
+    Choice(Vec<&'static str>),
+    /// A nested command, described by the given schema
+    Subcommand(CommandSetSchema),
+}
+
+/// Machine-readable description of a command argument
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ArgumentSchema {
+    /// Usage information for the argument
+    pub usage: &'static ArgumentUsage,
+    /// A detailed description of the argument, if one was given
+    pub desc: Option<HelpText>,
+    /// The kind of value the argument accepts
+    pub kind: ArgumentKind,
+}
+
+/// Machine-readable description of a single command
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct CommandSchema {
+    /// Usage information for the command
+    pub usage: &'static CommandUsage,
+    /// A detailed description of the command
+    pub desc: &'static CommandDesc,
+    /// Descriptions of the command's arguments, in the order they appear in
+    /// the usage line
+    pub args: Vec<ArgumentSchema>,
+}
+
+/// Machine-readable description of a tree of commands
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct CommandSetSchema {
+    /// A summary of the command set
+    pub summary: Option<HelpText>,
+    /// The commands in this set
+    pub commands: Vec<CommandSchema>,
+}
+
+impl CommandSetSchema {
+    /// Collect every link in the help text of this command set and all of its
+    /// subcommands
+    #[must_use]
+    pub fn links(&self) -> Vec<&str> {
+        let mut ret: Vec<_> = self.summary.iter().flat_map(HelpText::links).collect();
+
+        for cmd in &self.commands {
+            ret.extend(cmd.usage.desc.links());
+            ret.extend(cmd.desc.summary.iter().flat_map(HelpText::links));
+
+            for section in cmd.desc.sections {
+                if let DocSection::Text(_, text) = section {
+                    ret.extend(text.links());
+                }
+            }
+
+            for arg in &cmd.args {
+                ret.extend(arg.desc.iter().flat_map(HelpText::links));
+
+                if let ArgumentKind::Subcommand(ref set) = arg.kind {
+                    ret.extend(set.links());
+                }
+            }
+        }
+
+        ret
+    }
+
+    /// Collect every command path listed in a `See also` section of this
+    /// command set and all of its subcommands
+    #[must_use]
+    pub fn references(&self) -> Vec<&'static str> {
+        let mut ret = Vec::new();
+
+        for cmd in &self.commands {
+            for section in cmd.desc.sections {
+                if let DocSection::SeeAlso(refs) = section {
+                    ret.extend(refs.iter());
+                }
+            }
+
+            for arg in &cmd.args {
+                if let ArgumentKind::Subcommand(ref set) = arg.kind {
+                    ret.extend(set.references());
+                }
+            }
+        }
+
+        ret
+    }
+}
+
+/// A command that can describe its full command tree
+pub trait Schema: Help {
+    /// Describe this command and all of its subcommands
+    fn schema() -> CommandSetSchema;
+}
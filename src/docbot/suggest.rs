@@ -0,0 +1,115 @@
+//! Ranking of command names by how likely they are to be what the user meant
+
+/// Rows of a QWERTY keyboard, with the horizontal offset of each row
+const KEYBOARD: &[(f64, &str)] = &[
+    (0.0, "1234567890-="),
+    (0.5, "qwertyuiop[]"),
+    (0.75, "asdfghjkl;'"),
+    (1.25, "zxcvbnm,./"),
+];
+
+/// Cost of swapping two adjacent characters
+const TRANSPOSE_COST: f64 = 0.5;
+
+/// Cost of substituting a character for one on a neighboring key
+const NEIGHBOR_COST: f64 = 0.5;
+
+#[allow(clippy::cast_precision_loss)]
+fn key_pos(c: char) -> Option<(f64, f64)> {
+    KEYBOARD.iter().enumerate().find_map(|(y, (offset, row))| {
+        row.chars()
+            .position(|k| k == c)
+            .map(|x| (offset + x as f64, y as f64))
+    })
+}
+
+fn substitute_cost(a: char, b: char) -> f64 {
+    if a == b {
+        return 0.0;
+    }
+
+    match (key_pos(a), key_pos(b)) {
+        (Some((ax, ay)), Some((bx, by))) if (ax - bx).hypot(ay - by) <= 1.5 => NEIGHBOR_COST,
+        _ => 1.0,
+    }
+}
+
+/// Optimal string alignment distance, with typos on neighboring keys and
+/// swapped characters costing less than other edits
+#[allow(clippy::cast_precision_loss)]
+fn distance(a: &[char], b: &[char]) -> f64 {
+    let mut rows = vec![vec![0.0; b.len() + 1]; a.len() + 1];
+
+    for (i, row) in rows.iter_mut().enumerate() {
+        row[0] = i as f64;
+    }
+
+    for (j, cell) in rows[0].iter_mut().enumerate() {
+        *cell = j as f64;
+    }
+
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let mut d = (rows[i - 1][j] + 1.0)
+                .min(rows[i][j - 1] + 1.0)
+                .min(rows[i - 1][j - 1] + substitute_cost(a[i - 1], b[j - 1]));
+
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                d = d.min(rows[i - 2][j - 2] + TRANSPOSE_COST);
+            }
+
+            rows[i][j] = d;
+        }
+    }
+
+    rows[a.len()][b.len()]
+}
+
+/// Score how similar an input is to a name, from 0 to 1.  Since IDs can be
+/// abbreviated, the input is only compared against the start of the name.
+#[allow(clippy::cast_precision_loss)]
+pub fn similarity(input: &str, name: &str) -> f64 {
+    let input: Vec<_> = input.to_lowercase().chars().collect();
+    let name: Vec<_> = name.to_lowercase().chars().take(input.len() + 1).collect();
+    let len = input.len().max(name.len());
+
+    if len == 0 {
+        return 1.0;
+    }
+
+    1.0 - distance(&input, &name) / len as f64
+}
+
+/// List the names most similar to an input, best first.  Names are grouped by
+/// the command they belong to, and only the closest name of each command is
+/// listed, so aliases of one command don't crowd out other commands.
+pub fn rank<'a, S: AsRef<str>>(
+    input: &str,
+    groups: &'a [Vec<S>],
+    limit: usize,
+    threshold: f64,
+) -> Vec<&'a str>
+{
+    let mut scored: Vec<_> = groups
+        .iter()
+        .filter_map(|names| {
+            names
+                .iter()
+                .map(AsRef::as_ref)
+                .map(|n| (similarity(input, n), n))
+                .fold(None, |best: Option<(f64, &str)>, (s, n)| match best {
+                    Some((b, _)) if b >= s => best,
+                    _ => Some((s, n)),
+                })
+        })
+        .filter(|(s, _)| *s >= threshold)
+        .collect();
+
+    scored.sort_by(|(a, a_name), (b, b_name)| {
+        b.partial_cmp(a)
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then_with(|| a_name.cmp(b_name))
+    });
+
+    scored.into_iter().take(limit).map(|(_, n)| n).collect()
+}
@@ -0,0 +1,59 @@
+use docbot::prelude::*;
+
+#[derive(Docbot, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    /// on
+    /// Turn it on
+    On,
+    /// off
+    /// Turn it off
+    Off,
+}
+
+#[derive(Docbot, Debug, PartialEq, Eq)]
+pub enum ModeCommand {
+    /// (mark|set) <mode>
+    /// Set the mode
+    ///
+    /// # Arguments
+    /// mode: The mode to use
+    Mark(Mode),
+
+    /// (set default|default) <mode>
+    /// Set the default mode
+    ///
+    /// # Arguments
+    /// mode: The mode to use by default
+    Default(Mode),
+}
+
+#[test]
+fn completes_ids() {
+    assert_eq!(ModeCommand::complete([""]), ["mark", "set", "default"]);
+    assert_eq!(ModeCommand::complete(["s"]), ["set"]);
+}
+
+#[test]
+fn completes_both_ids_sharing_a_word() {
+    // `set` names `mark` on its own, and also starts `set default`
+    assert_eq!(
+        ModeCommand::complete(["set", ""]),
+        ["default", "on", "off"]
+    );
+    assert_eq!(ModeCommand::complete(["set", "o"]), ["on", "off"]);
+    assert_eq!(ModeCommand::complete(["set", "d"]), ["default"]);
+}
+
+#[test]
+fn completes_multi_word_ids() {
+    assert_eq!(
+        ModeCommand::complete(["set", "default", ""]),
+        ["on", "off"]
+    );
+    assert_eq!(ModeCommand::complete(["default", "of"]), ["off"]);
+}
+
+#[test]
+fn completes_nothing_after_the_last_argument() {
+    assert!(ModeCommand::complete(["mark", "on", ""]).is_empty());
+}
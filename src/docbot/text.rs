@@ -0,0 +1,126 @@
+use crate::{tokenize, Command, CommandParseError, Help, HelpPathError, Schema};
+use std::{
+    borrow::Cow,
+    fmt::{self, Display, Formatter},
+};
+use thiserror::Error;
+
+/// A segment of help text
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum HelpNode {
+    /// Plain text
+    Text(Cow<'static, str>),
+    /// A reference to another command, written as the input that would run it
+    /// starting from the root command
+    Link(Cow<'static, str>),
+}
+
+/// Help text which may contain references to other commands, written as
+/// ``[`command`]()`` in doc comments
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize), serde(transparent))]
+pub struct HelpText(pub Cow<'static, [HelpNode]>);
+
+impl HelpText {
+    /// Iterate over the commands referenced by this text
+    pub fn links(&self) -> impl Iterator<Item = &str> {
+        self.0.iter().filter_map(|n| match n {
+            HelpNode::Text(_) => None,
+            HelpNode::Link(l) => Some(l.as_ref()),
+        })
+    }
+}
+
+/// Renders the text with each link as its command in backticks
+impl Display for HelpText {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        for node in self.0.iter() {
+            match node {
+                HelpNode::Text(s) => f.write_str(s)?,
+                HelpNode::Link(l) => write!(f, "`{}`", l)?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Error type for a help link which does not refer to a valid command
+#[derive(Error, Debug)]
+#[error("invalid link to {link:?}: {error}")]
+pub struct LinkError {
+    /// The text of the link
+    pub link: String,
+    /// The error encountered parsing the link as a command
+    #[source]
+    pub error: CommandParseError,
+}
+
+/// Check that every link in the help text of a command tree parses as a
+/// command of its root.
+///
+/// # Errors
+/// Returns an error for each link that could not be parsed.
+pub fn check_links<R: Schema>() -> Result<(), Vec<LinkError>> {
+    let errors: Vec<_> = R::schema()
+        .links()
+        .into_iter()
+        .filter_map(|link| {
+            tokenize(link)
+                .map_err(Into::into)
+                .and_then(<R as Command>::parse)
+                .err()
+                .map(|error| LinkError {
+                    link: link.into(),
+                    error,
+                })
+        })
+        .collect();
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+/// Error type for a `See also` reference which does not name a valid command
+#[derive(Error, Debug)]
+#[error("invalid reference to {reference:?}: {error}")]
+pub struct ReferenceError {
+    /// The path of the referenced command
+    pub reference: &'static str,
+    /// The error encountered resolving the path to a help topic
+    #[source]
+    pub error: HelpPathError,
+}
+
+/// Check that every command listed in a `See also` section of a command tree
+/// names a help topic of its root.
+///
+/// # Errors
+/// Returns an error for each reference that could not be resolved.
+pub fn check_references<R: Schema>() -> Result<(), Vec<ReferenceError>> {
+    let errors: Vec<_> = R::schema()
+        .references()
+        .into_iter()
+        .filter_map(|reference| {
+            let path: Vec<_> = reference.split_whitespace().collect();
+
+            <R as Help>::help_path(&path)
+                .err()
+                .map(|error| ReferenceError { reference, error })
+        })
+        .collect();
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
@@ -0,0 +1,283 @@
+use std::{borrow::Cow, ops::Range};
+use thiserror::Error;
+
+/// Error type for failures when tokenizing command input
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum TokenizeError {
+    /// A quoted token was not closed before the end of the input
+    #[error("unterminated quote starting at byte {}", .0.start)]
+    UnterminatedQuote(Range<usize>),
+}
+
+/// A single token of command input, along with where it came from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Token<'a> {
+    /// The contents of the token, with quotes and escapes removed
+    pub text: Cow<'a, str>,
+    /// The byte range of the token in the input, including any quotes
+    pub span: Range<usize>,
+}
+
+impl AsRef<str> for Token<'_> {
+    fn as_ref(&self) -> &str { &self.text }
+}
+
+/// A string argument that may know where it came from in the original input
+pub trait Spanned: AsRef<str> {
+    /// The byte range of this argument in the input it was tokenized from, if
+    /// known
+    fn span(&self) -> Option<Range<usize>> { None }
+}
+
+impl Spanned for str {}
+impl Spanned for String {}
+impl Spanned for Box<str> {}
+impl Spanned for Cow<'_, str> {}
+
+impl Spanned for Token<'_> {
+    fn span(&self) -> Option<Range<usize>> { Some(self.span.clone()) }
+}
+
+impl<T: Spanned + ?Sized> Spanned for &T {
+    fn span(&self) -> Option<Range<usize>> { (*self).span() }
+}
+
+/// Split a string into whitespace-separated tokens.
+///
+/// Single-quoted tokens are taken literally, while double-quoted tokens may
+/// contain backslash escapes.  A quote in the middle of an unquoted token has
+/// no special meaning.
+///
+/// # Errors
+/// Returns an error if a quoted token is not terminated.
+pub fn tokenize(s: &str) -> Result<Vec<Token<'_>>, TokenizeError> {
+    let mut ret = Vec::new();
+    let mut chars = s.char_indices().peekable();
+
+    while let Some(&(start, c)) = chars.peek() {
+        chars.next();
+
+        if c.is_whitespace() {
+            continue;
+        }
+
+        let unterminated = || TokenizeError::UnterminatedQuote(start..s.len());
+
+        ret.push(match c {
+            '\'' => {
+                let end = loop {
+                    match chars.next() {
+                        Some((i, '\'')) => break i,
+                        Some(..) => (),
+                        None => return Err(unterminated()),
+                    }
+                };
+
+                Token {
+                    text: Cow::Borrowed(&s[start + 1..end]),
+                    span: start..end + 1,
+                }
+            },
+            '"' => {
+                let mut text = String::new();
+                let mut escaped = false;
+
+                let end = loop {
+                    match chars.next() {
+                        Some((i, '"')) => break i,
+                        Some((_, '\\')) => {
+                            text.push(chars.next().ok_or_else(unterminated)?.1);
+                            escaped = true;
+                        },
+                        Some((_, c)) => text.push(c),
+                        None => return Err(unterminated()),
+                    }
+                };
+
+                Token {
+                    text: if escaped {
+                        Cow::Owned(text)
+                    } else {
+                        Cow::Borrowed(&s[start + 1..end])
+                    },
+                    span: start..end + 1,
+                }
+            },
+            _ => {
+                let end = loop {
+                    match chars.peek() {
+                        Some(&(i, c)) if c.is_whitespace() => break i,
+                        Some(..) => {
+                            chars.next();
+                        },
+                        None => break s.len(),
+                    }
+                };
+
+                Token {
+                    text: Cow::Borrowed(&s[start..end]),
+                    span: start..end,
+                }
+            },
+        });
+    }
+
+    Ok(ret)
+}
+
+/// Quote a token if necessary, so that [`tokenize`] reads it back as a single
+/// token with the same contents
+#[must_use]
+pub fn quote(s: &str) -> Cow<'_, str> {
+    if !s.is_empty() && !s.starts_with(&['\'', '"'][..]) && !s.contains(char::is_whitespace) {
+        return Cow::Borrowed(s);
+    }
+
+    let mut ret = String::with_capacity(s.len() + 2);
+    ret.push('"');
+
+    for c in s.chars() {
+        if c == '"' || c == '\\' {
+            ret.push('\\');
+        }
+
+        ret.push(c);
+    }
+
+    ret.push('"');
+
+    Cow::Owned(ret)
+}
+
+/// Join a sequence of tokens into a string which [`tokenize`] splits back into
+/// the same tokens
+pub fn join_tokens<I: IntoIterator<Item = S>, S: AsRef<str>>(tokens: I) -> String {
+    let mut ret = String::new();
+
+    for (i, tok) in tokens.into_iter().enumerate() {
+        if i != 0 {
+            ret.push(' ');
+        }
+
+        ret.push_str(&quote(tok.as_ref()));
+    }
+
+    ret
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn texts(s: &str) -> Vec<String> {
+        tokenize(s)
+            .unwrap()
+            .into_iter()
+            .map(|t| t.text.into_owned())
+            .collect()
+    }
+
+    #[test]
+    fn splits_on_whitespace() {
+        let toks = tokenize("  foo\tbar\n baz ").unwrap();
+
+        assert_eq!(
+            toks.iter().map(|t| &*t.text).collect::<Vec<_>>(),
+            ["foo", "bar", "baz"]
+        );
+        assert_eq!(
+            toks.iter().map(|t| t.span.clone()).collect::<Vec<_>>(),
+            [2..5, 6..9, 11..14]
+        );
+    }
+
+    #[test]
+    fn quoted_tokens() {
+        let toks = tokenize(r#"'a b' "c d""#).unwrap();
+
+        assert_eq!(toks[0].text, "a b");
+        assert_eq!(toks[0].span, 0..5);
+        assert_eq!(toks[1].text, "c d");
+        assert_eq!(toks[1].span, 6..11);
+    }
+
+    #[test]
+    fn empty_tokens() {
+        assert_eq!(texts(r#"'' """#), ["", ""]);
+        assert!(tokenize("").unwrap().is_empty());
+        assert!(tokenize("   ").unwrap().is_empty());
+    }
+
+    #[test]
+    fn escapes() {
+        assert_eq!(texts(r#""a \"b\" \\c""#), [r#"a "b" \c"#]);
+        assert_eq!(texts(r#""\n""#), ["n"]);
+
+        // Escapes only apply inside double quotes
+        assert_eq!(texts(r"'a\b' a\b"), [r"a\b", r"a\b"]);
+    }
+
+    #[test]
+    fn escaped_tokens_are_owned() {
+        assert!(matches!(
+            tokenize(r#""a b""#).unwrap()[0].text,
+            Cow::Borrowed(_)
+        ));
+        assert!(matches!(
+            tokenize(r#""a\b""#).unwrap()[0].text,
+            Cow::Owned(_)
+        ));
+    }
+
+    #[test]
+    fn mid_token_quotes() {
+        assert_eq!(texts(r#"don't say "hi""#), ["don't", "say", "hi"]);
+        assert_eq!(texts(r#"a"b c'd"#), [r#"a"b"#, "c'd"]);
+    }
+
+    #[test]
+    fn unterminated_quotes() {
+        assert_eq!(
+            tokenize("foo 'bar baz"),
+            Err(TokenizeError::UnterminatedQuote(4..12))
+        );
+        assert_eq!(
+            tokenize(r#"foo "bar baz"#),
+            Err(TokenizeError::UnterminatedQuote(4..12))
+        );
+        assert_eq!(
+            tokenize(r#"foo "bar\"#),
+            Err(TokenizeError::UnterminatedQuote(4..9))
+        );
+        assert_eq!(
+            tokenize(r#"foo "bar\""#),
+            Err(TokenizeError::UnterminatedQuote(4..10))
+        );
+    }
+
+    #[test]
+    fn quote_only_when_needed() {
+        assert_eq!(quote("foo"), "foo");
+        assert_eq!(quote("don't"), "don't");
+        assert_eq!(quote(""), r#""""#);
+        assert_eq!(quote("a b"), r#""a b""#);
+        assert_eq!(quote("'a"), r#""'a""#);
+        assert_eq!(quote(r#""a\"#), r#""\"a\\""#);
+    }
+
+    #[test]
+    fn join_round_trips() {
+        let cases: &[&[&str]] = &[
+            &[],
+            &["foo", "bar"],
+            &["", "a b", "\t"],
+            &["'", "\"", "\\", r#"a"b"#, "c'd"],
+            &[r#""quoted""#, "'single'", r"back\slash", "new\nline"],
+            &["héllo wörld", "日本"],
+        ];
+
+        for &toks in cases {
+            assert_eq!(texts(&join_tokens(toks)), toks);
+        }
+    }
+}